@@ -2,8 +2,19 @@ extern crate autocfg;
 
 fn main() {
     let ac = autocfg::new();
-    // Allows `#[cfg(rustc_1_63)]` to be used in code
+    // Allows `#[cfg(rustc_1_XX)]` to be used in code for each of these versions
+    ac.emit_rustc_version(1, 46);
+    ac.emit_rustc_version(1, 51);
+    ac.emit_rustc_version(1, 55);
+    ac.emit_rustc_version(1, 56);
     ac.emit_rustc_version(1, 63);
+    ac.emit_rustc_version(1, 73);
+    ac.emit_rustc_version(1, 75);
+    ac.emit_rustc_version(1, 77);
+    ac.emit_rustc_version(1, 79);
+    ac.emit_rustc_version(1, 80);
+    ac.emit_rustc_version(1, 81);
+    ac.emit_rustc_version(1, 89);
 
     // Re-run if this file changes
     autocfg::rerun_path("build.rs");
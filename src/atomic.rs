@@ -0,0 +1,127 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+/// Atomically replace the contents of a file with a slice.
+///
+/// The contents are written to a temporary file in the same directory, which
+/// is synced to disk and then renamed over `path`. On Unix, the directory is
+/// also synced afterwards so that the rename itself is durable. Readers of
+/// `path` will therefore see either the old or the new contents, never a
+/// partially written file.
+///
+/// Errors identify the step which failed through their
+/// [`Operation`](crate::Operation), and the temporary file is removed if the
+/// contents could not be written. The new file is created with default
+/// permissions, rather than those of the file it replaces.
+///
+/// # Example
+///
+/// ```no_run
+/// fs_err::write_atomic("config.toml", "key = \"value\"\n")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+    let dir = parent_dir(path);
+
+    let (file, temp_path) = create_temp_file(dir, path)?;
+    let result = persist(file, &temp_path, path, contents);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    sync_dir(dir)
+}
+
+/// Syncs the directory containing `path` to disk.
+///
+/// Creating, renaming or removing a file changes its parent directory, so the
+/// change is not durable until the directory has been synced, even if the
+/// file itself has been. A path without a parent refers to a file in the
+/// current directory.
+///
+/// Directories cannot be synced on Windows and other non-Unix platforms, so
+/// this does nothing there.
+///
+/// # Example
+///
+/// ```no_run
+/// let file = fs_err::File::create("data.bin")?;
+/// file.sync_all()?;
+/// fs_err::fsync_parent_dir("data.bin")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn fsync_parent_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    sync_dir(parent_dir(path.as_ref()))
+}
+
+/// Writes `contents` to the temporary file, then renames it over `path`.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn persist(mut file: fs::File, temp_path: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    file.write_all(contents)
+        .with_write(ErrorKind::Write, temp_path, contents.len())?;
+    file.sync_all().with_path(ErrorKind::SyncFile, temp_path)?;
+    drop(file);
+    fs::rename(temp_path, path).with_paths(SourceDestErrorKind::Rename, temp_path, path)
+}
+
+/// Creates a new hidden file next to `path`, with a name which is unique
+/// within this process.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn create_temp_file(dir: &Path, path: &Path) -> io::Result<(fs::File, PathBuf)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path.file_name().unwrap_or_default();
+    loop {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_path = dir.join(temp_name);
+
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((file, temp_path)),
+            // Left behind by an earlier process with the same id, so try the next name.
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err).with_path(ErrorKind::CreateNewFile, &temp_path),
+        }
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if dir != Path::new("") => dir,
+        _ => Path::new("."),
+    }
+}
+
+#[cfg(unix)]
+#[cfg_attr(rustc_1_46, track_caller)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_path(ErrorKind::SyncDir, dir)
+}
+
+/// Directories cannot be opened as files on other platforms, so the rename is
+/// not made durable.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
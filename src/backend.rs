@@ -0,0 +1,140 @@
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The filesystem operations of fs-err, as a trait which can be replaced in
+/// tests.
+///
+/// Code which takes an `&dyn FsBackend`, or is generic over `FsBackend`, can
+/// use [`RealFs`] in production and a fake in tests, such as
+/// [`MemoryFs`](crate::MemoryFs) with the `testing` feature, to exercise error
+/// paths like a missing file or a full disk without touching the real disk.
+/// Implementations should return errors built by fs-err, or in the same
+/// format, so that messages look the same either way.
+///
+/// # Example
+///
+/// ```no_run
+/// use fs_err::{FsBackend, RealFs};
+/// use std::path::Path;
+///
+/// fn load_config(fs: &dyn FsBackend, dir: &Path) -> std::io::Result<String> {
+///     fs.read_to_string(&dir.join("config.toml"))
+/// }
+///
+/// let config = load_config(&RealFs, Path::new("/etc/myapp"))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait FsBackend {
+    /// Reads the entire contents of a file into a bytes vector.
+    ///
+    /// See [`crate::read`].
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    /// Reads the entire contents of a file into a string.
+    ///
+    /// See [`crate::read_to_string`].
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// Writes a slice as the entire contents of a file, creating it if it does
+    /// not exist.
+    ///
+    /// See [`crate::write`].
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    /// Opens a file for reading.
+    ///
+    /// See [`crate::File::open`].
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>>;
+    /// Returns the paths of the entries within a directory, sorted by name.
+    ///
+    /// See [`crate::read_dir`].
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    /// Creates a new, empty directory.
+    ///
+    /// See [`crate::create_dir`].
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+    /// Creates a directory and all of its missing parents.
+    ///
+    /// See [`crate::create_dir_all`].
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    /// Removes a file.
+    ///
+    /// See [`crate::remove_file`].
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    /// Removes an empty directory.
+    ///
+    /// See [`crate::remove_dir`].
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    /// Renames a file or directory, replacing `to` if it is a file.
+    ///
+    /// See [`crate::rename`].
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    /// Copies the contents of a file to another, returning the number of bytes
+    /// copied.
+    ///
+    /// See [`crate::copy`].
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64>;
+}
+
+/// The [`FsBackend`] which uses the real filesystem through the functions of
+/// this crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl FsBackend for RealFs {
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        crate::read(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        crate::read_to_string(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        crate::write(path, contents)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(crate::File::open(path)?))
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = crate::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        crate::create_dir(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        crate::create_dir_all(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        crate::remove_file(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        crate::remove_dir(path)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        crate::rename(from, to)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        crate::copy(from, to)
+    }
+}
@@ -0,0 +1,507 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorKind, ResultExt};
+use crate::temp::temp_name;
+
+/// A list of filesystem operations which are all attempted, even if some of
+/// them fail.
+///
+/// Operations are performed in the order they were added by
+/// [`execute`](Batch::execute). Instead of stopping at the first error, every
+/// operation is attempted and all of the errors are returned together in a
+/// [`MultiError`], so that a program such as an installer can report
+/// everything that went wrong at once. Note that an operation which depends on
+/// an earlier one that failed, such as copying a file into a directory which
+/// could not be created, usually fails too.
+///
+/// Alternatively, [`execute_transaction`](Batch::execute_transaction) stops at
+/// the first error and undoes the operations which were already performed.
+///
+/// # Example
+///
+/// ```no_run
+/// let result = fs_err::Batch::new()
+///     .create_dir_all("/opt/app/bin")
+///     .copy("build/app", "/opt/app/bin/app")
+///     .copy("build/app.conf", "/etc/app.conf")
+///     .remove_file("/opt/app/bin/app.old")
+///     .execute();
+/// if let Err(err) = result {
+///     // For example:
+///     // 2 of 4 operations failed:
+///     //   failed to copy file from build/app.conf to /etc/app.conf: ...
+///     //   failed to remove file `/opt/app/bin/app.old`: ...
+///     eprintln!("{}", err);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Batch {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Copy(PathBuf, PathBuf),
+    Rename(PathBuf, PathBuf),
+    RemoveFile(PathBuf),
+    RemoveDir(PathBuf),
+    RemoveDirAll(PathBuf),
+    CreateDir(PathBuf),
+    CreateDirAll(PathBuf),
+}
+
+impl Batch {
+    /// Creates an empty batch.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Batch { steps: Vec::new() }
+    }
+
+    /// Adds copying the file `from` to `to`, as with [`copy`](crate::copy).
+    pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) -> &mut Self {
+        self.push(Step::Copy(from.as_ref().to_owned(), to.as_ref().to_owned()))
+    }
+
+    /// Adds renaming `from` to `to`, as with [`rename`](crate::rename).
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) -> &mut Self {
+        self.push(Step::Rename(
+            from.as_ref().to_owned(),
+            to.as_ref().to_owned(),
+        ))
+    }
+
+    /// Adds removing the file `path`, as with
+    /// [`remove_file`](crate::remove_file).
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.push(Step::RemoveFile(path.as_ref().to_owned()))
+    }
+
+    /// Adds removing the empty directory `path`, as with
+    /// [`remove_dir`](crate::remove_dir).
+    pub fn remove_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.push(Step::RemoveDir(path.as_ref().to_owned()))
+    }
+
+    /// Adds removing the directory `path` and its contents, as with
+    /// [`remove_dir_all`](crate::remove_dir_all).
+    pub fn remove_dir_all<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.push(Step::RemoveDirAll(path.as_ref().to_owned()))
+    }
+
+    /// Adds creating the directory `path`, as with
+    /// [`create_dir`](crate::create_dir).
+    pub fn create_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.push(Step::CreateDir(path.as_ref().to_owned()))
+    }
+
+    /// Adds creating the directory `path` and its missing parents, as with
+    /// [`create_dir_all`](crate::create_dir_all).
+    pub fn create_dir_all<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.push(Step::CreateDirAll(path.as_ref().to_owned()))
+    }
+
+    /// Returns the number of operations in the batch.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns whether the batch contains no operations.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Performs every operation in the batch in order, returning the errors
+    /// from all of the operations which failed.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn execute(&self) -> Result<(), MultiError> {
+        let mut errors = Vec::new();
+        for step in &self.steps {
+            if let Err(err) = step.execute() {
+                errors.push(err);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(MultiError {
+                errors,
+                total: self.steps.len(),
+            })
+        }
+    }
+
+    /// Performs the operations in the batch in order, stopping at the first
+    /// one which fails and undoing the operations already performed, so that
+    /// either all of the changes are made or none of them are.
+    ///
+    /// To make this possible, files and directories which are removed or
+    /// replaced are first renamed to a backup next to them, which is removed
+    /// once every operation has succeeded, or renamed back if one fails. The
+    /// directories created by [`create_dir_all`](Batch::create_dir_all) are
+    /// recorded so that only the ones which did not already exist are removed.
+    /// The returned error includes both the error which caused the changes to
+    /// be rolled back and any errors from rolling them back.
+    ///
+    /// This is not atomic: other processes can observe the changes before they
+    /// are committed or rolled back, and if the process exits in between, the
+    /// backups are left behind with names starting with `.tmp`.
+    ///
+    /// ```no_run
+    /// let result = fs_err::Batch::new()
+    ///     .rename("/opt/app/current", "/opt/app/previous")
+    ///     .rename("/opt/app/staged", "/opt/app/current")
+    ///     .execute_transaction();
+    /// if let Err(err) = result {
+    ///     // For example:
+    ///     // failed to rename file from /opt/app/staged to /opt/app/current: ...
+    ///     // (all changes were rolled back)
+    ///     eprintln!("{}", err);
+    /// }
+    /// ```
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn execute_transaction(&self) -> Result<(), TransactionError> {
+        let mut transaction = Transaction {
+            undo: Vec::new(),
+            backups: Vec::new(),
+        };
+        for step in &self.steps {
+            if let Err(error) = transaction.execute(step) {
+                return Err(TransactionError {
+                    error,
+                    rollback_errors: transaction.roll_back(),
+                    committed: false,
+                });
+            }
+        }
+        transaction.commit().map_err(|error| TransactionError {
+            error,
+            rollback_errors: Vec::new(),
+            committed: true,
+        })
+    }
+
+    fn push(&mut self, step: Step) -> &mut Self {
+        self.steps.push(step);
+        self
+    }
+}
+
+impl Step {
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn execute(&self) -> io::Result<()> {
+        match self {
+            Step::Copy(from, to) => crate::copy(from, to).map(|_| ()),
+            Step::Rename(from, to) => crate::rename(from, to),
+            Step::RemoveFile(path) => crate::remove_file(path),
+            Step::RemoveDir(path) => crate::remove_dir(path),
+            Step::RemoveDirAll(path) => crate::remove_dir_all(path),
+            Step::CreateDir(path) => crate::create_dir(path),
+            Step::CreateDirAll(path) => crate::create_dir_all(path),
+        }
+    }
+}
+
+/// A change made by a transaction, which is undone if a later operation fails.
+#[derive(Debug)]
+enum Undo {
+    /// A directory which was created.
+    RemoveDir(PathBuf),
+    /// A file which may have been created by copying to it.
+    RemoveFile(PathBuf),
+    /// A directory which was removed.
+    CreateDir(PathBuf),
+    /// A file or directory which was renamed from `to` to `from`, either by
+    /// the operation itself or to back it up.
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+/// The state of a [`Batch`] being executed as a transaction.
+struct Transaction {
+    undo: Vec<Undo>,
+    /// Backups of removed or replaced files and directories, along with
+    /// whether they are directories, which are removed when the transaction is
+    /// committed.
+    backups: Vec<(PathBuf, bool)>,
+}
+
+impl Transaction {
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn execute(&mut self, step: &Step) -> io::Result<()> {
+        match step {
+            Step::Copy(from, to) => {
+                if is_dir(to) {
+                    // The copy fails without writing anything.
+                    return crate::copy(from, to).map(|_| ());
+                }
+                self.back_up(to, ErrorKind::CreateFile)?;
+                // A partially copied file is removed if the copy fails.
+                self.undo.push(Undo::RemoveFile(to.clone()));
+                crate::copy(from, to).map(|_| ())
+            }
+            Step::Rename(from, to) => {
+                if rename_replaces(from, to) {
+                    self.back_up(to, ErrorKind::RemoveFile)?;
+                }
+                crate::rename(from, to)?;
+                self.undo.push(Undo::Rename {
+                    from: to.clone(),
+                    to: from.clone(),
+                });
+                Ok(())
+            }
+            Step::RemoveFile(path) => {
+                // Let the real operation fail if this is a directory, or if
+                // there is nothing to remove.
+                match fs::symlink_metadata(path) {
+                    Ok(metadata) if !metadata.is_dir() => self.back_up(path, ErrorKind::RemoveFile),
+                    _ => crate::remove_file(path),
+                }
+            }
+            Step::RemoveDir(path) => {
+                crate::remove_dir(path)?;
+                self.undo.push(Undo::CreateDir(path.clone()));
+                Ok(())
+            }
+            Step::RemoveDirAll(path) => match fs::symlink_metadata(path) {
+                Ok(ref metadata) if metadata.is_dir() || metadata.file_type().is_symlink() => {
+                    self.back_up(path, ErrorKind::RemoveDir)
+                }
+                _ => crate::remove_dir_all(path),
+            },
+            Step::CreateDir(path) => {
+                crate::create_dir(path)?;
+                self.undo.push(Undo::RemoveDir(path.clone()));
+                Ok(())
+            }
+            Step::CreateDirAll(path) => {
+                // Create the missing directories one at a time, so that only
+                // those are removed if a later operation fails.
+                let mut missing = Vec::new();
+                for ancestor in path.ancestors() {
+                    if ancestor.as_os_str().is_empty() || is_dir(ancestor) {
+                        break;
+                    }
+                    missing.push(ancestor);
+                }
+                for dir in missing.into_iter().rev() {
+                    crate::create_dir(dir)?;
+                    self.undo.push(Undo::RemoveDir(dir.to_owned()));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Renames `path` to a backup next to it, if it exists. Errors are
+    /// reported as the operation `kind` on `path`, since that is the operation
+    /// which would otherwise have failed.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn back_up(&mut self, path: &Path, kind: ErrorKind) -> io::Result<()> {
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err).with_path(ErrorKind::Metadata, path),
+        };
+        let backup = path.with_file_name(temp_name());
+        fs::rename(path, &backup).with_path(kind, path)?;
+        self.undo.push(Undo::Rename {
+            from: backup.clone(),
+            to: path.to_owned(),
+        });
+        self.backups.push((backup, metadata.is_dir()));
+        Ok(())
+    }
+
+    /// Undoes the changes in reverse order, returning any errors.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn roll_back(self) -> Vec<io::Error> {
+        let mut errors = Vec::new();
+        for undo in self.undo.into_iter().rev() {
+            let result = match undo {
+                Undo::RemoveDir(path) => crate::remove_dir(path),
+                Undo::RemoveFile(path) => match crate::remove_file(path) {
+                    Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                    result => result,
+                },
+                Undo::CreateDir(path) => crate::create_dir(path),
+                Undo::Rename { from, to } => crate::rename(from, to),
+            };
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+
+    /// Removes the backups once every operation has succeeded.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn commit(self) -> io::Result<()> {
+        for (backup, is_dir) in self.backups {
+            if is_dir {
+                crate::remove_dir_all(backup)?;
+            } else {
+                crate::remove_file(backup)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether renaming `from` to `to` would replace `to`, so that it must
+/// be backed up first. Files and symbolic links replace each other, and on Unix
+/// a directory replaces an empty directory. Otherwise the rename fails, and
+/// backing up `to` would let it succeed.
+fn rename_replaces(from: &Path, to: &Path) -> bool {
+    let is_real_dir = |path| fs::symlink_metadata(path).map(|metadata| metadata.is_dir());
+    match (is_real_dir(from), is_real_dir(to)) {
+        (Ok(false), Ok(false)) => true,
+        (Ok(true), Ok(true)) => {
+            cfg!(unix)
+                && fs::read_dir(to)
+                    .map(|mut entries| entries.next().is_none())
+                    .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `path` is a directory, following symbolic links.
+fn is_dir(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|metadata| metadata.is_dir())
+        .unwrap_or(false)
+}
+
+/// The error returned when [`Batch::execute_transaction`] fails.
+///
+/// This converts into an [`io::Error`] with `?`, which has the kind of the
+/// error which caused the transaction to fail.
+#[derive(Debug)]
+pub struct TransactionError {
+    error: io::Error,
+    rollback_errors: Vec<io::Error>,
+    committed: bool,
+}
+
+impl TransactionError {
+    /// Returns the error which caused the transaction to fail.
+    ///
+    /// If the transaction was [committed](TransactionError::is_committed), this
+    /// is the error from removing a backup instead.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the errors from undoing the changes, in the order they were
+    /// undone. This is empty if every change was rolled back.
+    pub fn rollback_errors(&self) -> &[io::Error] {
+        &self.rollback_errors
+    }
+
+    /// Returns whether every change was rolled back, leaving the filesystem as
+    /// it was before the transaction.
+    pub fn is_rolled_back(&self) -> bool {
+        !self.committed && self.rollback_errors.is_empty()
+    }
+
+    /// Returns whether every operation succeeded, but a backup of a removed or
+    /// replaced file could not be removed afterwards.
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    /// Returns the error which caused the transaction to fail.
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.committed {
+            write!(
+                formatter,
+                "all changes were made, but a backup could not be removed: "
+            )?;
+            return fmt::Display::fmt(&self.error, formatter);
+        }
+
+        fmt::Display::fmt(&self.error, formatter)?;
+        if self.rollback_errors.is_empty() {
+            return write!(formatter, " (all changes were rolled back)");
+        }
+        let count = self.rollback_errors.len();
+        let changes = if count == 1 { "change" } else { "changes" };
+        write!(
+            formatter,
+            "\n{} {} could not be rolled back:",
+            count, changes
+        )?;
+        for err in &self.rollback_errors {
+            write!(formatter, "\n  ")?;
+            fmt::Display::fmt(err, formatter)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for TransactionError {}
+
+impl From<TransactionError> for io::Error {
+    fn from(err: TransactionError) -> io::Error {
+        io::Error::new(err.error.kind(), err)
+    }
+}
+
+/// The errors from the operations in a [`Batch`] which failed.
+///
+/// Each error includes the paths of the operation it came from, in the same
+/// way as the errors returned by the individual functions. This converts into
+/// an [`io::Error`] with `?`, which has the kind of the first error and
+/// displays all of them.
+#[derive(Debug)]
+pub struct MultiError {
+    errors: Vec<io::Error>,
+    total: usize,
+}
+
+impl MultiError {
+    /// Returns the errors, in the order the operations were performed.
+    pub fn errors(&self) -> &[io::Error] {
+        &self.errors
+    }
+
+    /// Returns the errors, in the order the operations were performed.
+    pub fn into_errors(self) -> Vec<io::Error> {
+        self.errors
+    }
+}
+
+impl fmt::Display for MultiError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} of {} operations failed:",
+            self.errors.len(),
+            self.total
+        )?;
+        for err in &self.errors {
+            write!(formatter, "\n  ")?;
+            fmt::Display::fmt(err, formatter)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for MultiError {}
+
+impl From<MultiError> for io::Error {
+    fn from(err: MultiError) -> io::Error {
+        io::Error::new(err.errors[0].kind(), err)
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::File;
+
+/// Wrapper around [`std::io::BufReader`][std::io::BufReader] for a
+/// [`File`](crate::File), which keeps the path of the file available.
+///
+/// Errors from reading already include the path, since they come from the
+/// fs-err [`File`](crate::File). This type additionally exposes the path
+/// through [`path`](BufReader::path), for code which only has access to the
+/// reader.
+///
+/// [std::io::BufReader]: https://doc.rust-lang.org/stable/std/io/struct.BufReader.html
+pub struct BufReader {
+    inner: io::BufReader<File>,
+}
+
+impl BufReader {
+    /// Opens a file in read-only mode and wraps it in a buffered reader.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        File::open(path).map(BufReader::new)
+    }
+
+    /// Creates a new buffered reader with a default buffer capacity.
+    ///
+    /// Wrapper for [`BufReader::new`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.new).
+    pub fn new(file: File) -> Self {
+        BufReader {
+            inner: io::BufReader::new(file),
+        }
+    }
+
+    /// Creates a new buffered reader with the specified buffer capacity.
+    ///
+    /// Wrapper for [`BufReader::with_capacity`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.with_capacity).
+    pub fn with_capacity(capacity: usize, file: File) -> Self {
+        BufReader {
+            inner: io::BufReader::with_capacity(capacity, file),
+        }
+    }
+
+    /// Returns the path of the underlying file.
+    pub fn path(&self) -> &Path {
+        self.inner.get_ref().path()
+    }
+
+    /// Returns a reference to the underlying file.
+    ///
+    /// Wrapper for [`BufReader::get_ref`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.get_ref).
+    pub fn get_ref(&self) -> &File {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    ///
+    /// Reading directly from the file may cause data to be skipped or read out
+    /// of order.
+    ///
+    /// Wrapper for [`BufReader::get_mut`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.get_mut).
+    pub fn get_mut(&mut self) -> &mut File {
+        self.inner.get_mut()
+    }
+
+    /// Returns the data which has been read from the file but not yet consumed.
+    ///
+    /// Wrapper for [`BufReader::buffer`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.buffer).
+    pub fn buffer(&self) -> &[u8] {
+        self.inner.buffer()
+    }
+
+    /// Unwraps this reader, returning the underlying file. Any buffered data
+    /// is lost.
+    ///
+    /// Wrapper for [`BufReader::into_inner`](https://doc.rust-lang.org/stable/std/io/struct.BufReader.html#method.into_inner).
+    pub fn into_inner(self) -> File {
+        self.inner.into_inner()
+    }
+}
+
+impl fmt::Debug for BufReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReader")
+            .field("path", &self.path())
+            .field("buffer", &self.inner.buffer().len())
+            .finish()
+    }
+}
+
+impl Read for BufReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.inner.read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.inner.read_to_string(buf)
+    }
+}
+
+impl BufRead for BufReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+impl Seek for BufReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
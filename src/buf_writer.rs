@@ -0,0 +1,131 @@
+use std::fmt;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::File;
+
+/// Wrapper around [`std::io::BufWriter`][std::io::BufWriter] for a
+/// [`File`](crate::File), which keeps the path of the file available.
+///
+/// Errors from writing, including those from flushing the buffer in
+/// [`into_inner`](BufWriter::into_inner), include the path since they come
+/// from the fs-err [`File`](crate::File). As with the standard library, errors
+/// from the final flush when the writer is dropped are ignored, so call
+/// [`flush`](Write::flush) to observe them.
+///
+/// [std::io::BufWriter]: https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html
+pub struct BufWriter {
+    inner: io::BufWriter<File>,
+}
+
+impl BufWriter {
+    /// Opens a file in write-only mode and wraps it in a buffered writer.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create<P>(path: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        File::create(path).map(BufWriter::new)
+    }
+
+    /// Creates a new buffered writer with a default buffer capacity.
+    ///
+    /// Wrapper for [`BufWriter::new`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.new).
+    pub fn new(file: File) -> Self {
+        BufWriter {
+            inner: io::BufWriter::new(file),
+        }
+    }
+
+    /// Creates a new buffered writer with the specified buffer capacity.
+    ///
+    /// Wrapper for [`BufWriter::with_capacity`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.with_capacity).
+    pub fn with_capacity(capacity: usize, file: File) -> Self {
+        BufWriter {
+            inner: io::BufWriter::with_capacity(capacity, file),
+        }
+    }
+
+    /// Returns the path of the underlying file.
+    pub fn path(&self) -> &Path {
+        self.inner.get_ref().path()
+    }
+
+    /// Returns a reference to the underlying file.
+    ///
+    /// Wrapper for [`BufWriter::get_ref`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.get_ref).
+    pub fn get_ref(&self) -> &File {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    ///
+    /// Writing directly to the file may cause data to be written out of order.
+    ///
+    /// Wrapper for [`BufWriter::get_mut`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.get_mut).
+    pub fn get_mut(&mut self) -> &mut File {
+        self.inner.get_mut()
+    }
+
+    /// Returns the data which has been written but not yet flushed to the file.
+    ///
+    /// Wrapper for [`BufWriter::buffer`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.buffer).
+    pub fn buffer(&self) -> &[u8] {
+        self.inner.buffer()
+    }
+
+    /// Flushes the buffer and returns the underlying file.
+    ///
+    /// If flushing fails, the error contains the standard library's writer, so
+    /// that the buffered data is not lost.
+    ///
+    /// Wrapper for [`BufWriter::into_inner`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.into_inner).
+    pub fn into_inner(self) -> Result<File, io::IntoInnerError<io::BufWriter<File>>> {
+        self.inner.into_inner()
+    }
+
+    /// Returns the underlying file and any data which has not been flushed to
+    /// it, without flushing.
+    ///
+    /// The buffered data is `Err` if a previous write panicked, in which case
+    /// it may be incomplete.
+    ///
+    /// Wrapper for [`BufWriter::into_parts`](https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html#method.into_parts).
+    #[cfg(rustc_1_56)]
+    pub fn into_parts(self) -> (File, Result<Vec<u8>, io::WriterPanicked>) {
+        self.inner.into_parts()
+    }
+}
+
+impl fmt::Debug for BufWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufWriter")
+            .field("path", &self.path())
+            .field("buffer", &self.inner.buffer().len())
+            .finish()
+    }
+}
+
+impl Write for BufWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Seek for BufWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
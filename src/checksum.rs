@@ -0,0 +1,146 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// A hash function used to compute the checksum of a file's contents.
+///
+/// fs-err does not provide any hash functions itself, so that you can use
+/// whichever one you already depend on. Implementations for the hashers of
+/// other crates are usually a few lines long.
+///
+/// # Example
+///
+/// An implementation of 64-bit FNV-1a, which is fast but not
+/// cryptographically secure:
+///
+/// ```
+/// #[derive(Default)]
+/// struct Fnv1a(Option<u64>);
+///
+/// impl fs_err::Digest for Fnv1a {
+///     type Output = [u8; 8];
+///
+///     fn update(&mut self, data: &[u8]) {
+///         let mut hash = self.0.unwrap_or(0xcbf29ce484222325);
+///         for &byte in data {
+///             hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+///         }
+///         self.0 = Some(hash);
+///     }
+///
+///     fn finalize(self) -> [u8; 8] {
+///         self.0.unwrap_or(0xcbf29ce484222325).to_be_bytes()
+///     }
+/// }
+/// ```
+pub trait Digest: Default {
+    /// The checksum computed by this hash function.
+    type Output: AsRef<[u8]>;
+
+    /// Adds `data` to the input of the hash function.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the checksum of all the data passed to [`update`](Digest::update).
+    fn finalize(self) -> Self::Output;
+}
+
+/// Read the entire contents of a file into a bytes vector, along with its
+/// checksum computed using `D`.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_with_checksum<D: Digest, P: AsRef<Path>>(path: P) -> io::Result<(Vec<u8>, D::Output)> {
+    let path = path.as_ref();
+    let mut file = fs::File::open(path).with_path(ErrorKind::OpenFile, path)?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)
+        .with_path(ErrorKind::Read, path)?;
+    let mut digest = D::default();
+    digest.update(&contents);
+    Ok((contents, digest.finalize()))
+}
+
+/// Read the entire contents of a file into a bytes vector, checking that its
+/// checksum computed using `D` is `expected`.
+///
+/// If the checksum does not match, the error has kind
+/// [`io::ErrorKind::InvalidData`] and its message includes both checksums in
+/// hexadecimal.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn verify_checksum<D: Digest, P: AsRef<Path>>(path: P, expected: &[u8]) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let (contents, actual) = read_with_checksum::<D, _>(path)?;
+    check(expected, actual.as_ref(), path)?;
+    Ok(contents)
+}
+
+/// Write a slice as the entire contents of a file, returning its checksum
+/// computed using `D`.
+///
+/// After the file is written and synced to disk, it is read back and its
+/// checksum compared with that of `contents`, so that the returned checksum is
+/// known to match the file. If they differ, the error has kind
+/// [`io::ErrorKind::InvalidData`] and its message includes both checksums in
+/// hexadecimal.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write_with_checksum<D: Digest, P: AsRef<Path>, C: AsRef<[u8]>>(
+    path: P,
+    contents: C,
+) -> io::Result<D::Output> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+
+    let mut digest = D::default();
+    digest.update(contents);
+    let expected = digest.finalize();
+
+    let mut file = fs::File::create(path).with_path(ErrorKind::CreateFile, path)?;
+    file.write_all(contents)
+        .with_write(ErrorKind::Write, path, contents.len())?;
+    file.sync_all().with_path(ErrorKind::SyncFile, path)?;
+    drop(file);
+
+    let actual = fs::File::open(path)
+        .and_then(hash::<D, _>)
+        .with_path(ErrorKind::ReadBack, path)?;
+    check(expected.as_ref(), actual.as_ref(), path)?;
+    Ok(expected)
+}
+
+/// Computes the checksum of everything read from `reader`, without holding it
+/// all in memory.
+fn hash<D: Digest, R: Read>(mut reader: R) -> io::Result<D::Output> {
+    let mut digest = D::default();
+    let mut buf = [0; 8 * 1024];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(digest.finalize()),
+            Ok(read) => digest.update(&buf[..read]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg_attr(rustc_1_46, track_caller)]
+fn check(expected: &[u8], actual: &[u8], path: &Path) -> io::Result<()> {
+    if expected == actual {
+        return Ok(());
+    }
+    let message = format!("expected {}, found {}", Hex(expected), Hex(actual));
+    Err(io::Error::new(io::ErrorKind::InvalidData, message))
+        .with_path(ErrorKind::ChecksumMismatch, path)
+}
+
+/// Formats bytes as lowercase hexadecimal.
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
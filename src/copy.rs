@@ -0,0 +1,424 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+use crate::temp::temp_name;
+
+/// Copies the contents of one file to another, preserving its permissions and
+/// its access and modification times.
+///
+/// Like [`copy`](crate::copy), this returns the number of bytes copied. If the
+/// contents were copied but the times could not be preserved, the error
+/// identifies whether reading the times of `from` or setting them on `to`
+/// failed. On Linux with the `libc` feature,
+/// `os::unix::fs::copy_preserving_xattrs` also preserves extended attributes.
+#[cfg(rustc_1_75)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn copy_preserving<P, Q>(from: P, to: Q) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+    let len = fs::copy(from, to).with_paths(SourceDestErrorKind::Copy, from, to)?;
+    copy_times(from, to)?;
+    Ok(len)
+}
+
+#[cfg(rustc_1_75)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub(crate) fn copy_times(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::metadata(from).with_path(ErrorKind::Metadata, from)?;
+    let accessed = metadata.accessed().with_path(ErrorKind::Metadata, from)?;
+    let modified = metadata.modified().with_path(ErrorKind::Metadata, from)?;
+    let times = fs::FileTimes::new()
+        .set_accessed(accessed)
+        .set_modified(modified);
+
+    open_for_times(to)
+        .with_path(ErrorKind::OpenFile, to)?
+        .set_times(times)
+        .with_path(ErrorKind::SetTimes, to)
+}
+
+/// Opens a file so that its times can be set, even if the permissions copied
+/// to it make it read-only.
+#[cfg(all(rustc_1_75, windows))]
+pub(crate) fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_WRITE_ATTRIBUTES: u32 = 0x100;
+    fs::OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .open(path)
+}
+
+/// Opens a file so that its times can be set, even if the permissions copied
+/// to it make it read-only.
+#[cfg(all(rustc_1_75, not(windows)))]
+pub(crate) fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    fs::File::open(path)
+}
+
+/// Recursively copies the contents of the directory `from` into `to`,
+/// creating `to` and its parents if they are missing.
+///
+/// This uses the default [`CopyDirOptions`]: symbolic links are copied as
+/// links, and existing files in `to` are replaced. Errors include the path of
+/// the entry within the tree which could not be copied. Fails with an error of
+/// kind [`io::ErrorKind::InvalidInput`], before creating anything, if `to` is
+/// inside `from`.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn copy_dir_all<P, Q>(from: P, to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    CopyDirOptions::new().copy(from, to)
+}
+
+/// What [`CopyDirOptions::copy`] does when a file it is copying already exists
+/// in the destination.
+///
+/// Existing directories are always merged with the directory being copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    /// Replace the existing file.
+    Replace,
+    /// Leave the existing file unchanged.
+    Skip,
+    /// Fail with an error of kind [`io::ErrorKind::AlreadyExists`].
+    Fail,
+}
+
+pub(crate) type Filter<'a> = Box<dyn Fn(&Path) -> bool + 'a>;
+
+/// Options for recursively copying a directory.
+///
+/// # Example
+///
+/// ```no_run
+/// use fs_err::{CopyDirOptions, Overwrite};
+///
+/// CopyDirOptions::new()
+///     .overwrite(Overwrite::Skip)
+///     .filter(|path| path.extension() != Some("tmp".as_ref()))
+///     .copy("assets", "target/assets")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct CopyDirOptions<'a> {
+    follow_symlinks: bool,
+    overwrite: Overwrite,
+    filter: Option<Filter<'a>>,
+}
+
+impl<'a> CopyDirOptions<'a> {
+    /// Creates options which copy symbolic links as links, replace existing
+    /// files and copy every entry.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        CopyDirOptions {
+            follow_symlinks: false,
+            overwrite: Overwrite::Replace,
+            filter: None,
+        }
+    }
+
+    /// Sets whether symbolic links are followed, copying the file or directory
+    /// they point to, rather than being recreated as links.
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Sets what happens when a file being copied already exists.
+    pub fn overwrite(&mut self, overwrite: Overwrite) -> &mut Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets a callback which is passed the source path of each entry, and
+    /// returns `false` to skip it. Skipping a directory skips all of its
+    /// contents.
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&Path) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Recursively copies the contents of the directory `from` into `to` with
+    /// these options, creating `to` and its parents if they are missing.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn copy<P, Q>(&self, from: P, to: Q) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        check_not_inside(from, to)?;
+        crate::create_dir_all(to)?;
+        self.copy_dir(from, to)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn copy_dir(&self, from: &Path, to: &Path) -> io::Result<()> {
+        for entry in crate::read_dir(from)? {
+            let entry = entry?;
+            let from = entry.path();
+            let to = to.join(entry.file_name());
+            if let Some(filter) = &self.filter {
+                if !filter(&from) {
+                    continue;
+                }
+            }
+
+            let file_type = if self.follow_symlinks {
+                crate::metadata(&from)?.file_type()
+            } else {
+                entry.file_type()?
+            };
+
+            if file_type.is_dir() {
+                match fs::create_dir(&to) {
+                    Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists && to.is_dir() => {}
+                    result => result.with_path(ErrorKind::CreateDir, &to)?,
+                }
+                self.copy_dir(&from, &to)?;
+            } else if self.replace(&from, &to)? {
+                if file_type.is_symlink() {
+                    copy_symlink(&from, &to)?;
+                } else {
+                    crate::copy(&from, &to)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the overwrite policy to `to`, returning whether `from` should be
+    /// copied to it.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn replace(&self, from: &Path, to: &Path) -> io::Result<bool> {
+        let existing = match fs::symlink_metadata(to) {
+            Ok(metadata) => metadata,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err).with_path(ErrorKind::SymlinkMetadata, to),
+        };
+        match self.overwrite {
+            // Symbolic links cannot be created over an existing file, and copying a file
+            // over a link would write to its target instead.
+            Overwrite::Replace if existing.file_type().is_symlink() => {
+                crate::remove_file(to)?;
+                Ok(true)
+            }
+            Overwrite::Replace => Ok(true),
+            Overwrite::Skip => Ok(false),
+            Overwrite::Fail => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "destination already exists",
+            ))
+            .with_paths(SourceDestErrorKind::Copy, from, to),
+        }
+    }
+}
+
+impl fmt::Debug for CopyDirOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyDirOptions")
+            .field("follow_symlinks", &self.follow_symlinks)
+            .field("overwrite", &self.overwrite)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// Fails with an error of kind [`io::ErrorKind::InvalidInput`] if `to` is
+/// inside the directory `from`. Otherwise copying `from` to `to` would copy
+/// `to` into itself as well, and so on forever.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub(crate) fn check_not_inside(from: &Path, to: &Path) -> io::Result<()> {
+    if let (Some(from_canonical), Some(to_canonical)) = (canonicalize(from), canonicalize(to)) {
+        if to_canonical != from_canonical && to_canonical.starts_with(&from_canonical) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "destination is inside the source directory `{}`",
+                    from.display()
+                ),
+            ))
+            .with_path(ErrorKind::CreateDir, to);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the canonical form of `path`, which need not exist, by
+/// canonicalizing the closest ancestor which does. Returns `None` if this
+/// cannot be determined.
+fn canonicalize(path: &Path) -> Option<PathBuf> {
+    let mut names = Vec::new();
+    let mut ancestor = path;
+    let mut canonical = loop {
+        if ancestor.as_os_str().is_empty() {
+            break std::env::current_dir().ok()?;
+        }
+        match fs::canonicalize(ancestor) {
+            Ok(canonical) => break canonical,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(_) => return None,
+        }
+        names.push(ancestor.file_name()?);
+        ancestor = ancestor.parent()?;
+    };
+    for name in names.into_iter().rev() {
+        canonical.push(name);
+    }
+    Some(canonical)
+}
+
+#[cfg(unix)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub(crate) fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    crate::os::unix::fs::symlink(crate::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub(crate) fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = crate::read_link(from)?;
+    if fs::metadata(from).map(|m| m.is_dir()).unwrap_or(false) {
+        crate::os::windows::fs::symlink_dir(target, to)
+    } else {
+        crate::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+/// Symbolic links cannot be created on other platforms, so the file they point
+/// to is copied instead.
+#[cfg(not(any(unix, windows)))]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub(crate) fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    crate::copy(from, to).map(drop)
+}
+
+/// Moves a file from `from` to `to`, copying it if they are on different
+/// filesystems.
+///
+/// This first tries to [`rename`](crate::rename) the file. If that fails
+/// because `from` and `to` are on different filesystems or volumes, the file
+/// is copied to a temporary file next to `to`, which is renamed over `to`, and
+/// then the original is removed. Errors identify whether the copy or the
+/// removal of `from` failed; if the copy fails, the temporary file is removed
+/// and any existing file at `to` is left unchanged.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn move_file<P, Q>(from: P, to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+    match fs::rename(from, to) {
+        Err(ref err) if is_cross_device(err) => {}
+        result => return result.with_paths(SourceDestErrorKind::Rename, from, to),
+    }
+
+    let temp = to.with_file_name(temp_name());
+    if let Err(err) = fs::copy(from, &temp).and_then(|_| fs::rename(&temp, to)) {
+        let _ = fs::remove_file(&temp);
+        return Err(err).with_paths(SourceDestErrorKind::MoveCopy, from, to);
+    }
+    fs::remove_file(from).with_path(ErrorKind::MoveRemoveFile, from)
+}
+
+/// Moves a directory from `from` to `to`, copying it if they are on different
+/// filesystems.
+///
+/// This first tries to [`rename`](crate::rename) the directory. If that fails
+/// because `from` and `to` are on different filesystems or volumes, its
+/// contents are copied with [`copy_dir_all`] and then `from` is removed.
+/// Errors from the copy include the path of the entry which could not be
+/// copied, and errors removing `from` are reported as such.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn move_dir<P, Q>(from: P, to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+    match fs::rename(from, to) {
+        Err(ref err) if is_cross_device(err) => {}
+        result => return result.with_paths(SourceDestErrorKind::Rename, from, to),
+    }
+
+    copy_dir_all(from, to)?;
+    fs::remove_dir_all(from).with_path(ErrorKind::MoveRemoveDir, from)
+}
+
+/// Creates a hard link from `to` to `from`, or copies `from` to `to` if hard
+/// links are not supported.
+///
+/// Copying is used if `from` and `to` are on different filesystems, the
+/// filesystem does not support hard links, or `from` has too many links
+/// already. Other errors, such as `to` already existing, are returned without
+/// copying. If the copy also fails, the error mentions both attempts.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn hard_link_or_copy<P, Q>(from: P, to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+    match fs::hard_link(from, to) {
+        Err(ref err) if is_cross_device(err) || is_link_unsupported(err) => {}
+        result => return result.with_paths(SourceDestErrorKind::HardLink, from, to),
+    }
+
+    fs::copy(from, to)
+        .map(drop)
+        .with_paths(SourceDestErrorKind::HardLinkOrCopy, from, to)
+}
+
+/// Returns whether a hard link failed because the filesystem does not support
+/// them, or because the file has too many links.
+fn is_link_unsupported(err: &io::Error) -> bool {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const CODES: &[i32] = &[1, 31, 95]; // EPERM, EMLINK, EOPNOTSUPP
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const CODES: &[i32] = &[1, 31, 45, 102]; // EPERM, EMLINK, ENOTSUP, EOPNOTSUPP
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios"
+        ))
+    ))]
+    const CODES: &[i32] = &[1, 31]; // EPERM, EMLINK
+                                    // ERROR_INVALID_FUNCTION, ERROR_NOT_SUPPORTED, ERROR_TOO_MANY_LINKS
+    #[cfg(windows)]
+    const CODES: &[i32] = &[1, 50, 1142];
+    #[cfg(not(any(unix, windows)))]
+    const CODES: &[i32] = &[];
+
+    CODES.iter().any(|&code| err.raw_os_error() == Some(code))
+}
+
+fn is_cross_device(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    const CROSS_DEVICE: i32 = 18; // EXDEV
+    #[cfg(windows)]
+    const CROSS_DEVICE: i32 = 17; // ERROR_NOT_SAME_DEVICE
+    #[cfg(not(any(unix, windows)))]
+    const CROSS_DEVICE: i32 = -1;
+
+    err.raw_os_error() == Some(CROSS_DEVICE)
+}
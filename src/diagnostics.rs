@@ -0,0 +1,500 @@
+//! Extra information attached to errors when the `diagnostics` feature is enabled.
+//!
+//! This contains the small amount of platform-specific code needed to find out
+//! why an operation failed, such as querying the free space of a filesystem.
+//!
+//! The information is gathered when the error is created, since the state of
+//! the filesystem may have changed by the time the error is displayed.
+
+use std::io;
+use std::path::Path;
+#[cfg(feature = "diagnostics")]
+use std::path::PathBuf;
+
+#[cfg(feature = "diagnostics")]
+use crate::errors::sanitize_path;
+
+/// Returns a note explaining why `source` may have occurred for `paths`.
+///
+/// `len` is the number of bytes that were being written, if known.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn diagnose(source: &io::Error, paths: &[&Path], len: Option<usize>) -> Option<String> {
+    // These errors say nothing about the path, which may be on a filesystem
+    // that is not responding, so it is not accessed again.
+    match source.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => {
+            return None
+        }
+        _ => {}
+    }
+
+    let mut notes = match source.kind() {
+        io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied => resolved_paths(paths),
+        _ => Vec::new(),
+    };
+
+    let note = if is_storage_full(source) {
+        // Data is written to the last path, for example the destination of a copy.
+        paths.last().and_then(|path| storage_full(path, len))
+    } else if is_sharing_violation(source) {
+        processes_using(paths)
+    } else {
+        match source.kind() {
+            io::ErrorKind::NotFound => missing_parents(paths),
+            io::ErrorKind::PermissionDenied => permission_denied(paths),
+            _ => None,
+        }
+    };
+    notes.extend(note);
+
+    if notes.is_empty() {
+        None
+    } else {
+        Some(notes.join("; "))
+    }
+}
+
+#[cfg(not(feature = "diagnostics"))]
+pub(crate) fn diagnose(
+    _source: &io::Error,
+    _paths: &[&Path],
+    _len: Option<usize>,
+) -> Option<String> {
+    None
+}
+
+/// Describes the absolute path that relative paths and symbolic links resolve to.
+#[cfg(feature = "diagnostics")]
+fn resolved_paths(paths: &[&Path]) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let resolved = resolve(path)?;
+            Some(if paths.len() == 1 {
+                format!("resolved to `{}`", sanitize_path(&resolved).display())
+            } else {
+                format!(
+                    "`{}` resolved to `{}`",
+                    sanitize_path(path).display(),
+                    sanitize_path(&resolved).display()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Returns the absolute path of `path` if it is relative or a symbolic link.
+#[cfg(feature = "diagnostics")]
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let is_symlink = std::fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_symlink && !path.is_relative() {
+        return None;
+    }
+
+    let resolved = match std::fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        // The path may not exist yet, for example when creating a file.
+        Err(_) if path.is_relative() => std::env::current_dir().ok()?.join(path),
+        Err(_) => return None,
+    };
+    if resolved == path {
+        None
+    } else {
+        Some(resolved)
+    }
+}
+
+/// Describes the parent directories of `paths` which do not exist.
+#[cfg(feature = "diagnostics")]
+fn missing_parents(paths: &[&Path]) -> Option<String> {
+    let notes: Vec<String> = paths
+        .iter()
+        .filter_map(|path| {
+            let parent = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())?;
+            if parent.exists() {
+                return None;
+            }
+            Some(format!(
+                "parent directory `{}` does not exist",
+                sanitize_path(parent).display()
+            ))
+        })
+        .collect();
+
+    if notes.is_empty() {
+        None
+    } else {
+        Some(notes.join("; "))
+    }
+}
+
+/// Describes the space available on the filesystem containing `path`.
+#[cfg(feature = "diagnostics")]
+fn storage_full(path: &Path, len: Option<usize>) -> Option<String> {
+    // The file may not exist yet, so query the closest directory that does.
+    let dir = path.ancestors().find(|path| path.is_dir())?;
+    let available = available_space(dir)?;
+
+    Some(match len {
+        Some(len) => format!(
+            "{} bytes are available on the filesystem containing `{}`, but {} bytes were being written",
+            available,
+            sanitize_path(dir).display(),
+            len
+        ),
+        None => format!(
+            "{} bytes are available on the filesystem containing `{}`",
+            available,
+            sanitize_path(dir).display()
+        ),
+    })
+}
+
+#[cfg(all(feature = "diagnostics", unix))]
+fn is_storage_full(source: &io::Error) -> bool {
+    source.raw_os_error() == Some(libc::ENOSPC)
+}
+
+#[cfg(all(feature = "diagnostics", windows))]
+fn is_storage_full(source: &io::Error) -> bool {
+    const ERROR_HANDLE_DISK_FULL: i32 = 39;
+    const ERROR_DISK_FULL: i32 = 112;
+
+    matches!(
+        source.raw_os_error(),
+        Some(ERROR_HANDLE_DISK_FULL) | Some(ERROR_DISK_FULL)
+    )
+}
+
+#[cfg(all(feature = "diagnostics", not(any(unix, windows))))]
+fn is_storage_full(_source: &io::Error) -> bool {
+    false
+}
+
+/// Returns the number of bytes available to the current user on the
+/// filesystem containing `dir`.
+#[cfg(all(feature = "diagnostics", unix))]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `dir` is a valid nul-terminated string and `stat` is valid for writes.
+    if unsafe { libc::statvfs(dir.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` succeeded, so it initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)))
+}
+
+/// Returns the number of bytes available to the current user on the volume
+/// containing `dir`.
+#[cfg(all(feature = "diagnostics", windows))]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory_name: *const u16,
+            free_bytes_available_to_caller: *mut u64,
+            total_number_of_bytes: *mut u64,
+            total_number_of_free_bytes: *mut u64,
+        ) -> i32;
+    }
+
+    let dir: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0;
+    // SAFETY: `dir` is a valid nul-terminated string, and the other parameters
+    // are either valid for writes or null, which is allowed.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            dir.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        None
+    } else {
+        Some(available)
+    }
+}
+
+#[cfg(all(feature = "diagnostics", not(any(unix, windows))))]
+fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// Describes the ownership and mode of each path, or its closest existing
+/// ancestor, along with the user the process is running as.
+#[cfg(all(feature = "diagnostics", unix))]
+fn permission_denied(paths: &[&Path]) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut notes: Vec<String> = Vec::new();
+    let mut seen: Vec<&Path> = Vec::new();
+    for path in paths {
+        let found = path.ancestors().find_map(|path| {
+            std::fs::metadata(path)
+                .ok()
+                .map(|metadata| (path, metadata))
+        });
+        if let Some((path, metadata)) = found {
+            if !seen.contains(&path) {
+                seen.push(path);
+                notes.push(format!(
+                    "`{}` has mode {:o} and is owned by uid {} and gid {}",
+                    sanitize_path(path).display(),
+                    metadata.mode() & 0o7777,
+                    metadata.uid(),
+                    metadata.gid(),
+                ));
+            }
+        }
+    }
+
+    if notes.is_empty() {
+        return None;
+    }
+
+    // SAFETY: these functions are always successful and have no side effects.
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    notes.push(format!(
+        "the process is running as uid {} and gid {}",
+        uid, gid
+    ));
+    Some(notes.join("; "))
+}
+
+/// Describes the attributes of each path which commonly cause access to be
+/// denied.
+#[cfg(all(feature = "diagnostics", windows))]
+fn permission_denied(paths: &[&Path]) -> Option<String> {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+
+    let notes: Vec<String> = paths
+        .iter()
+        .filter_map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            let note = if metadata.is_dir() {
+                "is a directory"
+            } else if metadata.file_attributes() & FILE_ATTRIBUTE_READONLY != 0 {
+                "has the read-only attribute set"
+            } else {
+                "is not read-only, so access was likely denied by its access control list or because it is in use by another process"
+            };
+            Some(format!("`{}` {}", sanitize_path(path).display(), note))
+        })
+        .collect();
+
+    if notes.is_empty() {
+        None
+    } else {
+        Some(notes.join("; "))
+    }
+}
+
+#[cfg(all(feature = "diagnostics", not(any(unix, windows))))]
+fn permission_denied(_paths: &[&Path]) -> Option<String> {
+    None
+}
+
+#[cfg(all(feature = "diagnostics", windows))]
+fn is_sharing_violation(source: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+
+    matches!(
+        source.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)
+    )
+}
+
+#[cfg(all(feature = "diagnostics", not(windows)))]
+fn is_sharing_violation(_source: &io::Error) -> bool {
+    false
+}
+
+/// Describes the processes which have `paths` open, using the Restart Manager.
+#[cfg(all(feature = "diagnostics", windows))]
+fn processes_using(paths: &[&Path]) -> Option<String> {
+    let processes = restart_manager::processes_using(paths)?;
+    if processes.is_empty() {
+        return None;
+    }
+
+    let processes: Vec<String> = processes
+        .iter()
+        .map(|(name, pid)| format!("{}, PID {}", name, pid))
+        .collect();
+    Some(format!(
+        "used by another process ({})",
+        processes.join("; ")
+    ))
+}
+
+#[cfg(all(feature = "diagnostics", not(windows)))]
+fn processes_using(_paths: &[&Path]) -> Option<String> {
+    None
+}
+
+#[cfg(all(feature = "diagnostics", windows))]
+mod restart_manager {
+    //! Bindings to the parts of the Restart Manager API used to find which
+    //! processes have a file open.
+
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr;
+
+    const ERROR_SUCCESS: u32 = 0;
+    const ERROR_MORE_DATA: u32 = 234;
+    const CCH_RM_SESSION_KEY: usize = 32;
+    const CCH_RM_MAX_APP_NAME: usize = 255;
+    const CCH_RM_MAX_SVC_NAME: usize = 63;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct FileTime {
+        low_date_time: u32,
+        high_date_time: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct RmUniqueProcess {
+        process_id: u32,
+        process_start_time: FileTime,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct RmProcessInfo {
+        process: RmUniqueProcess,
+        app_name: [u16; CCH_RM_MAX_APP_NAME + 1],
+        service_short_name: [u16; CCH_RM_MAX_SVC_NAME + 1],
+        application_type: i32,
+        app_status: u32,
+        ts_session_id: u32,
+        restartable: i32,
+    }
+
+    #[link(name = "rstrtmgr")]
+    extern "system" {
+        fn RmStartSession(session: *mut u32, flags: u32, session_key: *mut u16) -> u32;
+        fn RmRegisterResources(
+            session: u32,
+            files_len: u32,
+            files: *const *const u16,
+            applications_len: u32,
+            applications: *const RmUniqueProcess,
+            services_len: u32,
+            services: *const *const u16,
+        ) -> u32;
+        fn RmGetList(
+            session: u32,
+            processes_needed: *mut u32,
+            processes_len: *mut u32,
+            processes: *mut RmProcessInfo,
+            reboot_reasons: *mut u32,
+        ) -> u32;
+        fn RmEndSession(session: u32) -> u32;
+    }
+
+    /// Ends the Restart Manager session when dropped.
+    struct Session(u32);
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            // SAFETY: the session was started by `RmStartSession`.
+            unsafe { RmEndSession(self.0) };
+        }
+    }
+
+    /// Returns the name and process ID of each process which has one of
+    /// `paths` open.
+    pub(super) fn processes_using(paths: &[&Path]) -> Option<Vec<(String, u32)>> {
+        let mut session = 0;
+        let mut key = [0u16; CCH_RM_SESSION_KEY + 1];
+        // SAFETY: `session` and `key` are valid for writes, and `key` has room
+        // for a session key and nul terminator.
+        if unsafe { RmStartSession(&mut session, 0, key.as_mut_ptr()) } != ERROR_SUCCESS {
+            return None;
+        }
+        let session = Session(session);
+
+        let paths: Vec<Vec<u16>> = paths
+            .iter()
+            .map(|path| path.as_os_str().encode_wide().chain(Some(0)).collect())
+            .collect();
+        let path_ptrs: Vec<*const u16> = paths.iter().map(|path| path.as_ptr()).collect();
+        // SAFETY: `path_ptrs` contains pointers to valid nul-terminated strings.
+        let result = unsafe {
+            RmRegisterResources(
+                session.0,
+                path_ptrs.len() as u32,
+                path_ptrs.as_ptr(),
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+
+        let mut processes = Vec::new();
+        loop {
+            let mut needed = 0;
+            let mut len = processes.capacity() as u32;
+            let mut reboot_reasons = 0;
+            // SAFETY: `processes` has room for `len` elements, and the other
+            // parameters are valid for writes.
+            let result = unsafe {
+                RmGetList(
+                    session.0,
+                    &mut needed,
+                    &mut len,
+                    processes.as_mut_ptr(),
+                    &mut reboot_reasons,
+                )
+            };
+            match result {
+                ERROR_SUCCESS => {
+                    // SAFETY: `RmGetList` initialized the first `len` elements.
+                    unsafe { processes.set_len(len as usize) };
+                    break;
+                }
+                // The list of processes may grow between calls, so keep trying.
+                ERROR_MORE_DATA => processes.reserve(needed as usize),
+                _ => return None,
+            }
+        }
+
+        Some(
+            processes
+                .iter()
+                .map(|process: &RmProcessInfo| {
+                    let name = &process.app_name;
+                    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                    (
+                        String::from_utf16_lossy(&name[..len]),
+                        process.process.process_id,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
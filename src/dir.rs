@@ -3,18 +3,19 @@ use std::fs;
 use std::io;
 use std::path::PathBuf;
 
-use crate::errors::{Error, ErrorKind};
+use crate::errors::{Error, ErrorKind, ResultExt};
+use crate::metrics::Timer;
+use crate::Operation;
 
 /// Returns an iterator over the entries within a directory.
 ///
 /// Wrapper for [`fs::read_dir`](https://doc.rust-lang.org/stable/std/fs/fn.read_dir.html).
+#[cfg_attr(rustc_1_46, track_caller)]
 pub fn read_dir<P: Into<PathBuf>>(path: P) -> io::Result<ReadDir> {
     let path = path.into();
-
-    match fs::read_dir(&path) {
-        Ok(inner) => Ok(ReadDir { inner, path }),
-        Err(source) => Err(Error::build(source, ErrorKind::ReadDir, path)),
-    }
+    let timer = Timer::start(Operation::ReadDir, &path);
+    let inner = timer.finish(fs::read_dir(&path).with_path(ErrorKind::ReadDir, &path))?;
+    Ok(ReadDir { inner, path })
 }
 
 /// Wrapper around [`std::fs::ReadDir`][std::fs::ReadDir] which adds more
@@ -63,19 +64,21 @@ impl DirEntry {
     /// Returns the metadata for the file that this entry points at.
     ///
     /// Wrapper for [`DirEntry::metadata`](https://doc.rust-lang.org/stable/std/fs/struct.DirEntry.html#method.metadata).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn metadata(&self) -> io::Result<fs::Metadata> {
         self.inner
             .metadata()
-            .map_err(|source| Error::build(source, ErrorKind::Metadata, self.path()))
+            .with_path(ErrorKind::Metadata, self.path())
     }
 
     /// Returns the file type for the file that this entry points at.
     ///
     /// Wrapper for [`DirEntry::file_type`](https://doc.rust-lang.org/stable/std/fs/struct.DirEntry.html#method.file_type).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn file_type(&self) -> io::Result<fs::FileType> {
         self.inner
             .file_type()
-            .map_err(|source| Error::build(source, ErrorKind::Metadata, self.path()))
+            .with_path(ErrorKind::Metadata, self.path())
     }
 
     /// Returns the file name of this directory entry without any leading path component(s).
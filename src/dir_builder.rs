@@ -0,0 +1,58 @@
+use std::{fs, io, path::Path};
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// A builder used to create directories in various manners.
+///
+/// Wrapper around [`std::fs::DirBuilder`](https://doc.rust-lang.org/stable/std/fs/struct.DirBuilder.html).
+#[derive(Debug)]
+pub struct DirBuilder {
+    inner: fs::DirBuilder,
+}
+
+impl DirBuilder {
+    /// Creates a new set of options with default mode/security settings for all
+    /// platforms and also non-recursive.
+    ///
+    /// Wrapper for [`DirBuilder::new`](https://doc.rust-lang.org/stable/std/fs/struct.DirBuilder.html#method.new).
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        DirBuilder {
+            inner: fs::DirBuilder::new(),
+        }
+    }
+
+    /// Indicates that directories should be created recursively, creating all
+    /// parent directories. Parents that do not exist are created with the same
+    /// security and permissions settings.
+    ///
+    /// Wrapper for [`DirBuilder::recursive`](https://doc.rust-lang.org/stable/std/fs/struct.DirBuilder.html#method.recursive).
+    pub fn recursive(&mut self, recursive: bool) -> &mut Self {
+        self.inner.recursive(recursive);
+        self
+    }
+
+    /// Creates the specified directory with the options configured in this builder.
+    ///
+    /// Wrapper for [`DirBuilder::create`](https://doc.rust-lang.org/stable/std/fs/struct.DirBuilder.html#method.create).
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        self.inner
+            .create(path)
+            .with_path(ErrorKind::CreateDir, path)
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use crate::os::unix::fs::DirBuilderExt;
+    use std::os::unix::fs::DirBuilderExt as _;
+
+    impl DirBuilderExt for crate::DirBuilder {
+        fn mode(&mut self, mode: u32) -> &mut Self {
+            self.inner.mode(mode);
+            self
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::raw::{c_int, c_uint};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+/// An open directory, which files and directories can be opened, created and
+/// removed relative to.
+///
+/// Once a `Dir` is open, the `*_at` methods operate within that directory even
+/// if it is renamed or replaced, or a symbolic link is swapped in for one of
+/// its parents, which avoids races between checking a path and using it. The
+/// paths passed to them are relative to the directory, although they are
+/// still allowed to contain `..` or symbolic links which lead out of it.
+///
+/// Errors from the `*_at` methods include both the path of the directory and
+/// the relative path.
+///
+/// # Example
+///
+/// ```no_run
+/// let dir = fs_err::Dir::open("/var/spool/jobs")?;
+/// if dir.metadata_at("ready")?.is_file() {
+///     let job = dir.open_at("ready")?;
+///     // ...
+///     dir.remove_file_at("ready")?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Dir {
+    fd: fs::File,
+    path: PathBuf,
+}
+
+impl Dir {
+    /// Opens the directory at `path`.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open<P: Into<PathBuf>>(path: P) -> io::Result<Dir> {
+        let path = path.into();
+        let fd = sys::open_at(libc::AT_FDCWD, &path, libc::O_DIRECTORY, 0)
+            .with_path(ErrorKind::OpenDir, &path)?;
+        Ok(Dir { fd, path })
+    }
+
+    /// Returns the path that the directory was opened with.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens a file in the directory in read-only mode.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open_at<P: AsRef<Path>>(&self, path: P) -> io::Result<crate::File> {
+        let path = path.as_ref();
+        let file = sys::open_at(self.fd.as_raw_fd(), path, 0, 0).with_paths(
+            SourceDestErrorKind::OpenAt,
+            &self.path,
+            path,
+        )?;
+        Ok(crate::File::from_parts(file, self.path.join(path)))
+    }
+
+    /// Opens a file in the directory in write-only mode, creating it if it
+    /// does not exist and truncating it if it does.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_at<P: AsRef<Path>>(&self, path: P) -> io::Result<crate::File> {
+        let path = path.as_ref();
+        let flags = libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC;
+        let file = sys::open_at(self.fd.as_raw_fd(), path, flags, 0o666).with_paths(
+            SourceDestErrorKind::CreateAt,
+            &self.path,
+            path,
+        )?;
+        Ok(crate::File::from_parts(file, self.path.join(path)))
+    }
+
+    /// Opens a subdirectory of the directory.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open_dir_at<P: AsRef<Path>>(&self, path: P) -> io::Result<Dir> {
+        let path = path.as_ref();
+        let fd = sys::open_at(self.fd.as_raw_fd(), path, libc::O_DIRECTORY, 0).with_paths(
+            SourceDestErrorKind::OpenDirAt,
+            &self.path,
+            path,
+        )?;
+        Ok(Dir {
+            fd,
+            path: self.path.join(path),
+        })
+    }
+
+    /// Creates a new, empty subdirectory of the directory.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_dir_at<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        sys::create_dir_at(self.fd.as_raw_fd(), path).with_paths(
+            SourceDestErrorKind::CreateDirAt,
+            &self.path,
+            path,
+        )
+    }
+
+    /// Removes a file or symbolic link from the directory.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove_file_at<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        sys::unlink_at(self.fd.as_raw_fd(), path, 0).with_paths(
+            SourceDestErrorKind::RemoveFileAt,
+            &self.path,
+            path,
+        )
+    }
+
+    /// Removes an empty subdirectory of the directory.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove_dir_at<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        sys::unlink_at(self.fd.as_raw_fd(), path, libc::AT_REMOVEDIR).with_paths(
+            SourceDestErrorKind::RemoveDirAt,
+            &self.path,
+            path,
+        )
+    }
+
+    /// Queries the metadata of a file or directory in the directory, following
+    /// symbolic links.
+    ///
+    /// On platforms other than Linux and Android, this opens the file, so it
+    /// fails if the file cannot be read.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn metadata_at<P: AsRef<Path>>(&self, path: P) -> io::Result<fs::Metadata> {
+        let path = path.as_ref();
+        sys::open_at(self.fd.as_raw_fd(), path, sys::O_METADATA, 0)
+            .and_then(|file| file.metadata())
+            .with_paths(SourceDestErrorKind::MetadataAt, &self.path, path)
+    }
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Dir {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_raw_fd()
+    }
+}
+
+mod sys {
+    use super::*;
+
+    /// `O_PATH`, which opens a file without reading it.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const O_METADATA: c_int = libc::O_PATH;
+    /// `O_NONBLOCK`, so that opening a FIFO does not block.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const O_METADATA: c_int = libc::O_NONBLOCK;
+
+    const DIR_MODE: libc::mode_t = 0o777;
+
+    fn c_path(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path contains an interior nul byte",
+            )
+        })
+    }
+
+    pub fn open_at(dirfd: c_int, path: &Path, flags: c_int, mode: c_uint) -> io::Result<fs::File> {
+        let path = c_path(path)?;
+        loop {
+            // SAFETY: the path is a valid nul-terminated string for the
+            // duration of the call.
+            let fd = unsafe { libc::openat(dirfd, path.as_ptr(), flags | libc::O_CLOEXEC, mode) };
+            if fd >= 0 {
+                // SAFETY: the file descriptor was just opened, so nothing else
+                // owns it.
+                return Ok(unsafe { fs::File::from_raw_fd(fd) });
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    pub fn create_dir_at(dirfd: c_int, path: &Path) -> io::Result<()> {
+        let path = c_path(path)?;
+        // SAFETY: the path is a valid nul-terminated string for the duration of
+        // the call.
+        match unsafe { libc::mkdirat(dirfd, path.as_ptr(), DIR_MODE) } {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn unlink_at(dirfd: c_int, path: &Path, flags: c_int) -> io::Result<()> {
+        let path = c_path(path)?;
+        // SAFETY: the path is a valid nul-terminated string for the duration of
+        // the call.
+        match unsafe { libc::unlinkat(dirfd, path.as_ptr(), flags) } {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
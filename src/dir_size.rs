@@ -0,0 +1,101 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Returns the total size in bytes of all files within a directory,
+/// recursively.
+///
+/// This uses the default [`DirSizeOptions`]: the apparent size of each file is
+/// counted, and symbolic links are not followed. Errors include the path of the
+/// entry which could not be read.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn dir_size<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    DirSizeOptions::new().size(path)
+}
+
+/// Options for computing the size of a directory.
+///
+/// # Example
+///
+/// ```no_run
+/// let bytes = fs_err::DirSizeOptions::new()
+///     .allocated(true)
+///     .size("target")?;
+/// println!("target/ uses {} bytes on disk", bytes);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirSizeOptions {
+    allocated: bool,
+    follow_symlinks: bool,
+}
+
+impl DirSizeOptions {
+    /// Creates options which count the apparent size of files, without
+    /// following symbolic links.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        DirSizeOptions {
+            allocated: false,
+            follow_symlinks: false,
+        }
+    }
+
+    /// Sets whether the space allocated on disk for each file is counted,
+    /// rather than its length.
+    ///
+    /// The allocated size is only available on Unix. On other platforms, the
+    /// length of each file is always counted.
+    pub fn allocated(&mut self, allocated: bool) -> &mut Self {
+        self.allocated = allocated;
+        self
+    }
+
+    /// Sets whether symbolic links are followed, counting the size of the file
+    /// or directory they point to rather than the size of the link itself.
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Returns the total size in bytes of all files within the directory
+    /// `path`, recursively, with these options.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn size<P: AsRef<Path>>(&self, path: P) -> io::Result<u64> {
+        let mut total = 0;
+        for entry in crate::read_dir(path.as_ref())? {
+            let path = entry?.path();
+            let metadata = if self.follow_symlinks {
+                fs::metadata(&path).with_path(ErrorKind::Metadata, &path)?
+            } else {
+                fs::symlink_metadata(&path).with_path(ErrorKind::SymlinkMetadata, &path)?
+            };
+
+            total += if metadata.is_dir() {
+                self.size(&path)?
+            } else {
+                self.file_size(&metadata)
+            };
+        }
+        Ok(total)
+    }
+
+    #[cfg(unix)]
+    fn file_size(&self, metadata: &fs::Metadata) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+
+        if self.allocated {
+            // `st_blocks` is always in units of 512 bytes, regardless of the block size.
+            metadata.blocks() * 512
+        } else {
+            metadata.len()
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn file_size(&self, metadata: &fs::Metadata) -> u64 {
+        metadata.len()
+    }
+}
@@ -1,40 +1,1013 @@
+use std::borrow::Cow;
 use std::error::Error as StdError;
 use std::fmt;
 use std::io;
-use std::path::PathBuf;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+#[cfg(rustc_1_63)]
+use std::sync::PoisonError;
+
+use crate::diagnostics;
+
+/// The filesystem operation that failed.
+///
+/// Errors returned by fs-err record which operation was attempted. Use
+/// [`operation_of`] to retrieve it from an [`io::Error`] without parsing the
+/// error message.
+///
+/// New operations may be added in future releases as more of [`std::fs`] is
+/// wrapped, so this enum is marked `#[non_exhaustive]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Operation {
+    /// Opening a file.
+    OpenFile,
+    /// Creating a file.
+    CreateFile,
+    /// Creating a file which must not already exist.
+    CreateNewFile,
+    /// Creating a directory.
+    CreateDir,
+    /// Syncing a file to disk.
+    SyncFile,
+    /// Syncing a directory to disk (Unix only).
+    SyncDir,
+    /// Setting the length of a file.
+    SetLen,
+    /// Allocating disk space for a file.
+    Allocate,
+    /// Querying the metadata of a file.
+    Metadata,
+    /// Cloning a file handle.
+    Clone,
+    /// Setting the permissions of a file.
+    SetPermissions,
+    /// Setting the timestamps of a file.
+    SetTimes,
+    /// Acquiring an exclusive lock on a file.
+    Lock,
+    /// Acquiring a shared lock on a file.
+    LockShared,
+    /// Releasing the locks on a file.
+    Unlock,
+    /// Reading from a file.
+    Read,
+    /// Seeking in a file.
+    Seek,
+    /// Writing to a file.
+    Write,
+    /// Flushing a file.
+    Flush,
+    /// Checking that the contents of a file match what was written to it.
+    Verify,
+    /// Moving a file or directory to the trash.
+    Trash,
+    /// Reading the entries of a directory.
+    ReadDir,
+    /// Removing a file.
+    RemoveFile,
+    /// Removing a directory.
+    RemoveDir,
+    /// Canonicalizing a path.
+    Canonicalize,
+    /// Making a path absolute.
+    Absolute,
+    /// Reading the target of a symbolic link.
+    ReadLink,
+    /// Querying the metadata of a symbolic link.
+    SymlinkMetadata,
+    /// Checking whether a path exists.
+    FileExists,
+    /// Seeking and reading from a file (Windows only).
+    SeekRead,
+    /// Seeking and writing to a file (Windows only).
+    SeekWrite,
+    /// Reading from a file at an offset (Unix only).
+    ReadAt,
+    /// Writing to a file at an offset (Unix only).
+    WriteAt,
+    /// Reading an exact number of bytes from a file at an offset (Unix only).
+    ReadExactAt,
+    /// Writing an entire buffer to a file at an offset (Unix only).
+    WriteAllAt,
+    /// Changing the owner of a file (Unix only).
+    Chown,
+    /// Changing the owner of a symbolic link (Unix only).
+    Lchown,
+    /// Advising the kernel how a file will be accessed (Linux only).
+    Fadvise,
+    /// Validating a buffer for direct I/O on a file (Unix only).
+    DirectIo,
+    /// Reading the extended attributes of a file (Linux only).
+    ReadXattrs,
+    /// Setting the extended attributes of a file (Linux only).
+    WriteXattrs,
+    /// Copying a file.
+    Copy,
+    /// Creating a hard link.
+    HardLink,
+    /// Cloning a file with copy-on-write (Linux and macOS only).
+    Reflink,
+    /// Renaming a file or directory.
+    Rename,
+    /// Creating a symbolic link with the deprecated `soft_link` function.
+    SoftLink,
+    /// Creating a symbolic link (Unix only).
+    Symlink,
+    /// Creating a symbolic link to a directory (Windows only).
+    SymlinkDir,
+    /// Creating a symbolic link to a file (Windows only).
+    SymlinkFile,
+}
+
+/// Returns information about `err` if it was returned by fs-err.
+///
+/// See [`paths_of`] for how errors wrapping an fs-err error are handled.
+///
+/// ```no_run
+/// if let Err(err) = fs_err::read_to_string("foo.txt") {
+///     if let Some(context) = fs_err::context_of(&err) {
+///         eprintln!("{:?} failed for {}", context.operation(), context.path().display());
+///     }
+/// }
+/// ```
+pub fn context_of(err: &io::Error) -> Option<ErrorContext<'_>> {
+    Inner::find(err).map(ErrorContext::new)
+}
+
+/// Returns the operation that failed if `err` was returned by fs-err.
+///
+/// Returns `None` for errors that did not originate from this crate. See
+/// [`paths_of`] for how errors wrapping an fs-err error are handled.
+///
+/// ```no_run
+/// use fs_err::Operation;
+///
+/// if let Err(err) = fs_err::remove_file("foo.txt") {
+///     assert_eq!(fs_err::operation_of(&err), Some(Operation::RemoveFile));
+/// }
+/// ```
+pub fn operation_of(err: &io::Error) -> Option<Operation> {
+    context_of(err).map(|context| context.operation())
+}
+
+/// Returns the path(s) involved in the failed operation if `err` was returned
+/// by fs-err.
+///
+/// The first path is the path the operation was performed on, or the source
+/// path for operations such as [`copy`](crate::copy) and
+/// [`rename`](crate::rename). The second path is the destination path of those
+/// operations, and `None` otherwise.
+///
+/// If `err` does not directly come from fs-err, its chain of sources is
+/// searched and the paths of the first fs-err error found are returned. This
+/// allows finding the offending file even after the error has been wrapped by
+/// another library. Returns `None` if no fs-err error is found.
+///
+/// ```no_run
+/// if let Err(err) = fs_err::rename("from.txt", "to.txt") {
+///     if let Some((from, to)) = fs_err::paths_of(&err) {
+///         eprintln!("failed to rename {} to {:?}", from.display(), to);
+///     }
+/// }
+/// ```
+pub fn paths_of(err: &io::Error) -> Option<(&Path, Option<&Path>)> {
+    context_of(err).map(|context| (context.path(), context.dest_path()))
+}
+
+/// Returns the original error returned by [`std::fs`] if `err` was returned by
+/// fs-err.
+///
+/// The [`kind`](io::Error::kind) of errors returned by fs-err is always the same
+/// as the kind of the original error. However, other information such as the
+/// [`raw_os_error`](io::Error::raw_os_error) is not available on the error
+/// returned by fs-err, and can be retrieved from the original error instead.
+///
+/// If the original error is itself an fs-err error, it is unwrapped as well.
+/// See [`paths_of`] for how errors wrapping an fs-err error are handled.
+///
+/// ```no_run
+/// if let Err(err) = fs_err::remove_file("foo.txt") {
+///     let original = fs_err::original_error_of(&err).unwrap();
+///     assert_eq!(original.kind(), err.kind());
+///     println!("os error: {:?}", original.raw_os_error());
+/// }
+/// ```
+pub fn original_error_of(err: &io::Error) -> Option<&io::Error> {
+    context_of(err).map(|context| context.original_error())
+}
+
+/// Returns the OS error code of `err`, looking through errors returned by fs-err.
+///
+/// [`io::Error::raw_os_error`] always returns `None` for errors returned by
+/// fs-err, because an `io::Error` cannot hold both an OS error code and the
+/// path information added by this crate. This function returns the code of the
+/// [original error](original_error_of) instead, so errno-based handling keeps
+/// working after switching to fs-err.
+///
+/// ```no_run
+/// if let Err(err) = fs_err::create_dir("foo") {
+///     if fs_err::raw_os_error_of(&err) == Some(17) {
+///         println!("directory already exists");
+///     }
+/// }
+/// ```
+pub fn raw_os_error_of(err: &io::Error) -> Option<i32> {
+    err.raw_os_error()
+        .or_else(|| original_error_of(err).and_then(io::Error::raw_os_error))
+}
+
+/// Information about an error returned by fs-err.
+///
+/// This type is created with [`context_of`]. Its `Display` implementation
+/// writes the same message as the error it was created from.
+///
+/// When the `serde` feature is enabled, this type implements `Serialize`,
+/// producing the operation, path(s), OS error code, location and full error
+/// message.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorContext<'a> {
+    inner: Inner<'a>,
+}
+
+impl<'a> ErrorContext<'a> {
+    /// Returns the operation that failed.
+    pub fn operation(&self) -> Operation {
+        match self.inner {
+            Inner::Path(err) => err.kind.operation(),
+            Inner::SourceDest(err) => err.kind.operation(),
+        }
+    }
+
+    /// Returns the path the operation was performed on, or the source path
+    /// for operations involving two paths.
+    pub fn path(&self) -> &'a Path {
+        match self.inner {
+            Inner::Path(err) => &err.path,
+            Inner::SourceDest(err) => &err.from_path,
+        }
+    }
+
+    /// Returns the destination path for operations involving two paths, such as
+    /// [`copy`](crate::copy) and [`rename`](crate::rename).
+    pub fn dest_path(&self) -> Option<&'a Path> {
+        match self.inner {
+            Inner::Path(_) => None,
+            Inner::SourceDest(err) => Some(&err.to_path),
+        }
+    }
+
+    /// Returns the location of the call to fs-err which failed.
+    ///
+    /// This is recorded for functions and methods of this crate which are called
+    /// directly, on Rust 1.46 and later. It is `None` for errors returned from
+    /// implementations of standard library traits such as [`Read`](std::io::Read)
+    /// and from async functions, where the caller cannot be tracked.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        match self.inner {
+            Inner::Path(err) => err.location,
+            Inner::SourceDest(err) => err.location,
+        }
+    }
+
+    /// Returns extra information explaining why the operation may have failed.
+    ///
+    /// This is only recorded when the `diagnostics` feature is enabled, and is
+    /// included in the error message. For example, when permission is denied on
+    /// Unix, it describes the mode and owner of the file and the user the
+    /// process is running as.
+    pub fn diagnostic(&self) -> Option<&'a str> {
+        match self.inner {
+            Inner::Path(err) => err.diagnostic.as_deref(),
+            Inner::SourceDest(err) => err.diagnostic.as_deref(),
+        }
+    }
+
+    /// Returns the original error returned by [`std::fs`].
+    ///
+    /// See [`original_error_of`].
+    pub fn original_error(&self) -> &'a io::Error {
+        let mut original = self.inner.source();
+        while let Some(inner) = original.get_ref().and_then(|err| Inner::downcast(err)) {
+            original = inner.source();
+        }
+        original
+    }
+
+    /// Writes the message fs-err uses by default to describe the operation,
+    /// such as ``failed to open file `foo.txt` ``, or the template set with
+    /// [`set_message_template`] for it.
+    ///
+    /// This does not include the original error. It can be used to fall back to
+    /// the default message from a custom [`MessageFormatter`].
+    pub fn write_default_message(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.write_message(formatter, false)
+    }
+
+    /// Returns the error message with the operation and paths highlighted
+    /// using ANSI escape codes, for printing to a terminal.
+    ///
+    /// Paths are only highlighted in the default message and in templates set
+    /// with [`set_message_template`], not in messages written by a custom
+    /// [`MessageFormatter`].
+    ///
+    /// ```no_run
+    /// if let Err(err) = fs_err::read_to_string("foo.txt") {
+    ///     match fs_err::context_of(&err) {
+    ///         Some(context) => eprintln!("{}", context.to_colored_string()),
+    ///         None => eprintln!("{}", err),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "color")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "color")))]
+    pub fn to_colored_string(&self) -> String {
+        struct Colored<'a>(&'a ErrorContext<'a>);
+
+        impl fmt::Display for Colored<'_> {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt_error(formatter, true)
+            }
+        }
+
+        Colored(self).to_string()
+    }
+
+    fn new(inner: Inner<'a>) -> Self {
+        ErrorContext { inner }
+    }
+
+    fn write_message(&self, formatter: &mut fmt::Formatter, color: bool) -> fmt::Result {
+        #[cfg(rustc_1_63)]
+        {
+            if let Some(template) = hooks::message_template().and_then(|f| f(self.operation())) {
+                return self.write_template(formatter, template, color);
+            }
+        }
+
+        match self.inner {
+            Inner::Path(err) => err.fmt_message(formatter, color),
+            Inner::SourceDest(err) => err.fmt_message(formatter, color),
+        }
+    }
+
+    #[cfg(rustc_1_63)]
+    fn write_template(
+        &self,
+        formatter: &mut fmt::Formatter,
+        mut template: &str,
+        color: bool,
+    ) -> fmt::Result {
+        let path = DisplayPath::new(self.path(), color);
+        let dest_path = self
+            .dest_path()
+            .map(|dest_path| DisplayPath::new(dest_path, color));
+
+        while let Some(start) = template.find('{') {
+            formatter.write_str(&template[..start])?;
+            template = &template[start..];
+
+            if template.starts_with("{path}") || template.starts_with("{from}") {
+                write!(formatter, "{}", path)?;
+                template = &template["{path}".len()..];
+            } else if let Some(dest_path) =
+                dest_path.as_ref().filter(|_| template.starts_with("{to}"))
+            {
+                write!(formatter, "{}", dest_path)?;
+                template = &template["{to}".len()..];
+            } else {
+                formatter.write_str("{")?;
+                template = &template[1..];
+            }
+        }
+        formatter.write_str(template)
+    }
+
+    fn fmt_message(&self, formatter: &mut fmt::Formatter, color: bool) -> fmt::Result {
+        #[cfg(rustc_1_63)]
+        {
+            if let Some(message_formatter) = hooks::message_formatter() {
+                return message_formatter(self, formatter);
+            }
+        }
+
+        self.write_message(formatter, color)
+    }
+
+    fn fmt_error(&self, formatter: &mut fmt::Formatter, color: bool) -> fmt::Result {
+        if color {
+            formatter.write_str(BOLD)?;
+            self.fmt_message(formatter, color)?;
+            formatter.write_str(RESET)?;
+        } else {
+            self.fmt_message(formatter, color)?;
+        }
+
+        #[cfg(feature = "caller_location")]
+        {
+            if let Some(location) = self.location() {
+                write!(formatter, " at {}", location)?;
+            }
+        }
+
+        if let Some(diagnostic) = self.diagnostic() {
+            write!(formatter, " ({})", diagnostic)?;
+        }
+
+        // The `expose_original_error` feature indicates the caller should display the original error,
+        // unless it was explicitly requested with the alternate flag (`{:#}`).
+        if formatter.alternate() {
+            write!(formatter, ": {:#}", self.inner.source())?;
+        } else {
+            #[cfg(not(feature = "expose_original_error"))]
+            write!(formatter, ": {}", self.inner.source())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ErrorContext<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_error(formatter, false)
+    }
+}
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const DEFAULT_COLOR: &str = "\x1b[39m";
+const RESET: &str = "\x1b[0m";
+
+/// Displays a path in an error message, after applying the path sanitizer.
+struct DisplayPath<'a> {
+    path: Cow<'a, Path>,
+    color: bool,
+}
+
+impl<'a> DisplayPath<'a> {
+    fn new(path: &'a Path, color: bool) -> Self {
+        DisplayPath {
+            path: sanitize_path(path),
+            color,
+        }
+    }
+}
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.color {
+            write!(
+                formatter,
+                "{}{}{}",
+                CYAN,
+                self.path.display(),
+                DEFAULT_COLOR
+            )
+        } else {
+            write!(formatter, "{}", self.path.display())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ErrorContext<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("ErrorContext", 6)?;
+        state.serialize_field("operation", &self.operation())?;
+        state.serialize_field("path", &sanitize_path(self.path()).to_string_lossy())?;
+        state.serialize_field(
+            "dest_path",
+            &self
+                .dest_path()
+                .map(|path| sanitize_path(path).to_string_lossy().into_owned()),
+        )?;
+        state.serialize_field("os_error", &self.original_error().raw_os_error())?;
+        state.serialize_field(
+            "location",
+            &self.location().map(|location| location.to_string()),
+        )?;
+        state.serialize_field("message", &format!("{:#}", self))?;
+        state.end()
+    }
+}
+
+/// A function which writes the message describing a failed operation.
+///
+/// See [`set_message_formatter`].
+pub type MessageFormatter = fn(&ErrorContext<'_>, &mut fmt::Formatter<'_>) -> fmt::Result;
+
+/// Sets the function used to describe failed operations in error messages.
+///
+/// The formatter replaces the default description of the operation and its
+/// path(s), such as ``failed to open file `foo.txt` ``. The original error is
+/// still appended as usual. This applies to all errors returned by fs-err,
+/// including errors which were created before the formatter was set.
+///
+/// This function is intended to be called once at the start of the program.
+/// [`ErrorContext::write_default_message`] can be used to fall back to the
+/// default message.
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// use std::fmt;
+///
+/// fn format(context: &fs_err::ErrorContext, f: &mut fmt::Formatter) -> fmt::Result {
+///     match context.dest_path() {
+///         Some(dest) => write!(
+///             f,
+///             "{:?} \"{}\" -> \"{}\"",
+///             context.operation(),
+///             context.path().display(),
+///             dest.display()
+///         ),
+///         None => write!(f, "{:?} \"{}\"", context.operation(), context.path().display()),
+///     }
+/// }
+///
+/// fs_err::set_message_formatter(format);
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_message_formatter(formatter: MessageFormatter) {
+    *hooks::MESSAGE_FORMATTER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(formatter);
+}
+
+/// A function which returns the message template used to describe a failed
+/// operation, or `None` to use the default message.
+///
+/// See [`set_message_template`].
+pub type MessageTemplate = fn(Operation) -> Option<&'static str>;
+
+/// Sets the function used to look up the message template for each operation.
+///
+/// This allows messages to be translated without reimplementing how fs-err
+/// describes operations and paths. In a template, `{path}` is replaced with the
+/// path of the operation, and `{from}` and `{to}` are replaced with the source
+/// and destination paths of operations such as [`copy`](crate::copy). Paths
+/// are sanitized as described in [`set_path_sanitizer`], and the original error
+/// is appended to the message as usual.
+///
+/// A [`MessageFormatter`] takes precedence over templates, but can use them by
+/// calling [`ErrorContext::write_default_message`].
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// use fs_err::Operation;
+///
+/// fn template(operation: Operation) -> Option<&'static str> {
+///     match operation {
+///         Operation::OpenFile => Some("impossible d'ouvrir le fichier `{path}`"),
+///         Operation::Copy => Some("impossible de copier {from} vers {to}"),
+///         _ => None,
+///     }
+/// }
+///
+/// fs_err::set_message_template(template);
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_message_template(template: MessageTemplate) {
+    *hooks::MESSAGE_TEMPLATE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(template);
+}
+
+/// A function which rewrites a path before it is included in an error message.
+///
+/// See [`set_path_sanitizer`].
+pub type PathSanitizer = fn(&Path) -> Cow<'_, Path>;
+
+/// Sets the function used to rewrite paths before they are included in error
+/// messages.
+///
+/// This can be used to remove sensitive information, such as user names and
+/// home directories, from errors which are sent elsewhere. The sanitizer is
+/// applied when errors are displayed and serialized, including errors which
+/// were created before it was set. Paths returned by [`ErrorContext::path`] and
+/// [`ErrorContext::dest_path`] are not affected, so a custom
+/// [`MessageFormatter`] should apply its own sanitization.
+///
+/// Paths included by the `diagnostics` feature are sanitized when the error is
+/// created, so the sanitizer should be set at the start of the program.
+///
+/// The sanitizer also applies to the paths in the message of a
+/// [`SlowOperation`](crate::SlowOperation), but not to those returned by its
+/// methods.
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// use std::borrow::Cow;
+/// use std::path::Path;
+///
+/// fn sanitize(path: &Path) -> Cow<'_, Path> {
+///     match std::env::var_os("HOME") {
+///         Some(home) => match path.strip_prefix(home) {
+///             Ok(rest) => Cow::Owned(Path::new("~").join(rest)),
+///             Err(_) => Cow::Borrowed(path),
+///         },
+///         None => Cow::Borrowed(path),
+///     }
+/// }
+///
+/// fs_err::set_path_sanitizer(sanitize);
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_path_sanitizer(sanitizer: PathSanitizer) {
+    *hooks::PATH_SANITIZER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(sanitizer);
+}
+
+/// Applies the sanitizer set with [`set_path_sanitizer`] to `path`.
+pub(crate) fn sanitize_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(rustc_1_63)]
+    {
+        if let Some(sanitizer) = hooks::path_sanitizer() {
+            return sanitizer(path);
+        }
+    }
+
+    Cow::Borrowed(path)
+}
+
+/// A function which handles an error that occurred while a value was being
+/// dropped.
+///
+/// See [`set_drop_error_handler`].
+pub type DropErrorHandler = fn(io::Error);
+
+/// Sets the function called with errors which occur when fs-err values are
+/// dropped, such as a [`TempDir`](crate::TempDir) failing to remove its
+/// directory.
+///
+/// Since `Drop` cannot return errors, they are ignored unless a handler is set.
+/// The errors include the path which could not be cleaned up, like any other
+/// error from fs-err. Types which clean up when dropped also have a method
+/// such as [`TempDir::close`](crate::TempDir::close) which returns the error
+/// instead.
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// fs_err::set_drop_error_handler(|err| eprintln!("warning: {}", err));
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_drop_error_handler(handler: DropErrorHandler) {
+    *hooks::DROP_ERROR_HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(handler);
+}
+
+/// Passes an error which occurred while dropping a value to the handler set
+/// with [`set_drop_error_handler`], if any.
+pub(crate) fn report_drop_error(err: io::Error) {
+    #[cfg(rustc_1_63)]
+    {
+        if let Some(handler) = hooks::drop_error_handler() {
+            handler(err);
+        }
+    }
+    #[cfg(not(rustc_1_63))]
+    drop(err);
+}
+
+#[cfg(rustc_1_63)]
+mod hooks {
+    use super::{DropErrorHandler, MessageFormatter, MessageTemplate, PathSanitizer};
+    use std::sync::{PoisonError, RwLock};
+
+    pub(super) static MESSAGE_FORMATTER: RwLock<Option<MessageFormatter>> = RwLock::new(None);
+
+    pub(super) static MESSAGE_TEMPLATE: RwLock<Option<MessageTemplate>> = RwLock::new(None);
+
+    pub(super) static PATH_SANITIZER: RwLock<Option<PathSanitizer>> = RwLock::new(None);
+
+    pub(super) static DROP_ERROR_HANDLER: RwLock<Option<DropErrorHandler>> = RwLock::new(None);
+
+    pub(super) fn message_formatter() -> Option<MessageFormatter> {
+        *MESSAGE_FORMATTER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(super) fn message_template() -> Option<MessageTemplate> {
+        *MESSAGE_TEMPLATE
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(super) fn path_sanitizer() -> Option<PathSanitizer> {
+        *PATH_SANITIZER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(super) fn drop_error_handler() -> Option<DropErrorHandler> {
+        *DROP_ERROR_HANDLER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// An error created by this crate, found inside an `io::Error`.
+#[derive(Debug, Clone, Copy)]
+enum Inner<'a> {
+    Path(&'a Error),
+    SourceDest(&'a SourceDestError),
+}
+
+impl<'a> Inner<'a> {
+    fn source(&self) -> &'a io::Error {
+        match *self {
+            Inner::Path(err) => &err.source,
+            Inner::SourceDest(err) => &err.source,
+        }
+    }
+
+    /// Searches `err` and its sources for the first error created by this crate.
+    fn find(err: &'a io::Error) -> Option<Self> {
+        let mut next: Option<&(dyn StdError + 'static)> = Some(err);
+        while let Some(err) = next {
+            if let Some(inner) = Inner::downcast(err) {
+                return Some(inner);
+            }
+
+            // `io::Error::source` skips over the error it wraps, so look at it directly.
+            next = match err.downcast_ref::<io::Error>() {
+                Some(err) => match err.get_ref() {
+                    Some(inner) => Some(inner),
+                    None => err.source(),
+                },
+                None => err.source(),
+            };
+        }
+        None
+    }
+
+    fn downcast(err: &'a (dyn StdError + 'static)) -> Option<Self> {
+        if let Some(err) = err.downcast_ref::<Error>() {
+            return Some(Inner::Path(err));
+        }
+        err.downcast_ref::<SourceDestError>().map(Inner::SourceDest)
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum ErrorKind {
     OpenFile,
+    OpenAppend,
     CreateFile,
+    #[allow(dead_code)]
+    CreateNewFile,
+    CreateTempFile,
     CreateDir,
+    CreateTempDir,
     SyncFile,
+    #[cfg(unix)]
+    SyncDir,
+    SyncRange {
+        offset: u64,
+        len: u64,
+    },
     SetLen,
+    Allocate {
+        len: u64,
+    },
     Metadata,
     Clone,
     SetPermissions,
+    #[cfg(unix)]
+    CreateFileWithMode {
+        mode: u32,
+    },
+    #[cfg(unix)]
+    SetMode {
+        mode: u32,
+    },
+    #[cfg(not(unix))]
+    SetReadonly {
+        readonly: bool,
+    },
+    #[allow(dead_code)]
+    SetTimes,
+    #[allow(dead_code)]
+    Lock,
+    #[allow(dead_code)]
+    LockShared,
+    #[allow(dead_code)]
+    LockRetry {
+        attempts: u32,
+        waited: std::time::Duration,
+    },
+    #[allow(dead_code)]
+    Unlock,
     Read,
+    ReadLine {
+        line: u64,
+    },
     Seek,
     Write,
+    Append,
     Flush,
+    ReadBack,
+    Verify,
+    #[cfg(feature = "hash")]
+    ChecksumMismatch,
     ReadDir,
     RemoveFile,
+    RemoveTempFile,
     RemoveDir,
+    RemoveTempDir,
+    #[cfg(feature = "trash")]
+    TrashFile,
+    #[cfg(feature = "trash")]
+    TrashDir,
+    MoveRemoveFile,
+    MoveRemoveDir,
+    ShredOpen,
+    ShredOverwrite {
+        pass: u32,
+        passes: u32,
+    },
+    ShredSync {
+        pass: u32,
+        passes: u32,
+    },
+    ShredRemove,
+    LockFileHeld {
+        pid: Option<u32>,
+    },
     Canonicalize,
+    #[allow(dead_code)]
+    Absolute,
     ReadLink,
     SymlinkMetadata,
     #[allow(dead_code)]
     FileExists,
 
     #[cfg(windows)]
-    SeekRead,
+    SeekRead {
+        len: usize,
+        offset: u64,
+    },
     #[cfg(windows)]
-    SeekWrite,
+    SeekWrite {
+        len: usize,
+        offset: u64,
+    },
 
     #[cfg(unix)]
-    ReadAt,
+    ReadAt {
+        len: usize,
+        offset: u64,
+    },
     #[cfg(unix)]
-    WriteAt,
+    WriteAt {
+        len: usize,
+        offset: u64,
+    },
+    #[cfg(unix)]
+    ReadExactAt {
+        len: usize,
+        offset: u64,
+    },
+    #[cfg(unix)]
+    WriteAllAt {
+        len: usize,
+        offset: u64,
+    },
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    Chown,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    Lchown,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    Fadvise {
+        advice: &'static str,
+    },
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    OpenDirect,
+    OpenNoFollow,
+    #[cfg(unix)]
+    DirectIo {
+        alignment: usize,
+    },
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    ReadXattrs,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    WriteXattrs,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    OpenAt2 {
+        reason: Option<&'static str>,
+    },
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    OpenDir,
+}
+
+impl ErrorKind {
+    /// Converts this kind to its public [`Operation`].
+    pub(crate) fn operation(self) -> Operation {
+        use ErrorKind as E;
+
+        match self {
+            E::OpenFile | E::OpenAppend => Operation::OpenFile,
+            E::CreateFile => Operation::CreateFile,
+            E::CreateNewFile | E::CreateTempFile => Operation::CreateNewFile,
+            E::CreateDir | E::CreateTempDir => Operation::CreateDir,
+            E::SyncFile => Operation::SyncFile,
+            #[cfg(unix)]
+            E::SyncDir => Operation::SyncDir,
+            E::SyncRange { .. } => Operation::SyncFile,
+            E::SetLen => Operation::SetLen,
+            E::Allocate { .. } => Operation::Allocate,
+            E::Metadata => Operation::Metadata,
+            E::Clone => Operation::Clone,
+            E::SetPermissions => Operation::SetPermissions,
+            #[cfg(unix)]
+            E::CreateFileWithMode { .. } => Operation::CreateFile,
+            #[cfg(unix)]
+            E::SetMode { .. } => Operation::SetPermissions,
+            #[cfg(not(unix))]
+            E::SetReadonly { .. } => Operation::SetPermissions,
+            E::SetTimes => Operation::SetTimes,
+            E::Lock => Operation::Lock,
+            E::LockShared => Operation::LockShared,
+            E::LockRetry { .. } => Operation::Lock,
+            E::Unlock => Operation::Unlock,
+            E::Read | E::ReadLine { .. } => Operation::Read,
+            E::Seek => Operation::Seek,
+            E::Write | E::Append => Operation::Write,
+            E::ReadBack => Operation::Read,
+            E::Verify => Operation::Verify,
+            #[cfg(feature = "hash")]
+            E::ChecksumMismatch => Operation::Verify,
+            E::Flush => Operation::Flush,
+            E::ReadDir => Operation::ReadDir,
+            E::RemoveFile | E::RemoveTempFile | E::MoveRemoveFile => Operation::RemoveFile,
+            E::RemoveDir | E::RemoveTempDir | E::MoveRemoveDir => Operation::RemoveDir,
+            #[cfg(feature = "trash")]
+            E::TrashFile | E::TrashDir => Operation::Trash,
+            E::ShredOpen => Operation::OpenFile,
+            E::ShredOverwrite { .. } => Operation::Write,
+            E::ShredSync { .. } => Operation::SyncFile,
+            E::ShredRemove => Operation::RemoveFile,
+            E::LockFileHeld { .. } => Operation::Lock,
+            E::Canonicalize => Operation::Canonicalize,
+            E::Absolute => Operation::Absolute,
+            E::ReadLink => Operation::ReadLink,
+            E::SymlinkMetadata => Operation::SymlinkMetadata,
+            E::FileExists => Operation::FileExists,
+
+            #[cfg(windows)]
+            E::SeekRead { .. } => Operation::SeekRead,
+            #[cfg(windows)]
+            E::SeekWrite { .. } => Operation::SeekWrite,
+
+            #[cfg(unix)]
+            E::ReadAt { .. } => Operation::ReadAt,
+            #[cfg(unix)]
+            E::WriteAt { .. } => Operation::WriteAt,
+            #[cfg(unix)]
+            E::ReadExactAt { .. } => Operation::ReadExactAt,
+            #[cfg(unix)]
+            E::WriteAllAt { .. } => Operation::WriteAllAt,
+            #[cfg(unix)]
+            E::Chown => Operation::Chown,
+            #[cfg(unix)]
+            E::Lchown => Operation::Lchown,
+            #[cfg(unix)]
+            E::Fadvise { .. } => Operation::Fadvise,
+            #[cfg(unix)]
+            E::OpenDirect => Operation::OpenFile,
+            E::OpenNoFollow => Operation::OpenFile,
+            #[cfg(unix)]
+            E::DirectIo { .. } => Operation::DirectIo,
+            #[cfg(unix)]
+            E::ReadXattrs => Operation::ReadXattrs,
+            #[cfg(unix)]
+            E::WriteXattrs => Operation::WriteXattrs,
+            #[cfg(unix)]
+            E::OpenAt2 { .. } => Operation::OpenFile,
+            #[cfg(unix)]
+            E::OpenDir => Operation::OpenFile,
+        }
+    }
 }
 
 /// Contains an IO error that has a file path attached.
@@ -46,44 +1019,227 @@ pub(crate) struct Error {
     kind: ErrorKind,
     source: io::Error,
     path: PathBuf,
+    location: Option<&'static Location<'static>>,
+    diagnostic: Option<String>,
 }
 
 impl Error {
     pub fn build(source: io::Error, kind: ErrorKind, path: impl Into<PathBuf>) -> io::Error {
+        Self::build_at(source, kind, path, None)
+    }
+
+    /// Like [`Error::build`], but records the location of the caller when used
+    /// from `#[track_caller]` functions.
+    #[cfg_attr(not(rustc_1_89), allow(dead_code))]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn build_tracked(
+        source: io::Error,
+        kind: ErrorKind,
+        path: impl Into<PathBuf>,
+    ) -> io::Error {
+        Self::build_at(source, kind, path, caller())
+    }
+
+    /// Like [`Error::build`], for a failed attempt to write `len` bytes.
+    pub fn build_write(
+        source: io::Error,
+        kind: ErrorKind,
+        path: impl Into<PathBuf>,
+        len: usize,
+    ) -> io::Error {
+        Self::build_with_len(source, kind, path.into(), None, Some(len))
+    }
+
+    fn build_at(
+        source: io::Error,
+        kind: ErrorKind,
+        path: impl Into<PathBuf>,
+        location: Option<&'static Location<'static>>,
+    ) -> io::Error {
+        Self::build_with_len(source, kind, path.into(), location, None)
+    }
+
+    /// Like [`Error::build`], but without a diagnostic, for errors built in
+    /// async code. Gathering a diagnostic makes blocking filesystem calls on the
+    /// path which just failed, which would block the executor.
+    #[cfg(feature = "tokio")]
+    pub fn build_async(source: io::Error, kind: ErrorKind, path: impl Into<PathBuf>) -> io::Error {
+        Self::from_parts(source, kind, path.into(), None, None)
+    }
+
+    fn build_with_len(
+        source: io::Error,
+        kind: ErrorKind,
+        path: PathBuf,
+        location: Option<&'static Location<'static>>,
+        len: Option<usize>,
+    ) -> io::Error {
+        let diagnostic = diagnostics::diagnose(&source, &[&path], len);
+        Self::from_parts(source, kind, path, location, diagnostic)
+    }
+
+    fn from_parts(
+        source: io::Error,
+        kind: ErrorKind,
+        path: PathBuf,
+        location: Option<&'static Location<'static>>,
+        diagnostic: Option<String>,
+    ) -> io::Error {
         io::Error::new(
             source.kind(),
             Self {
                 kind,
                 source,
-                path: path.into(),
+                path,
+                location,
+                diagnostic,
             },
         )
     }
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+impl Error {
+    fn fmt_message(&self, formatter: &mut fmt::Formatter, color: bool) -> fmt::Result {
         use ErrorKind as E;
 
-        let path = self.path.display();
+        let path = DisplayPath::new(&self.path, color);
 
         match self.kind {
             E::OpenFile => write!(formatter, "failed to open file `{}`", path),
+            E::OpenAppend => write!(formatter, "failed to open file `{}` for appending", path),
             E::CreateFile => write!(formatter, "failed to create file `{}`", path),
+            E::CreateNewFile => write!(formatter, "failed to create new file `{}`", path),
+            E::CreateTempFile => write!(formatter, "failed to create temporary file `{}`", path),
             E::CreateDir => write!(formatter, "failed to create directory `{}`", path),
+            E::CreateTempDir => {
+                write!(formatter, "failed to create temporary directory `{}`", path)
+            }
             E::SyncFile => write!(formatter, "failed to sync file `{}`", path),
+            #[cfg(unix)]
+            E::SyncDir => write!(formatter, "failed to sync directory `{}`", path),
+            E::SyncRange { offset, len: 0 } => write!(
+                formatter,
+                "failed to sync file `{}` from offset {}",
+                path, offset
+            ),
+            E::SyncRange { offset, len } => write!(
+                formatter,
+                "failed to sync {} bytes at offset {} of file `{}`",
+                len, offset, path
+            ),
             E::SetLen => write!(formatter, "failed to set length of file `{}`", path),
+            E::Allocate { len } => write!(
+                formatter,
+                "failed to allocate {} bytes for file `{}`",
+                len, path
+            ),
             E::Metadata => write!(formatter, "failed to query metadata of file `{}`", path),
             E::Clone => write!(formatter, "failed to clone handle for file `{}`", path),
             E::SetPermissions => write!(formatter, "failed to set permissions for file `{}`", path),
+            #[cfg(unix)]
+            E::CreateFileWithMode { mode } => write!(
+                formatter,
+                "failed to create file `{}` with mode {:#o}",
+                path, mode
+            ),
+            #[cfg(unix)]
+            E::SetMode { mode } => write!(
+                formatter,
+                "failed to set mode {:#o} for file `{}`",
+                mode, path
+            ),
+            #[cfg(not(unix))]
+            E::SetReadonly { readonly: true } => {
+                write!(formatter, "failed to make file `{}` read-only", path)
+            }
+            #[cfg(not(unix))]
+            E::SetReadonly { readonly: false } => {
+                write!(formatter, "failed to make file `{}` writable", path)
+            }
+            E::SetTimes => write!(formatter, "failed to set times for file `{}`", path),
+            E::Lock => write!(formatter, "failed to lock file `{}`", path),
+            E::LockShared => write!(
+                formatter,
+                "failed to acquire shared lock on file `{}`",
+                path
+            ),
+            E::LockRetry { attempts, waited } => write!(
+                formatter,
+                "failed to lock file `{}` after {} attempts over {:?}",
+                path, attempts, waited
+            ),
+            E::Unlock => write!(formatter, "failed to unlock file `{}`", path),
             E::Read => write!(formatter, "failed to read from file `{}`", path),
+            E::ReadLine { line } => {
+                write!(formatter, "failed to read line {} of file `{}`", line, path)
+            }
             E::Seek => write!(formatter, "failed to seek in file `{}`", path),
             E::Write => write!(formatter, "failed to write to file `{}`", path),
+            E::Append => write!(formatter, "failed to append to file `{}`", path),
             E::Flush => write!(formatter, "failed to flush file `{}`", path),
+            E::ReadBack => write!(formatter, "failed to read back file `{}`", path),
+            E::Verify => write!(
+                formatter,
+                "contents of file `{}` do not match what was written",
+                path
+            ),
+            #[cfg(feature = "hash")]
+            E::ChecksumMismatch => write!(formatter, "checksum of file `{}` does not match", path),
             E::ReadDir => write!(formatter, "failed to read directory `{}`", path),
             E::RemoveFile => write!(formatter, "failed to remove file `{}`", path),
+            E::RemoveTempFile => write!(formatter, "failed to remove temporary file `{}`", path),
             E::RemoveDir => write!(formatter, "failed to remove directory `{}`", path),
+            E::RemoveTempDir => {
+                write!(formatter, "failed to remove temporary directory `{}`", path)
+            }
+            #[cfg(feature = "trash")]
+            E::TrashFile => write!(formatter, "failed to move file `{}` to the trash", path),
+            #[cfg(feature = "trash")]
+            E::TrashDir => write!(
+                formatter,
+                "failed to move directory `{}` to the trash",
+                path
+            ),
+            E::MoveRemoveFile => write!(
+                formatter,
+                "failed to remove file `{}` after copying it to another filesystem",
+                path
+            ),
+            E::MoveRemoveDir => write!(
+                formatter,
+                "failed to remove directory `{}` after copying it to another filesystem",
+                path
+            ),
+            E::ShredOpen => write!(
+                formatter,
+                "failed to open file `{}` to overwrite it before removing it",
+                path
+            ),
+            E::ShredOverwrite { pass, passes } => write!(
+                formatter,
+                "failed to overwrite file `{}` (pass {} of {})",
+                path, pass, passes
+            ),
+            E::ShredSync { pass, passes } => write!(
+                formatter,
+                "failed to sync file `{}` to disk after overwriting it (pass {} of {})",
+                path, pass, passes
+            ),
+            E::ShredRemove => write!(
+                formatter,
+                "failed to remove file `{}` after overwriting it",
+                path
+            ),
+            E::LockFileHeld { pid: Some(pid) } => {
+                write!(formatter, "lock file `{}` is held by process {}", path, pid)
+            }
+            E::LockFileHeld { pid: None } => write!(
+                formatter,
+                "lock file `{}` is already held by another process",
+                path
+            ),
             E::Canonicalize => write!(formatter, "failed to canonicalize path `{}`", path),
+            E::Absolute => write!(formatter, "failed to make path absolute `{}`", path),
             E::ReadLink => write!(formatter, "failed to read symbolic link `{}`", path),
             E::SymlinkMetadata => {
                 write!(formatter, "failed to query metadata of symlink `{}`", path)
@@ -91,21 +1247,96 @@ impl fmt::Display for Error {
             E::FileExists => write!(formatter, "failed to check file existence `{}`", path),
 
             #[cfg(windows)]
-            E::SeekRead => write!(formatter, "failed to seek and read from `{}`", path),
+            E::SeekRead { len, offset } => write!(
+                formatter,
+                "failed to seek and read {} bytes at offset {} from `{}`",
+                len, offset, path
+            ),
             #[cfg(windows)]
-            E::SeekWrite => write!(formatter, "failed to seek and write to `{}`", path),
+            E::SeekWrite { len, offset } => write!(
+                formatter,
+                "failed to seek and write {} bytes at offset {} to `{}`",
+                len, offset, path
+            ),
 
             #[cfg(unix)]
-            E::ReadAt => write!(formatter, "failed to read with offset from `{}`", path),
+            E::ReadAt { len, offset } => write!(
+                formatter,
+                "failed to read {} bytes at offset {} from `{}`",
+                len, offset, path
+            ),
             #[cfg(unix)]
-            E::WriteAt => write!(formatter, "failed to write with offset to `{}`", path),
-        }?;
-
-        // The `expose_original_error` feature indicates the caller should display the original error
-        #[cfg(not(feature = "expose_original_error"))]
-        write!(formatter, ": {}", self.source)?;
+            E::WriteAt { len, offset } => write!(
+                formatter,
+                "failed to write {} bytes at offset {} to `{}`",
+                len, offset, path
+            ),
+            #[cfg(unix)]
+            E::ReadExactAt { len, offset } => write!(
+                formatter,
+                "failed to read exactly {} bytes at offset {} from `{}`",
+                len, offset, path
+            ),
+            #[cfg(unix)]
+            E::WriteAllAt { len, offset } => write!(
+                formatter,
+                "failed to write all {} bytes at offset {} to `{}`",
+                len, offset, path
+            ),
+            #[cfg(unix)]
+            E::Chown => write!(formatter, "failed to change owner of `{}`", path),
+            #[cfg(unix)]
+            E::Lchown => write!(formatter, "failed to change owner of symlink `{}`", path),
+            #[cfg(unix)]
+            E::Fadvise { advice } => write!(
+                formatter,
+                "failed to apply `{}` advice to file `{}`",
+                advice, path
+            ),
+            #[cfg(unix)]
+            E::OpenDirect => write!(formatter, "failed to open file `{}` for direct I/O", path),
+            E::OpenNoFollow => write!(
+                formatter,
+                "refused to open file `{}` because it is a symbolic link",
+                path
+            ),
+            #[cfg(unix)]
+            E::DirectIo { alignment } => write!(
+                formatter,
+                "invalid direct I/O on file `{}` with {}-byte alignment",
+                path, alignment
+            ),
+            #[cfg(unix)]
+            E::ReadXattrs => write!(
+                formatter,
+                "failed to read extended attributes of file `{}`",
+                path
+            ),
+            #[cfg(unix)]
+            E::WriteXattrs => write!(
+                formatter,
+                "failed to set extended attributes of file `{}`",
+                path
+            ),
+            #[cfg(unix)]
+            E::OpenAt2 {
+                reason: Some(reason),
+            } => write!(
+                formatter,
+                "failed to open file `{}` because {}",
+                path, reason
+            ),
+            #[cfg(unix)]
+            E::OpenAt2 { reason: None } => write!(formatter, "failed to open file `{}`", path),
+            #[cfg(unix)]
+            E::OpenDir => write!(formatter, "failed to open directory `{}`", path),
+        }
+    }
+}
 
-        Ok(())
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&ErrorContext::new(Inner::Path(self)), formatter)
     }
 }
 
@@ -128,12 +1359,42 @@ impl StdError for Error {
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum SourceDestErrorKind {
     Copy,
+    CopyRead,
+    CopyWrite,
+    CopyRange,
+    MoveCopy,
     HardLink,
+    HardLinkOrCopy,
+    #[allow(dead_code)]
+    Reflink,
     Rename,
+    Persist,
+    Backup,
     SoftLink,
 
     #[cfg(unix)]
     Symlink,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    OpenAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    CreateAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    OpenDirAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    CreateDirAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    RemoveFileAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    RemoveDirAt,
+    #[cfg(unix)]
+    #[allow(dead_code)]
+    MetadataAt,
 
     #[cfg(windows)]
     SymlinkDir,
@@ -141,6 +1402,45 @@ pub(crate) enum SourceDestErrorKind {
     SymlinkFile,
 }
 
+impl SourceDestErrorKind {
+    /// Converts this kind to its public [`Operation`].
+    pub(crate) fn operation(self) -> Operation {
+        match self {
+            SourceDestErrorKind::Copy | SourceDestErrorKind::MoveCopy => Operation::Copy,
+            SourceDestErrorKind::HardLink => Operation::HardLink,
+            SourceDestErrorKind::HardLinkOrCopy => Operation::Copy,
+            SourceDestErrorKind::CopyRange => Operation::Copy,
+            SourceDestErrorKind::CopyRead => Operation::Read,
+            SourceDestErrorKind::CopyWrite => Operation::Write,
+            SourceDestErrorKind::Reflink => Operation::Reflink,
+            SourceDestErrorKind::Rename
+            | SourceDestErrorKind::Persist
+            | SourceDestErrorKind::Backup => Operation::Rename,
+            SourceDestErrorKind::SoftLink => Operation::SoftLink,
+
+            #[cfg(unix)]
+            SourceDestErrorKind::Symlink => Operation::Symlink,
+            #[cfg(unix)]
+            SourceDestErrorKind::OpenAt | SourceDestErrorKind::OpenDirAt => Operation::OpenFile,
+            #[cfg(unix)]
+            SourceDestErrorKind::CreateAt => Operation::CreateFile,
+            #[cfg(unix)]
+            SourceDestErrorKind::CreateDirAt => Operation::CreateDir,
+            #[cfg(unix)]
+            SourceDestErrorKind::RemoveFileAt => Operation::RemoveFile,
+            #[cfg(unix)]
+            SourceDestErrorKind::RemoveDirAt => Operation::RemoveDir,
+            #[cfg(unix)]
+            SourceDestErrorKind::MetadataAt => Operation::Metadata,
+
+            #[cfg(windows)]
+            SourceDestErrorKind::SymlinkDir => Operation::SymlinkDir,
+            #[cfg(windows)]
+            SourceDestErrorKind::SymlinkFile => Operation::SymlinkFile,
+        }
+    }
+}
+
 /// Error type used by functions like `fs::copy` that holds two paths.
 #[derive(Debug)]
 pub(crate) struct SourceDestError {
@@ -148,41 +1448,120 @@ pub(crate) struct SourceDestError {
     source: io::Error,
     from_path: PathBuf,
     to_path: PathBuf,
+    location: Option<&'static Location<'static>>,
+    diagnostic: Option<String>,
 }
 
 impl SourceDestError {
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
     pub fn build(
         source: io::Error,
         kind: SourceDestErrorKind,
         from_path: impl Into<PathBuf>,
         to_path: impl Into<PathBuf>,
+    ) -> io::Error {
+        Self::build_at(source, kind, from_path, to_path, None)
+    }
+
+    fn build_at(
+        source: io::Error,
+        kind: SourceDestErrorKind,
+        from_path: impl Into<PathBuf>,
+        to_path: impl Into<PathBuf>,
+        location: Option<&'static Location<'static>>,
+    ) -> io::Error {
+        let from_path = from_path.into();
+        let to_path = to_path.into();
+        let diagnostic = diagnostics::diagnose(&source, &[&from_path, &to_path], None);
+        Self::from_parts(source, kind, from_path, to_path, location, diagnostic)
+    }
+
+    /// Like [`SourceDestError::build`], but without a diagnostic, for errors
+    /// built in async code.
+    #[cfg(feature = "tokio")]
+    pub fn build_async(
+        source: io::Error,
+        kind: SourceDestErrorKind,
+        from_path: impl Into<PathBuf>,
+        to_path: impl Into<PathBuf>,
+    ) -> io::Error {
+        Self::from_parts(source, kind, from_path.into(), to_path.into(), None, None)
+    }
+
+    fn from_parts(
+        source: io::Error,
+        kind: SourceDestErrorKind,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        location: Option<&'static Location<'static>>,
+        diagnostic: Option<String>,
     ) -> io::Error {
         io::Error::new(
             source.kind(),
             Self {
                 kind,
                 source,
-                from_path: from_path.into(),
-                to_path: to_path.into(),
+                from_path,
+                to_path,
+                location,
+                diagnostic,
             },
         )
     }
 }
 
-impl fmt::Display for SourceDestError {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        let from = self.from_path.display();
-        let to = self.to_path.display();
+impl SourceDestError {
+    fn fmt_message(&self, formatter: &mut fmt::Formatter, color: bool) -> fmt::Result {
+        let from = DisplayPath::new(&self.from_path, color);
+        let to = DisplayPath::new(&self.to_path, color);
         match self.kind {
             SourceDestErrorKind::Copy => {
                 write!(formatter, "failed to copy file from {} to {}", from, to)
             }
+            SourceDestErrorKind::CopyRange => {
+                write!(formatter, "failed to copy data from {} to {}", from, to)
+            }
+            SourceDestErrorKind::CopyRead => {
+                write!(
+                    formatter,
+                    "failed to read from {} while copying to {}",
+                    from, to
+                )
+            }
+            SourceDestErrorKind::CopyWrite => {
+                write!(
+                    formatter,
+                    "failed to write to {} while copying from {}",
+                    to, from
+                )
+            }
+            SourceDestErrorKind::MoveCopy => write!(
+                formatter,
+                "failed to copy file from {} to {} while moving it across filesystems",
+                from, to
+            ),
             SourceDestErrorKind::HardLink => {
                 write!(formatter, "failed to hardlink file from {} to {}", from, to)
             }
+            SourceDestErrorKind::HardLinkOrCopy => write!(
+                formatter,
+                "failed to hardlink or copy file from {} to {}",
+                from, to
+            ),
+            SourceDestErrorKind::Reflink => {
+                write!(formatter, "failed to clone file from {} to {}", from, to)
+            }
             SourceDestErrorKind::Rename => {
                 write!(formatter, "failed to rename file from {} to {}", from, to)
             }
+            SourceDestErrorKind::Backup => {
+                write!(formatter, "failed to back up file {} to {}", from, to)
+            }
+            SourceDestErrorKind::Persist => write!(
+                formatter,
+                "failed to persist temporary file {} as {}",
+                from, to
+            ),
             SourceDestErrorKind::SoftLink => {
                 write!(formatter, "failed to softlink file from {} to {}", from, to)
             }
@@ -191,6 +1570,52 @@ impl fmt::Display for SourceDestError {
             SourceDestErrorKind::Symlink => {
                 write!(formatter, "failed to symlink file from {} to {}", from, to)
             }
+            #[cfg(unix)]
+            SourceDestErrorKind::OpenAt => {
+                write!(
+                    formatter,
+                    "failed to open file {} in directory {}",
+                    to, from
+                )
+            }
+            #[cfg(unix)]
+            SourceDestErrorKind::CreateAt => {
+                write!(
+                    formatter,
+                    "failed to create file {} in directory {}",
+                    to, from
+                )
+            }
+            #[cfg(unix)]
+            SourceDestErrorKind::OpenDirAt => write!(
+                formatter,
+                "failed to open directory {} in directory {}",
+                to, from
+            ),
+            #[cfg(unix)]
+            SourceDestErrorKind::CreateDirAt => write!(
+                formatter,
+                "failed to create directory {} in directory {}",
+                to, from
+            ),
+            #[cfg(unix)]
+            SourceDestErrorKind::RemoveFileAt => write!(
+                formatter,
+                "failed to remove file {} from directory {}",
+                to, from
+            ),
+            #[cfg(unix)]
+            SourceDestErrorKind::RemoveDirAt => write!(
+                formatter,
+                "failed to remove directory {} from directory {}",
+                to, from
+            ),
+            #[cfg(unix)]
+            SourceDestErrorKind::MetadataAt => write!(
+                formatter,
+                "failed to query metadata of {} in directory {}",
+                to, from
+            ),
 
             #[cfg(windows)]
             SourceDestErrorKind::SymlinkFile => {
@@ -200,13 +1625,13 @@ impl fmt::Display for SourceDestError {
             SourceDestErrorKind::SymlinkDir => {
                 write!(formatter, "failed to symlink dir from {} to {}", from, to)
             }
-        }?;
-
-        // The `expose_original_error` feature indicates the caller should display the original error
-        #[cfg(not(feature = "expose_original_error"))]
-        write!(formatter, ": {}", self.source)?;
+        }
+    }
+}
 
-        Ok(())
+impl fmt::Display for SourceDestError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&ErrorContext::new(Inner::SourceDest(self)), formatter)
     }
 }
 
@@ -225,3 +1650,119 @@ impl StdError for SourceDestError {
         Some(&self.source)
     }
 }
+
+/// Extension trait for wrapping the errors of [`std::fs`] functions.
+///
+/// Unlike calling [`Error::build`] from a closure, these methods record the
+/// location of the caller when used from `#[track_caller]` functions. Public
+/// functions which call them should be annotated with
+/// `#[cfg_attr(rustc_1_46, track_caller)]`.
+pub(crate) trait ResultExt<T> {
+    fn with_path(self, kind: ErrorKind, path: impl Into<PathBuf>) -> io::Result<T>;
+
+    /// Like [`ResultExt::with_path`], for a failed attempt to write `len` bytes.
+    fn with_write(self, kind: ErrorKind, path: impl Into<PathBuf>, len: usize) -> io::Result<T>;
+
+    fn with_paths(
+        self,
+        kind: SourceDestErrorKind,
+        from_path: impl Into<PathBuf>,
+        to_path: impl Into<PathBuf>,
+    ) -> io::Result<T>;
+}
+
+impl<T> ResultExt<T> for io::Result<T> {
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn with_path(self, kind: ErrorKind, path: impl Into<PathBuf>) -> io::Result<T> {
+        #[cfg(all(feature = "fault_injection", rustc_1_63))]
+        let (result, path) = {
+            let path = path.into();
+            (inject_fault(self, kind.operation(), &[&path]), path)
+        };
+        #[cfg(not(all(feature = "fault_injection", rustc_1_63)))]
+        let result = self;
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(source) => Err(Error::build_at(source, kind, path, caller())),
+        }
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    #[cfg_attr(
+        all(feature = "fault_injection", rustc_1_63),
+        allow(clippy::useless_conversion)
+    )]
+    fn with_write(self, kind: ErrorKind, path: impl Into<PathBuf>, len: usize) -> io::Result<T> {
+        #[cfg(all(feature = "fault_injection", rustc_1_63))]
+        let (result, path) = {
+            let path = path.into();
+            (inject_fault(self, kind.operation(), &[&path]), path)
+        };
+        #[cfg(not(all(feature = "fault_injection", rustc_1_63)))]
+        let result = self;
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(source) => Err(Error::build_with_len(
+                source,
+                kind,
+                path.into(),
+                caller(),
+                Some(len),
+            )),
+        }
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn with_paths(
+        self,
+        kind: SourceDestErrorKind,
+        from_path: impl Into<PathBuf>,
+        to_path: impl Into<PathBuf>,
+    ) -> io::Result<T> {
+        #[cfg(all(feature = "fault_injection", rustc_1_63))]
+        let (result, from_path, to_path) = {
+            let (from_path, to_path) = (from_path.into(), to_path.into());
+            let result = inject_fault(self, kind.operation(), &[&from_path, &to_path]);
+            (result, from_path, to_path)
+        };
+        #[cfg(not(all(feature = "fault_injection", rustc_1_63)))]
+        let result = self;
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(source) => Err(SourceDestError::build_at(
+                source,
+                kind,
+                from_path,
+                to_path,
+                caller(),
+            )),
+        }
+    }
+}
+
+/// Replaces a successful result with an error from a matching
+/// [`FaultRule`](crate::FaultRule), if any.
+#[cfg(all(feature = "fault_injection", rustc_1_63))]
+fn inject_fault<T>(result: io::Result<T>, operation: Operation, paths: &[&Path]) -> io::Result<T> {
+    match result {
+        Ok(value) => match crate::fault::check(operation, paths) {
+            Some(err) => Err(err),
+            None => Ok(value),
+        },
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(rustc_1_46)]
+#[track_caller]
+fn caller() -> Option<&'static Location<'static>> {
+    Some(Location::caller())
+}
+
+#[cfg(not(rustc_1_46))]
+fn caller() -> Option<&'static Location<'static>> {
+    None
+}
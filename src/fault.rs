@@ -0,0 +1,203 @@
+use std::io;
+use std::path::Path;
+use std::sync::{PoisonError, RwLock};
+
+use crate::random::random_u64;
+use crate::Operation;
+
+static RULES: RwLock<Vec<Rule>> = RwLock::new(Vec::new());
+
+/// A rule which makes matching fs-err operations fail, added with
+/// [`inject_fault`].
+///
+/// By default a rule matches every operation on every path and always fails.
+/// It can be narrowed down to an [`Operation`] and a path pattern, and made to
+/// fail only on the nth matching call or with some probability.
+///
+/// # Example
+///
+/// ```no_run
+/// use fs_err::{FaultRule, Operation};
+///
+/// // Fail the third write to a log file as if the disk were full.
+/// fs_err::inject_fault(
+///     FaultRule::os_error(28)
+///         .operation(Operation::Write)
+///         .path("*.log")
+///         .nth(3),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub struct FaultRule {
+    error: Fault,
+    operation: Option<Operation>,
+    pattern: Option<String>,
+    nth: Option<u64>,
+    probability: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Fault {
+    Kind(io::ErrorKind),
+    Os(i32),
+}
+
+impl FaultRule {
+    /// Creates a rule which fails with an error of the given kind.
+    pub fn new(kind: io::ErrorKind) -> FaultRule {
+        FaultRule::with_fault(Fault::Kind(kind))
+    }
+
+    /// Creates a rule which fails with the given OS error code, such as
+    /// `ENOSPC`, so that the message is the same as for a real failure.
+    pub fn os_error(code: i32) -> FaultRule {
+        FaultRule::with_fault(Fault::Os(code))
+    }
+
+    fn with_fault(error: Fault) -> FaultRule {
+        FaultRule {
+            error,
+            operation: None,
+            pattern: None,
+            nth: None,
+            probability: 1.0,
+        }
+    }
+
+    /// Only fails operations of this kind.
+    pub fn operation(mut self, operation: Operation) -> FaultRule {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Only fails operations on paths matching `pattern`, in which `*` matches
+    /// any sequence of characters, including path separators, and `?` matches
+    /// any single character. Operations with two paths, such as
+    /// [`copy`](crate::copy), match if either path does.
+    pub fn path<S: Into<String>>(mut self, pattern: S) -> FaultRule {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Only fails the nth matching operation, counting from 1.
+    pub fn nth(mut self, n: u64) -> FaultRule {
+        self.nth = Some(n);
+        self
+    }
+
+    /// Fails each matching operation with the given probability, between 0
+    /// and 1.
+    pub fn probability(mut self, probability: f64) -> FaultRule {
+        self.probability = probability;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Rule {
+    rule: FaultRule,
+    calls: u64,
+}
+
+/// Makes fs-err operations matching `rule` fail with a synthetic error.
+///
+/// This is intended for testing how a program handles filesystem errors. The
+/// injected errors have the same messages as real ones, for example
+/// ``failed to write to file `app.log`: No space left on device (os error
+/// 28)``, and can be inspected with [`operation_of`](crate::operation_of) and
+/// the other functions in the same way.
+///
+/// Faults are injected into synchronous functions and methods after the
+/// underlying operation has completed, replacing its result, so the operation
+/// still takes effect. Rules apply to all threads, and are checked in the
+/// order they were added until one fails the operation.
+///
+/// This function requires Rust 1.63 or later.
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub fn inject_fault(rule: FaultRule) {
+    RULES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Rule { rule, calls: 0 });
+}
+
+/// Removes all rules added with [`inject_fault`].
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub fn clear_faults() {
+    RULES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+/// Returns an error to inject into a successful operation, if a rule matches.
+pub(crate) fn check(operation: Operation, paths: &[&Path]) -> Option<io::Error> {
+    if RULES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_empty()
+    {
+        return None;
+    }
+
+    let mut rules = RULES.write().unwrap_or_else(PoisonError::into_inner);
+    for rule in rules.iter_mut() {
+        if !rule.matches(operation, paths) {
+            continue;
+        }
+        rule.calls += 1;
+        let fails = match rule.rule.nth {
+            Some(n) => rule.calls == n,
+            None => true,
+        };
+        if fails && random() < rule.rule.probability {
+            return Some(match rule.rule.error {
+                Fault::Kind(kind) => io::Error::from(kind),
+                Fault::Os(code) => io::Error::from_raw_os_error(code),
+            });
+        }
+    }
+    None
+}
+
+impl Rule {
+    fn matches(&self, operation: Operation, paths: &[&Path]) -> bool {
+        if let Some(expected) = self.rule.operation {
+            if expected != operation {
+                return false;
+            }
+        }
+        match self.rule.pattern {
+            Some(ref pattern) => paths.iter().any(|path| {
+                let path = path.to_string_lossy();
+                glob_match(pattern.as_bytes(), path.as_bytes())
+            }),
+            None => true,
+        }
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        Some((b'?', rest)) => match text.split_first() {
+            // Skip the rest of a multi-byte character.
+            Some((_, text)) => {
+                let skip = text.iter().take_while(|&&b| b & 0xc0 == 0x80).count();
+                glob_match(rest, &text[skip..])
+            }
+            None => false,
+        },
+        Some((&c, rest)) => match text.split_first() {
+            Some((&t, text)) => c == t && glob_match(rest, text),
+            None => false,
+        },
+    }
+}
+
+/// Returns a random number in `[0, 1)`.
+fn random() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -2,7 +2,9 @@ use std::fs;
 use std::io::{self, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 
-use crate::errors::{Error, ErrorKind};
+use crate::errors::{Error, ErrorKind, ResultExt, SourceDestErrorKind};
+use crate::metrics::Timer;
+use crate::Operation;
 
 /// Wrapper around [`std::fs::File`][std::fs::File] which adds more helpful
 /// information to all errors.
@@ -14,17 +16,6 @@ pub struct File {
     path: PathBuf,
 }
 
-// Opens a std File and returns it or an error generator which only needs the path to produce the error.
-// Exists for the `crate::read*` functions so they don't unconditionally build a PathBuf.
-pub(crate) fn open(path: &Path) -> Result<std::fs::File, impl FnOnce(PathBuf) -> io::Error> {
-    fs::File::open(path).map_err(|err| |path| Error::build(err, ErrorKind::OpenFile, path))
-}
-
-// like `open()` but for `crate::write`
-pub(crate) fn create(path: &Path) -> Result<std::fs::File, impl FnOnce(PathBuf) -> io::Error> {
-    fs::File::create(path).map_err(|err| |path| Error::build(err, ErrorKind::CreateFile, path))
-}
-
 /// Wrappers for methods from [`std::fs::File`][std::fs::File].
 ///
 /// [std::fs::File]: https://doc.rust-lang.org/stable/std/fs/struct.File.html
@@ -32,65 +23,104 @@ impl File {
     /// Attempts to open a file in read-only mode.
     ///
     /// Wrapper for [`File::open`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.open).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn open<P>(path: P) -> Result<Self, io::Error>
     where
         P: Into<PathBuf>,
     {
         let path = path.into();
-        match open(&path) {
-            Ok(file) => Ok(File::from_parts(file, path)),
-            Err(err_gen) => Err(err_gen(path)),
-        }
+        let timer = Timer::start(Operation::OpenFile, &path);
+        let file = timer.finish(fs::File::open(&path).with_path(ErrorKind::OpenFile, &path))?;
+        Ok(File::from_parts(file, path))
     }
 
     /// Opens a file in write-only mode.
     ///
     /// Wrapper for [`File::create`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.create).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn create<P>(path: P) -> Result<Self, io::Error>
     where
         P: Into<PathBuf>,
     {
         let path = path.into();
-        match create(&path) {
-            Ok(file) => Ok(File::from_parts(file, path)),
-            Err(err_gen) => Err(err_gen(path)),
-        }
+        let timer = Timer::start(Operation::CreateFile, &path);
+        let file = timer.finish(fs::File::create(&path).with_path(ErrorKind::CreateFile, &path))?;
+        Ok(File::from_parts(file, path))
+    }
+
+    /// Opens a file in read-write mode, failing if the file already exists.
+    ///
+    /// Wrapper for [`File::create_new`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.create_new).
+    #[cfg(rustc_1_77)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_new<P>(path: P) -> Result<Self, io::Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let timer = Timer::start(Operation::CreateNewFile, &path);
+        let file =
+            timer.finish(fs::File::create_new(&path).with_path(ErrorKind::CreateNewFile, &path))?;
+        Ok(File::from_parts(file, path))
+    }
+
+    /// Returns a new `OpenOptions` object.
+    ///
+    /// Wrapper for [`File::options`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.options).
+    pub fn options() -> crate::OpenOptions {
+        crate::OpenOptions::new()
     }
 
     /// Attempts to sync all OS-internal metadata to disk.
     ///
     /// Wrapper for [`File::sync_all`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.sync_all).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn sync_all(&self) -> Result<(), io::Error> {
-        self.file
+        let timer = Timer::start(Operation::SyncFile, &self.path);
+        let result = self
+            .file
             .sync_all()
-            .map_err(|source| self.error(source, ErrorKind::SyncFile))
+            .with_path(ErrorKind::SyncFile, &self.path);
+        timer.finish(result)
     }
 
     /// This function is similar to [`sync_all`], except that it might not synchronize file metadata to the filesystem.
     ///
     /// Wrapper for [`File::sync_data`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.sync_data).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn sync_data(&self) -> Result<(), io::Error> {
-        self.file
+        let timer = Timer::start(Operation::SyncFile, &self.path);
+        let result = self
+            .file
             .sync_data()
-            .map_err(|source| self.error(source, ErrorKind::SyncFile))
+            .with_path(ErrorKind::SyncFile, &self.path);
+        timer.finish(result)
     }
 
     /// Truncates or extends the underlying file, updating the size of this file to become `size`.
     ///
     /// Wrapper for [`File::set_len`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.set_len).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn set_len(&self, size: u64) -> Result<(), io::Error> {
-        self.file
+        let timer = Timer::start(Operation::SetLen, &self.path);
+        let result = self
+            .file
             .set_len(size)
-            .map_err(|source| self.error(source, ErrorKind::SetLen))
+            .with_path(ErrorKind::SetLen, &self.path);
+        timer.finish(result)
     }
 
     /// Queries metadata about the underlying file.
     ///
     /// Wrapper for [`File::metadata`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.metadata).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn metadata(&self) -> Result<fs::Metadata, io::Error> {
-        self.file
+        let timer = Timer::start(Operation::Metadata, &self.path);
+        let result = self
+            .file
             .metadata()
-            .map_err(|source| self.error(source, ErrorKind::Metadata))
+            .with_path(ErrorKind::Metadata, &self.path);
+        timer.finish(result)
     }
 
     /// Creates a new `File` instance that shares the same underlying file handle as the
@@ -98,6 +128,7 @@ impl File {
     /// instances simultaneously.
     ///
     /// Wrapper for [`File::try_clone`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.try_clone).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn try_clone(&self) -> Result<Self, io::Error> {
         self.file
             .try_clone()
@@ -105,16 +136,165 @@ impl File {
                 file,
                 path: self.path.clone(),
             })
-            .map_err(|source| self.error(source, ErrorKind::Clone))
+            .with_path(ErrorKind::Clone, &self.path)
     }
 
     /// Changes the permissions on the underlying file.
     ///
     /// Wrapper for [`File::set_permissions`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.set_permissions).
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn set_permissions(&self, perm: fs::Permissions) -> Result<(), io::Error> {
         self.file
             .set_permissions(perm)
-            .map_err(|source| self.error(source, ErrorKind::SetPermissions))
+            .with_path(ErrorKind::SetPermissions, &self.path)
+    }
+
+    /// Changes the timestamps of the underlying file.
+    ///
+    /// Wrapper for [`File::set_times`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.set_times).
+    #[cfg(rustc_1_75)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn set_times(&self, times: fs::FileTimes) -> Result<(), io::Error> {
+        self.file
+            .set_times(times)
+            .with_path(ErrorKind::SetTimes, &self.path)
+    }
+
+    /// Changes the modification time of the underlying file.
+    ///
+    /// Wrapper for [`File::set_modified`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.set_modified).
+    #[cfg(rustc_1_75)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn set_modified(&self, time: std::time::SystemTime) -> Result<(), io::Error> {
+        self.file
+            .set_modified(time)
+            .with_path(ErrorKind::SetTimes, &self.path)
+    }
+
+    /// Acquires an exclusive lock on the file, blocking until it can be acquired.
+    ///
+    /// Wrapper for [`File::lock`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.lock).
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn lock(&self) -> Result<(), io::Error> {
+        self.file.lock().with_path(ErrorKind::Lock, &self.path)
+    }
+
+    /// Acquires a shared lock on the file, blocking until it can be acquired.
+    ///
+    /// Wrapper for [`File::lock_shared`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.lock_shared).
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn lock_shared(&self) -> Result<(), io::Error> {
+        self.file
+            .lock_shared()
+            .with_path(ErrorKind::LockShared, &self.path)
+    }
+
+    /// Tries to acquire an exclusive lock on the file, without blocking.
+    ///
+    /// If the lock is held by someone else, [`TryLockError::WouldBlock`](fs::TryLockError::WouldBlock)
+    /// is returned. Other errors include the path of the file.
+    ///
+    /// Wrapper for [`File::try_lock`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.try_lock).
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn try_lock(&self) -> Result<(), fs::TryLockError> {
+        match self.file.try_lock() {
+            Err(fs::TryLockError::Error(source)) => Err(fs::TryLockError::Error(
+                Error::build_tracked(source, ErrorKind::Lock, &self.path),
+            )),
+            result => result,
+        }
+    }
+
+    /// Tries to acquire a shared lock on the file, without blocking.
+    ///
+    /// If an exclusive lock is held by someone else,
+    /// [`TryLockError::WouldBlock`](fs::TryLockError::WouldBlock) is returned.
+    /// Other errors include the path of the file.
+    ///
+    /// Wrapper for [`File::try_lock_shared`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.try_lock_shared).
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn try_lock_shared(&self) -> Result<(), fs::TryLockError> {
+        match self.file.try_lock_shared() {
+            Err(fs::TryLockError::Error(source)) => Err(fs::TryLockError::Error(
+                Error::build_tracked(source, ErrorKind::LockShared, &self.path),
+            )),
+            result => result,
+        }
+    }
+
+    /// Acquires an exclusive lock on the file, retrying according to `policy`
+    /// while the lock is held by someone else.
+    ///
+    /// If the lock could not be acquired after all attempts, the returned error
+    /// has kind [`io::ErrorKind::WouldBlock`] and its message includes the number
+    /// of attempts and how long was spent waiting. Other errors are returned
+    /// immediately.
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn lock_with_retry(&self, policy: &crate::LockRetryPolicy) -> Result<(), io::Error> {
+        let start = std::time::Instant::now();
+        let attempts = policy.max_attempts();
+        for attempt in 0..attempts {
+            match self.file.try_lock() {
+                Ok(()) => return Ok(()),
+                Err(fs::TryLockError::WouldBlock) => {}
+                Err(fs::TryLockError::Error(source)) => {
+                    return Err(Error::build_tracked(source, ErrorKind::Lock, &self.path))
+                }
+            }
+            if attempt + 1 < attempts {
+                std::thread::sleep(policy.delay(attempt));
+            }
+        }
+
+        let kind = ErrorKind::LockRetry {
+            attempts,
+            waited: start.elapsed(),
+        };
+        Err(Error::build_tracked(
+            io::ErrorKind::WouldBlock.into(),
+            kind,
+            &self.path,
+        ))
+    }
+
+    /// Releases all locks on the file.
+    ///
+    /// Wrapper for [`File::unlock`](https://doc.rust-lang.org/stable/std/fs/struct.File.html#method.unlock).
+    #[cfg(rustc_1_89)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn unlock(&self) -> Result<(), io::Error> {
+        self.file.unlock().with_path(ErrorKind::Unlock, &self.path)
+    }
+
+    /// Attempts to write multiple buffers to the file, retrying until all of
+    /// them have been written.
+    ///
+    /// This is equivalent to the unstable [`Write::write_all_vectored`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all_vectored),
+    /// and uses vectored writes on the underlying file. The slices in `bufs`
+    /// are modified to track the progress of the write.
+    #[cfg(rustc_1_81)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn write_all_vectored(&mut self, mut bufs: &mut [io::IoSlice<'_>]) -> io::Result<()> {
+        io::IoSlice::advance_slices(&mut bufs, 0);
+        while !bufs.is_empty() {
+            let len = bufs.iter().map(|buf| buf.len()).sum();
+            match self.file.write_vectored(bufs) {
+                Ok(0) => {
+                    let source =
+                        io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+                    return Err(source).with_write(ErrorKind::Write, &self.path, len);
+                }
+                Ok(n) => io::IoSlice::advance_slices(&mut bufs, n),
+                Err(source) if source.kind() == io::ErrorKind::Interrupted => {}
+                Err(source) => return Err(source).with_write(ErrorKind::Write, &self.path, len),
+            }
+        }
+        Ok(())
     }
 }
 
@@ -123,6 +303,39 @@ impl File {
 ///
 /// [std::fs::File]: https://doc.rust-lang.org/stable/std/fs/struct.File.html
 impl File {
+    /// Opens a file in read-only mode, failing if `path` refers to a symbolic
+    /// link.
+    ///
+    /// See [`OpenOptions::no_follow`](crate::OpenOptions::no_follow) for details.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open_no_follow<P>(path: P) -> Result<Self, io::Error>
+    where
+        P: Into<PathBuf>,
+    {
+        crate::OpenOptions::new()
+            .read(true)
+            .no_follow(true)
+            .open(path)
+    }
+
+    /// Opens a file in read-only mode and wraps it in a [`BufReader`](crate::BufReader).
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open_buffered<P>(path: P) -> Result<crate::BufReader, io::Error>
+    where
+        P: Into<PathBuf>,
+    {
+        crate::BufReader::open(path)
+    }
+
+    /// Opens a file in write-only mode and wraps it in a [`BufWriter`](crate::BufWriter).
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_buffered<P>(path: P) -> Result<crate::BufWriter, io::Error>
+    where
+        P: Into<PathBuf>,
+    {
+        crate::BufWriter::create(path)
+    }
+
     /// Creates a [`File`](struct.File.html) from a raw file and its path.
     pub fn from_parts<P>(file: fs::File, path: P) -> Self
     where
@@ -158,6 +371,79 @@ impl File {
         &self.path
     }
 
+    /// Syncs the data in `len` bytes of the file starting at `offset` to disk,
+    /// on a best-effort basis. A `len` of zero syncs up to the end of the file.
+    ///
+    /// On Linux with the `libc` feature, this writes out the range with
+    /// `sync_file_range` and waits for it to complete, which can be much
+    /// cheaper than [`sync_data`](File::sync_data) for a small part of a large
+    /// file. This does not flush metadata or the write cache of the disk, so it is not
+    /// enough for durability on its own. On other platforms, or if
+    /// `sync_file_range` is not supported, the whole file is synced with
+    /// `sync_data` instead.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn sync_range(&self, offset: u64, len: u64) -> Result<(), io::Error> {
+        sys::sync_range(&self.file, offset, len)
+            .with_path(ErrorKind::SyncRange { offset, len }, &self.path)
+    }
+
+    /// Allocates disk space for the file, so that writes up to `len` bytes
+    /// will not fail due to lack of space.
+    ///
+    /// If the file is smaller than `len`, it is extended to `len` bytes as if
+    /// by [`set_len`](File::set_len). Larger files are left unchanged.
+    ///
+    /// This uses `SetFileInformationByHandle` on Windows, and with the `libc`
+    /// feature, `posix_fallocate` on Linux and `fcntl(F_PREALLOCATE)` on macOS.
+    /// Otherwise the file is only extended, without allocating space.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn allocate(&self, len: u64) -> Result<(), io::Error> {
+        sys::allocate(&self.file, len).with_path(ErrorKind::Allocate { len }, &self.path)
+    }
+
+    /// Copies up to `len` bytes from the current position of this file to the
+    /// current position of `dst`, returning the number of bytes copied.
+    ///
+    /// Fewer than `len` bytes are only copied if the end of this file is
+    /// reached. On Linux with the `libc` feature, the data is copied by the
+    /// kernel using `copy_file_range`, or `sendfile` if that is not supported,
+    /// without passing through user space. Elsewhere, or if neither is
+    /// supported, the data is read into a buffer and written to `dst`.
+    ///
+    /// Errors include the paths of both files.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn copy_to(&self, dst: &File, len: u64) -> Result<u64, io::Error> {
+        let mut copied = sys::copy_range(&self.file, &dst.file, len).with_paths(
+            SourceDestErrorKind::CopyRange,
+            &self.path,
+            &dst.path,
+        )?;
+
+        let mut buf = [0; 8 * 1024];
+        while copied < len {
+            let max = (len - copied).min(buf.len() as u64) as usize;
+            let read = match (&self.file).read(&mut buf[..max]) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    return Err(err).with_paths(
+                        SourceDestErrorKind::CopyRead,
+                        &self.path,
+                        &dst.path,
+                    )
+                }
+            };
+            (&dst.file).write_all(&buf[..read]).with_paths(
+                SourceDestErrorKind::CopyWrite,
+                &self.path,
+                &dst.path,
+            )?;
+            copied += read as u64;
+        }
+        Ok(copied)
+    }
+
     /// Wrap the error in information specific to this `File` object.
     fn error(&self, source: io::Error, kind: ErrorKind) -> io::Error {
         Error::build(source, kind, &self.path)
@@ -166,29 +452,90 @@ impl File {
 
 impl Read for File {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.file
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = self
+            .file
             .read(buf)
-            .map_err(|source| self.error(source, ErrorKind::Read))
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
     }
 
     fn read_vectored(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> std::io::Result<usize> {
-        self.file
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = self
+            .file
             .read_vectored(bufs)
-            .map_err(|source| self.error(source, ErrorKind::Read))
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = self
+            .file
+            .read_to_end(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> std::io::Result<usize> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = self
+            .file
+            .read_to_string(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = self
+            .file
+            .read_exact(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
     }
 }
 
-impl<'a> Read for &'a File {
+impl Read for &File {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        (&self.file)
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = (&self.file)
             .read(buf)
-            .map_err(|source| self.error(source, ErrorKind::Read))
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
     }
 
     fn read_vectored(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> std::io::Result<usize> {
-        (&self.file)
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = (&self.file)
             .read_vectored(bufs)
-            .map_err(|source| self.error(source, ErrorKind::Read))
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = (&self.file)
+            .read_to_end(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> std::io::Result<usize> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = (&self.file)
+            .read_to_string(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        let timer = Timer::start_io(Operation::Read, &self.path);
+        let result = (&self.file)
+            .read_exact(buf)
+            .map_err(|source| self.error(source, ErrorKind::Read));
+        timer.finish(result)
     }
 }
 
@@ -204,60 +551,118 @@ impl Seek for File {
             .seek(pos)
             .map_err(|source| self.error(source, ErrorKind::Seek))
     }
+
+    #[cfg(rustc_1_55)]
+    fn rewind(&mut self) -> std::io::Result<()> {
+        self.file
+            .rewind()
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
+
+    #[cfg(rustc_1_51)]
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        self.file
+            .stream_position()
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
+
+    #[cfg(rustc_1_80)]
+    fn seek_relative(&mut self, offset: i64) -> std::io::Result<()> {
+        self.file
+            .seek_relative(offset)
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
 }
 
-impl<'a> Seek for &'a File {
+impl Seek for &File {
     fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
         (&self.file)
             .seek(pos)
             .map_err(|source| self.error(source, ErrorKind::Seek))
     }
+
+    #[cfg(rustc_1_55)]
+    fn rewind(&mut self) -> std::io::Result<()> {
+        (&self.file)
+            .rewind()
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
+
+    #[cfg(rustc_1_51)]
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        (&self.file)
+            .stream_position()
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
+
+    #[cfg(rustc_1_80)]
+    fn seek_relative(&mut self, offset: i64) -> std::io::Result<()> {
+        (&self.file)
+            .seek_relative(offset)
+            .map_err(|source| self.error(source, ErrorKind::Seek))
+    }
 }
 
 impl Write for File {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.file
+        let timer = Timer::start_io(Operation::Write, &self.path);
+        let result = self
+            .file
             .write(buf)
-            .map_err(|source| self.error(source, ErrorKind::Write))
+            .map_err(|source| Error::build_write(source, ErrorKind::Write, &self.path, buf.len()));
+        timer.finish(result)
     }
 
     fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
-        self.file
-            .write_vectored(bufs)
-            .map_err(|source| self.error(source, ErrorKind::Write))
+        let timer = Timer::start_io(Operation::Write, &self.path);
+        let result = self.file.write_vectored(bufs).map_err(|source| {
+            let len = bufs.iter().map(|buf| buf.len()).sum();
+            Error::build_write(source, ErrorKind::Write, &self.path, len)
+        });
+        timer.finish(result)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        self.file
+        let timer = Timer::start_io(Operation::Flush, &self.path);
+        let result = self
+            .file
             .flush()
-            .map_err(|source| self.error(source, ErrorKind::Flush))
+            .map_err(|source| self.error(source, ErrorKind::Flush));
+        timer.finish(result)
     }
 }
 
-impl<'a> Write for &'a File {
+impl Write for &File {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        (&self.file)
+        let timer = Timer::start_io(Operation::Write, &self.path);
+        let result = (&self.file)
             .write(buf)
-            .map_err(|source| self.error(source, ErrorKind::Write))
+            .map_err(|source| Error::build_write(source, ErrorKind::Write, &self.path, buf.len()));
+        timer.finish(result)
     }
 
     fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
-        (&self.file)
-            .write_vectored(bufs)
-            .map_err(|source| self.error(source, ErrorKind::Write))
+        let timer = Timer::start_io(Operation::Write, &self.path);
+        let result = (&self.file).write_vectored(bufs).map_err(|source| {
+            let len = bufs.iter().map(|buf| buf.len()).sum();
+            Error::build_write(source, ErrorKind::Write, &self.path, len)
+        });
+        timer.finish(result)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        (&self.file)
+        let timer = Timer::start_io(Operation::Flush, &self.path);
+        let result = (&self.file)
             .flush()
-            .map_err(|source| self.error(source, ErrorKind::Flush))
+            .map_err(|source| self.error(source, ErrorKind::Flush));
+        timer.finish(result)
     }
 }
 
 #[cfg(unix)]
 mod unix {
+    use crate::errors::{ErrorKind, ResultExt};
     use crate::os::unix::fs::FileExt;
-    use crate::ErrorKind;
     use std::io;
     use std::os::unix::fs::FileExt as _;
     use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
@@ -275,21 +680,66 @@ mod unix {
     }
 
     impl FileExt for crate::File {
+        #[cfg_attr(rustc_1_46, track_caller)]
         fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            let kind = ErrorKind::ReadAt {
+                len: buf.len(),
+                offset,
+            };
             self.file()
                 .read_at(buf, offset)
-                .map_err(|err| self.error(err, ErrorKind::ReadAt))
+                .with_path(kind, self.path())
         }
+        #[cfg_attr(rustc_1_46, track_caller)]
         fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+            let kind = ErrorKind::WriteAt {
+                len: buf.len(),
+                offset,
+            };
             self.file()
                 .write_at(buf, offset)
-                .map_err(|err| self.error(err, ErrorKind::WriteAt))
+                .with_write(kind, self.path(), buf.len())
+        }
+        #[cfg_attr(rustc_1_46, track_caller)]
+        fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+            let kind = ErrorKind::ReadExactAt {
+                len: buf.len(),
+                offset,
+            };
+            self.file()
+                .read_exact_at(buf, offset)
+                .with_path(kind, self.path())
+        }
+        #[cfg_attr(rustc_1_46, track_caller)]
+        fn write_all_at(&self, buf: &[u8], offset: u64) -> io::Result<()> {
+            let kind = ErrorKind::WriteAllAt {
+                len: buf.len(),
+                offset,
+            };
+            self.file()
+                .write_all_at(buf, offset)
+                .with_write(kind, self.path(), buf.len())
         }
     }
 
     #[cfg(rustc_1_63)]
     mod io_safety {
         use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
+        use std::path::PathBuf;
+
+        impl crate::File {
+            /// Creates a [`File`](crate::File) from an owned file descriptor and
+            /// the path it refers to, which is used in error messages.
+            ///
+            /// This is useful for file descriptors received from another process
+            /// or opened with `openat`.
+            pub fn from_fd<P>(fd: OwnedFd, path: P) -> Self
+            where
+                P: Into<PathBuf>,
+            {
+                crate::File::from_parts(fd.into(), path)
+            }
+        }
 
         impl AsFd for crate::File {
             fn as_fd(&self) -> BorrowedFd<'_> {
@@ -307,8 +757,8 @@ mod unix {
 
 #[cfg(windows)]
 mod windows {
+    use crate::errors::{ErrorKind, ResultExt};
     use crate::os::windows::fs::FileExt;
-    use crate::ErrorKind;
     use std::io;
     use std::os::windows::{
         fs::FileExt as _,
@@ -316,16 +766,26 @@ mod windows {
     };
 
     impl FileExt for crate::File {
+        #[cfg_attr(rustc_1_46, track_caller)]
         fn seek_read(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            let kind = ErrorKind::SeekRead {
+                len: buf.len(),
+                offset,
+            };
             self.file()
                 .seek_read(buf, offset)
-                .map_err(|err| self.error(err, ErrorKind::SeekRead))
+                .with_path(kind, self.path())
         }
 
+        #[cfg_attr(rustc_1_46, track_caller)]
         fn seek_write(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+            let kind = ErrorKind::SeekWrite {
+                len: buf.len(),
+                offset,
+            };
             self.file()
                 .seek_write(buf, offset)
-                .map_err(|err| self.error(err, ErrorKind::SeekWrite))
+                .with_write(kind, self.path(), buf.len())
         }
     }
 
@@ -348,6 +808,21 @@ mod windows {
     #[cfg(rustc_1_63)]
     mod io_safety {
         use std::os::windows::io::{AsHandle, BorrowedHandle, OwnedHandle};
+        use std::path::PathBuf;
+
+        impl crate::File {
+            /// Creates a [`File`](crate::File) from an owned handle and the path
+            /// it refers to, which is used in error messages.
+            ///
+            /// This is useful for handles inherited from or duplicated by
+            /// another process.
+            pub fn from_handle<P>(handle: OwnedHandle, path: P) -> Self
+            where
+                P: Into<PathBuf>,
+            {
+                crate::File::from_parts(handle.into(), path)
+            }
+        }
 
         impl AsHandle for crate::File {
             fn as_handle(&self) -> BorrowedHandle<'_> {
@@ -362,3 +837,240 @@ mod windows {
         }
     }
 }
+
+/// Platform-specific implementations of [`File::allocate`], [`File::copy_to`]
+/// and [`File::sync_range`].
+pub(crate) mod sys {
+    use std::fs;
+    use std::io;
+
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+    pub(super) fn allocate(file: &fs::File, len: u64) -> io::Result<()> {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let len =
+            libc::off_t::try_from(len).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        loop {
+            // SAFETY: the file descriptor is valid for the lifetime of `file`.
+            match unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len) } {
+                0 => return Ok(()),
+                err => {
+                    let err = io::Error::from_raw_os_error(err);
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "libc"))]
+    pub(super) fn allocate(file: &fs::File, len: u64) -> io::Result<()> {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let size = file.metadata()?.len();
+        if len <= size {
+            return Ok(());
+        }
+
+        let length = libc::off_t::try_from(len - size)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let mut store = libc::fstore_t {
+            fst_flags: libc::F_ALLOCATECONTIG,
+            fst_posmode: libc::F_PEOFPOSMODE,
+            fst_offset: 0,
+            fst_length: length,
+            fst_bytesalloc: 0,
+        };
+        // SAFETY: the file descriptor is valid and `store` is a valid `fstore_t`.
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &mut store) } == -1 {
+            // Contiguous space is not available, so allocate it in any way possible.
+            store.fst_flags = libc::F_ALLOCATEALL;
+            // SAFETY: as above.
+            if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &mut store) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        file.set_len(len)
+    }
+
+    #[cfg(windows)]
+    pub(super) fn allocate(file: &fs::File, len: u64) -> io::Result<()> {
+        use std::convert::TryFrom;
+        use std::os::raw::c_void;
+        use std::os::windows::io::{AsRawHandle, RawHandle};
+
+        const FILE_ALLOCATION_INFO: i32 = 5;
+
+        #[repr(C)]
+        struct FileAllocationInfo {
+            allocation_size: i64,
+        }
+
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn SetFileInformationByHandle(
+                file: RawHandle,
+                class: i32,
+                info: *const c_void,
+                size: u32,
+            ) -> i32;
+        }
+
+        if len <= file.metadata()?.len() {
+            return Ok(());
+        }
+
+        let info = FileAllocationInfo {
+            allocation_size: i64::try_from(len)
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?,
+        };
+        // SAFETY: the handle is valid for the lifetime of `file`, and `info`
+        // is a valid `FILE_ALLOCATION_INFO` of the given size.
+        let ok = unsafe {
+            SetFileInformationByHandle(
+                file.as_raw_handle(),
+                FILE_ALLOCATION_INFO,
+                &info as *const FileAllocationInfo as *const c_void,
+                std::mem::size_of::<FileAllocationInfo>() as u32,
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        file.set_len(len)
+    }
+
+    #[cfg(not(any(
+        all(
+            any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "macos",
+                target_os = "ios"
+            ),
+            feature = "libc"
+        ),
+        windows
+    )))]
+    pub(super) fn allocate(file: &fs::File, len: u64) -> io::Result<()> {
+        // Preallocation is not supported, so just make sure the file is large enough.
+        if len > file.metadata()?.len() {
+            file.set_len(len)?;
+        }
+        Ok(())
+    }
+
+    /// Copies data between files in the kernel, returning the number of bytes
+    /// copied. Zero is returned if this is not supported, so that the caller
+    /// can fall back to copying through a buffer.
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    pub(super) fn copy_range(src: &fs::File, dst: &fs::File, len: u64) -> io::Result<u64> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut copied = 0;
+        let mut use_sendfile = false;
+        while copied < len {
+            // Limit each call so that the count fits in `isize` on 32-bit platforms.
+            let count = (len - copied).min(1 << 30) as usize;
+            // SAFETY: both file descriptors are valid, and null offsets use the
+            // current file positions.
+            let ret = unsafe {
+                if use_sendfile {
+                    libc::sendfile(
+                        dst.as_raw_fd(),
+                        src.as_raw_fd(),
+                        std::ptr::null_mut(),
+                        count,
+                    )
+                } else {
+                    libc::copy_file_range(
+                        src.as_raw_fd(),
+                        std::ptr::null_mut(),
+                        dst.as_raw_fd(),
+                        std::ptr::null_mut(),
+                        count,
+                        0,
+                    )
+                }
+            };
+            match ret {
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    match err.raw_os_error() {
+                        Some(libc::EINTR) => {}
+                        // `copy_file_range` is not supported by the kernel or filesystems.
+                        Some(libc::EPERM)
+                        | Some(libc::EXDEV)
+                        | Some(libc::EINVAL)
+                        | Some(libc::ENOSYS)
+                        | Some(libc::EOPNOTSUPP)
+                            if copied == 0 && !use_sendfile =>
+                        {
+                            use_sendfile = true;
+                        }
+                        Some(libc::EINVAL) | Some(libc::ENOSYS) if copied == 0 => return Ok(0),
+                        _ => return Err(err),
+                    }
+                }
+                0 => break,
+                n => copied += n as u64,
+            }
+        }
+        Ok(copied)
+    }
+
+    /// Kernel copies are not supported on this platform, so the caller copies
+    /// through a buffer instead.
+    #[cfg(not(all(target_os = "linux", feature = "libc")))]
+    pub(super) fn copy_range(_src: &fs::File, _dst: &fs::File, _len: u64) -> io::Result<u64> {
+        Ok(0)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    pub(crate) fn sync_file_range(
+        file: &fs::File,
+        offset: u64,
+        nbytes: u64,
+        flags: u32,
+    ) -> io::Result<()> {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let (offset, nbytes) = match (
+            libc::off64_t::try_from(offset),
+            libc::off64_t::try_from(nbytes),
+        ) {
+            (Ok(offset), Ok(nbytes)) => (offset, nbytes),
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        };
+        loop {
+            // SAFETY: the file descriptor is valid for the duration of the call.
+            if unsafe { libc::sync_file_range(file.as_raw_fd(), offset, nbytes, flags) } == 0 {
+                return Ok(());
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    pub(super) fn sync_range(file: &fs::File, offset: u64, len: u64) -> io::Result<()> {
+        let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+            | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        match sync_file_range(file, offset, len, flags) {
+            Err(ref err) if err.raw_os_error() == Some(libc::ENOSYS) => file.sync_data(),
+            result => result,
+        }
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "libc")))]
+    pub(super) fn sync_range(file: &fs::File, _offset: u64, _len: u64) -> io::Result<()> {
+        file.sync_data()
+    }
+}
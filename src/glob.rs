@@ -0,0 +1,295 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Returns an iterator over the paths which match a glob pattern, in
+/// alphabetical order within each directory.
+///
+/// Within a path component, `*` matches any sequence of characters, `?`
+/// matches any single character, and `[...]` matches any of the characters
+/// or ranges such as `a-z` in the brackets, or any other character if the
+/// first one is `!`. A component of just `**` matches any number of nested
+/// directories, including none. As in a shell, wildcards do not match a `.` at
+/// the start of a file name, so hidden files are only matched by patterns such
+/// as `.*`, and `**` does not descend into hidden directories or follow
+/// symbolic links.
+///
+/// An invalid pattern is reported immediately, as an error of kind
+/// [`io::ErrorKind::InvalidInput`] whose message includes the pattern. Since
+/// the pattern is not a path, this error has no
+/// [`ErrorContext`](crate::ErrorContext). Errors reading
+/// directories while matching are returned by the iterator and include the
+/// path of the directory, then matching continues with the other directories.
+/// Directories which do not exist simply have no matches.
+///
+/// # Example
+///
+/// ```no_run
+/// for path in fs_err::glob("src/**/*.rs")? {
+///     println!("{}", path?.display());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub fn glob(pattern: &str) -> io::Result<Glob> {
+    // The pattern is not a path, so it is only included in the message.
+    let invalid = |message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid glob pattern `{}`: {}", pattern, message),
+        )
+    };
+
+    // The leading components without wildcards are the directory to start
+    // matching from.
+    let mut base = PathBuf::new();
+    let mut segments = Vec::new();
+    for component in Path::new(pattern).components() {
+        let segment = match component {
+            Component::Normal(name) => Segment::parse(&name.to_string_lossy()).map_err(invalid)?,
+            other => Segment::Literal(other.as_os_str().to_owned()),
+        };
+        match segment {
+            Segment::Literal(name) if segments.is_empty() => base.push(name),
+            segment => segments.push(segment),
+        }
+    }
+    if base.as_os_str().is_empty() && segments.is_empty() {
+        return Err(invalid("pattern is empty"));
+    }
+
+    Ok(Glob {
+        pattern: pattern.to_owned(),
+        segments,
+        stack: vec![Pending::Visit(base, 0)],
+    })
+}
+
+/// An iterator over the paths matching a glob pattern, created by [`glob`].
+#[derive(Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub struct Glob {
+    pattern: String,
+    segments: Vec<Segment>,
+    stack: Vec<Pending>,
+}
+
+#[derive(Debug)]
+enum Pending {
+    /// A path which matches the segments before the given index.
+    Visit(PathBuf, usize),
+    Error(io::Error),
+}
+
+#[derive(Debug)]
+enum Segment {
+    Literal(OsString),
+    Pattern(Vec<Token>),
+    /// `**`, which matches any number of directories.
+    Recursive,
+}
+
+#[derive(Debug)]
+enum Token {
+    Char(char),
+    Any,
+    Star,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Glob {
+    /// Returns the pattern being matched.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// Returns the entries of `dir` in reverse alphabetical order, so that they are
+/// visited in order when pushed onto `stack`. Errors are pushed onto `stack`
+/// instead, and `None` is returned if the directory cannot be read at all.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn read_dir(stack: &mut Vec<Pending>, dir: &Path) -> Option<Vec<(PathBuf, fs::FileType)>> {
+    let dir_or_current = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let read_dir = match crate::read_dir(dir_or_current) {
+        Ok(read_dir) => read_dir,
+        // Paths which do not exist or are not directories have no matches.
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound || !dir_or_current.is_dir() => {
+            return Some(Vec::new())
+        }
+        Err(err) => {
+            stack.push(Pending::Error(err));
+            return None;
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for entry in read_dir {
+        match entry.and_then(|entry| Ok((entry.file_name(), entry.file_type()?))) {
+            Ok((name, file_type)) => entries.push((dir.join(name), file_type)),
+            Err(err) => errors.push(Pending::Error(err)),
+        }
+    }
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+    stack.extend(errors);
+    Some(entries)
+}
+
+impl Iterator for Glob {
+    type Item = io::Result<PathBuf>;
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pending) = self.stack.pop() {
+            let (path, index) = match pending {
+                Pending::Visit(path, index) => (path, index),
+                Pending::Error(err) => return Some(Err(err)),
+            };
+            let is_last = index + 1 >= self.segments.len();
+            match self.segments.get(index) {
+                None => {
+                    if fs::symlink_metadata(&path).is_ok() {
+                        return Some(Ok(path));
+                    }
+                }
+                Some(Segment::Literal(name)) => {
+                    let path = path.join(name);
+                    self.stack.push(Pending::Visit(path, index + 1));
+                }
+                Some(Segment::Pattern(tokens)) => {
+                    for (path, _) in read_dir(&mut self.stack, &path).unwrap_or_default() {
+                        if matches_name(tokens, &path) && (is_last || path.is_dir()) {
+                            self.stack.push(Pending::Visit(path, index + 1));
+                        }
+                    }
+                }
+                Some(Segment::Recursive) => {
+                    let entries = match read_dir(&mut self.stack, &path) {
+                        Some(entries) => entries,
+                        // Avoid reading the directory again, which would
+                        // return the same error.
+                        None if !is_last => continue,
+                        None => Vec::new(),
+                    };
+                    for (path, file_type) in entries {
+                        if file_type.is_dir() && !is_hidden(&path) {
+                            self.stack.push(Pending::Visit(path, index));
+                        }
+                    }
+                    // Matching no directories comes before the subdirectories.
+                    self.stack.push(Pending::Visit(path, index + 1));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Segment {
+    fn parse(component: &str) -> Result<Segment, &'static str> {
+        if component == "**" {
+            return Ok(Segment::Recursive);
+        }
+        if !component.chars().any(|c| "*?[".contains(c)) {
+            return Ok(Segment::Literal(component.into()));
+        }
+
+        let mut tokens = Vec::new();
+        let mut chars = component.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    return Err("`**` must be a whole path component");
+                }
+                '*' => Token::Star,
+                '?' => Token::Any,
+                '[' => {
+                    let negated = chars.peek() == Some(&'!');
+                    if negated {
+                        chars.next();
+                    }
+                    let mut ranges = Vec::new();
+                    loop {
+                        let start = match chars.next() {
+                            // A `]` at the start is part of the class.
+                            Some(']') if !ranges.is_empty() => break,
+                            Some(c) => c,
+                            None => return Err("unclosed `[`"),
+                        };
+                        let mut lookahead = chars.clone();
+                        let end = match (lookahead.next(), lookahead.next()) {
+                            (Some('-'), Some(end)) if end != ']' => {
+                                chars.next();
+                                chars.next();
+                                end
+                            }
+                            _ => start,
+                        };
+                        if start > end {
+                            return Err("character range is out of order");
+                        }
+                        ranges.push((start, end));
+                    }
+                    Token::Class { negated, ranges }
+                }
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+        }
+        Ok(Segment::Pattern(tokens))
+    }
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Char(expected) => *expected == c,
+            Token::Any | Token::Star => true,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+            }
+        }
+    }
+}
+
+fn matches_name(tokens: &[Token], path: &Path) -> bool {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return false,
+    };
+    if name.starts_with('.') {
+        match tokens.first() {
+            Some(Token::Char('.')) => {}
+            _ => return false,
+        }
+    }
+    let name: Vec<char> = name.chars().collect();
+    matches(tokens, &name)
+}
+
+fn matches(tokens: &[Token], name: &[char]) -> bool {
+    match tokens.split_first() {
+        None => name.is_empty(),
+        Some((Token::Star, rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+        Some((token, rest)) => match name.split_first() {
+            Some((&c, name)) => token.matches(c) && matches(rest, name),
+            None => false,
+        },
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().starts_with('.'),
+        None => false,
+    }
+}
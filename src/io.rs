@@ -0,0 +1,39 @@
+//! I/O helpers for fs-err [`File`](crate::File)s.
+
+use std::io::{self, Read, Write};
+
+use crate::errors::{ResultExt, SourceDestErrorKind};
+use crate::File;
+
+/// Copies the entire contents of `reader` into `writer`, returning the number
+/// of bytes copied.
+///
+/// Unlike [`std::io::copy`](https://doc.rust-lang.org/stable/std/io/fn.copy.html),
+/// errors say whether reading from `reader` or writing to `writer` failed, and
+/// include both paths. Use [`paths_of`](crate::paths_of) to get the paths, or
+/// [`operation_of`](crate::operation_of) to find out which side failed.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn copy(reader: &mut File, writer: &mut File) -> io::Result<u64> {
+    let mut buf = [0; 8 * 1024];
+    let mut written = 0;
+    loop {
+        let len = match reader.file_mut().read(&mut buf) {
+            Ok(0) => return Ok(written),
+            Ok(len) => len,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(err).with_paths(
+                    SourceDestErrorKind::CopyRead,
+                    reader.path(),
+                    writer.path(),
+                )
+            }
+        };
+        writer.file_mut().write_all(&buf[..len]).with_paths(
+            SourceDestErrorKind::CopyWrite,
+            reader.path(),
+            writer.path(),
+        )?;
+        written += len as u64;
+    }
+}
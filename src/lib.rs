@@ -25,6 +25,11 @@ The system cannot find the file specified. (os error 2)
 failed to open file `does not exist.txt`: The system cannot find the file specified. (os error 2)
 ```
 
+Formatting an error with the alternate flag (`{:#}`) always includes the
+original error, even when the `expose_original_error` feature is enabled. The
+alternate flag is passed on to the original error, so this also applies to any
+fs-err errors it contains.
+
 # Usage
 
 fs-err's API is the same as [`std::fs`][std::fs], so migrating code to use it is easy.
@@ -63,6 +68,19 @@ println!("Program config: {:?}", decoded);
 
 * `expose_original_error`: when enabled, the [`Error::source()`](https://doc.rust-lang.org/stable/std/error/trait.Error.html#method.source) method of errors returned by this crate return the original `io::Error`. To avoid duplication in error messages,
   this also suppresses printing its message in their `Display` implementation, so make sure that you are printing the full error chain.
+* `caller_location`: when enabled, error messages include the location in your code where the failing fs-err function was called, for example ``failed to open file `foo.txt` at src/main.rs:4:13``.
+  The location is also available through [`ErrorContext::location()`](https://docs.rs/fs-err/latest/fs_err/struct.ErrorContext.html#method.location) regardless of this feature. It is only recorded for synchronous functions and methods on Rust 1.46 and later.
+* `serde`: implements `Serialize` for [`ErrorContext`](https://docs.rs/fs-err/latest/fs_err/struct.ErrorContext.html) and [`Operation`](https://docs.rs/fs-err/latest/fs_err/enum.Operation.html), allowing errors to be reported in structured formats such as JSON.
+* `diagnostics`: when enabled, errors include extra information explaining why an operation may have failed, available through [`ErrorContext::diagnostic()`](https://docs.rs/fs-err/latest/fs_err/struct.ErrorContext.html#method.diagnostic). For example, when permission is denied, the mode and owner of the file and the user the process is running as are included on Unix, and the file's attributes on Windows. When a write fails because the disk is full, the available space and the number of bytes being written are included. When a file is not found or permission is denied, relative paths and symbolic links are followed by the absolute path they resolve to, and missing parent directories are pointed out when a file is not found. On Windows, sharing violations list the processes which have the file open. Errors from the `tokio` module do not include diagnostics, since gathering them would block the executor.
+* `color`: adds [`ErrorContext::to_colored_string()`](https://docs.rs/fs-err/latest/fs_err/struct.ErrorContext.html#method.to_colored_string), which highlights the operation and paths of an error using ANSI escape codes, for command-line tools which print errors to a terminal.
+* `hash`: adds [`read_with_checksum()`](https://docs.rs/fs-err/latest/fs_err/fn.read_with_checksum.html), [`verify_checksum()`](https://docs.rs/fs-err/latest/fs_err/fn.verify_checksum.html) and [`write_with_checksum()`](https://docs.rs/fs-err/latest/fs_err/fn.write_with_checksum.html), which compute checksums of file contents using a hash function provided through the [`Digest`](https://docs.rs/fs-err/latest/fs_err/trait.Digest.html) trait.
+* `trash`: adds [`remove_file_to_trash()`](https://docs.rs/fs-err/latest/fs_err/fn.remove_file_to_trash.html) and [`remove_dir_to_trash()`](https://docs.rs/fs-err/latest/fs_err/fn.remove_dir_to_trash.html), which move files and directories to the trash or recycle bin instead of permanently removing them.
+* `libc`: uses system calls from the [`libc`](https://crates.io/crates/libc) crate to add [`Dir`](https://docs.rs/fs-err/latest/fs_err/struct.Dir.html) on Unix, an open directory handle whose methods open, create and remove files relative to the directory, and [`os::unix::fs::openat2()`](https://docs.rs/fs-err/latest/fs_err/os/unix/fs/fn.openat2.html), [`os::unix::fs::sync_file_range()`](https://docs.rs/fs-err/latest/fs_err/os/unix/fs/fn.sync_file_range.html), [`os::unix::fs::fadvise()`](https://docs.rs/fs-err/latest/fs_err/os/unix/fs/fn.fadvise.html), [`os::unix::fs::open_direct()`](https://docs.rs/fs-err/latest/fs_err/os/unix/fs/fn.open_direct.html) and [`os::unix::fs::copy_preserving_xattrs()`](https://docs.rs/fs-err/latest/fs_err/os/unix/fs/fn.copy_preserving_xattrs.html) on Linux. It also makes [`File::copy_to()`](https://docs.rs/fs-err/latest/fs_err/struct.File.html#method.copy_to) copy data in the kernel and [`File::sync_range()`](https://docs.rs/fs-err/latest/fs_err/struct.File.html#method.sync_range) sync only the given range on Linux, and [`File::allocate()`](https://docs.rs/fs-err/latest/fs_err/struct.File.html#method.allocate) allocate disk space rather than only extending the file and [`copy_reflink()`](https://docs.rs/fs-err/latest/fs_err/fn.copy_reflink.html) clone files on Linux and macOS. [`remove_file_secure()`](https://docs.rs/fs-err/latest/fs_err/fn.remove_file_secure.html) also warns when a file on Linux is on a copy-on-write filesystem.
+* `testing`: adds [`MemoryFs`](https://docs.rs/fs-err/latest/fs_err/struct.MemoryFs.html), an in-memory implementation of the [`FsBackend`](https://docs.rs/fs-err/latest/fs_err/trait.FsBackend.html) trait for unit tests, which formats errors like the real filesystem and can inject errors such as a full disk or permission being denied.
+* `fault_injection`: adds [`inject_fault()`](https://docs.rs/fs-err/latest/fs_err/fn.inject_fault.html), which makes fs-err operations fail with synthetic errors according to rules matching the operation, a path pattern, the nth call or a probability, for testing how programs handle filesystem errors. This feature requires Rust 1.63 or later.
+* `glob`: adds [`glob()`](https://docs.rs/fs-err/latest/fs_err/fn.glob.html), which returns an iterator over the paths matching a glob pattern such as `src/**/*.rs`. Errors for directories which cannot be read include the path of the directory, like other errors from fs-err, and errors for invalid patterns include the pattern in their message.
+* `tokio-rt`: adds [`lock()`](https://docs.rs/fs-err/latest/fs_err/tokio/struct.File.html#method.lock) and the other locking methods to [`tokio::File`](https://docs.rs/fs-err/latest/fs_err/tokio/struct.File.html), which wait for locks on tokio's blocking thread pool. This enables tokio's `rt` feature, and locking requires Rust 1.89 or later.
+* `tokio-time`: adds [`tokio::with_timeout()`](https://docs.rs/fs-err/latest/fs_err/tokio/fn.with_timeout.html), which runs async filesystem operations with a time limit and reports a timeout as a `TimedOut` error including the operation and path. This enables tokio's `time` feature.
 
 
 # Minimum Supported Rust Version
@@ -71,7 +89,7 @@ The oldest rust version this crate is tested on is **1.40**.
 
 This crate will generally be conservative with rust version updates. It uses the [`autocfg`](https://crates.io/crates/autocfg) crate to allow wrapping new APIs without incrementing the MSRV.
 
-If the `tokio` feature is enabled, this crate will inherit the MSRV of the selected [`tokio`](https://crates.io/crates/tokio) version.
+If the `tokio`, `serde`, `diagnostics`, `trash` or `libc` features are enabled, this crate will inherit the MSRV of the selected [`tokio`](https://crates.io/crates/tokio), [`serde`](https://crates.io/crates/serde) or [`libc`](https://crates.io/crates/libc) version.
 
 [std::fs]: https://doc.rust-lang.org/stable/std/fs/
 [std::io::Error]: https://doc.rust-lang.org/stable/std/io/struct.Error.html
@@ -83,204 +101,517 @@ If the `tokio` feature is enabled, this crate will inherit the MSRV of the selec
 #![deny(missing_debug_implementations, missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod atomic;
+mod backend;
+mod batch;
+mod buf_reader;
+mod buf_writer;
+#[cfg(feature = "hash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hash")))]
+mod checksum;
+mod copy;
+mod diagnostics;
 mod dir;
+mod dir_builder;
+#[cfg(all(unix, feature = "libc"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "libc"))))]
+mod dir_handle;
+mod dir_size;
 mod errors;
+#[cfg(all(feature = "fault_injection", rustc_1_63))]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+mod fault;
 mod file;
+#[cfg(feature = "glob")]
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+mod glob;
+pub mod io;
+mod limited;
+mod line_writer;
+mod lines;
+#[cfg(rustc_1_89)]
+mod lock;
+mod lock_file;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+mod memory_fs;
+mod metrics;
 mod open_options;
 pub mod os;
 mod path;
+mod permissions;
+mod random;
+mod reflink;
+mod remove;
+mod scoped_root;
+mod shred;
+mod sync_dir;
+mod temp;
 #[cfg(feature = "tokio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 pub mod tokio;
+#[cfg(feature = "trash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "trash")))]
+mod trash;
+mod verify;
+mod walk_dir;
 
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
-use errors::{Error, ErrorKind, SourceDestError, SourceDestErrorKind};
-
+use errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+use metrics::Timer;
+
+pub use atomic::{fsync_parent_dir, write_atomic};
+pub use backend::{FsBackend, RealFs};
+pub use batch::{Batch, MultiError, TransactionError};
+pub use buf_reader::BufReader;
+pub use buf_writer::BufWriter;
+#[cfg(feature = "hash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hash")))]
+pub use checksum::{read_with_checksum, verify_checksum, write_with_checksum, Digest};
+#[cfg(rustc_1_75)]
+pub use copy::copy_preserving;
+pub use copy::{copy_dir_all, hard_link_or_copy, move_dir, move_file, CopyDirOptions, Overwrite};
 pub use dir::*;
+pub use dir_builder::DirBuilder;
+#[cfg(all(unix, feature = "libc"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "libc"))))]
+pub use dir_handle::Dir;
+pub use dir_size::{dir_size, DirSizeOptions};
+pub use errors::{
+    context_of, operation_of, original_error_of, paths_of, raw_os_error_of, DropErrorHandler,
+    ErrorContext, MessageFormatter, MessageTemplate, Operation, PathSanitizer,
+};
+#[cfg(rustc_1_63)]
+pub use errors::{
+    set_drop_error_handler, set_message_formatter, set_message_template, set_path_sanitizer,
+};
+#[cfg(all(feature = "fault_injection", rustc_1_63))]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub use fault::{clear_faults, inject_fault, FaultRule};
 pub use file::*;
+#[cfg(feature = "glob")]
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub use glob::{glob, Glob};
+pub use limited::{read_limited, read_to_string_limited};
+pub use line_writer::LineWriter;
+pub use lines::{read_lines, Lines};
+#[cfg(rustc_1_89)]
+pub use lock::LockRetryPolicy;
+pub use lock_file::LockFile;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub use memory_fs::MemoryFs;
+#[cfg(rustc_1_63)]
+pub use metrics::{set_metrics_recorder, set_slow_operation_handler};
+pub use metrics::{MetricsRecorder, OperationMetrics, SlowOperation, SlowOperationHandler};
 pub use open_options::OpenOptions;
 pub use path::PathExt;
+pub use permissions::{set_permissions_recursive, SetPermissionsOptions};
+pub use reflink::copy_reflink;
+pub use remove::{remove_dir_all_robust, remove_on_drop, RemoveGuard};
+pub use scoped_root::ScopedRoot;
+pub use shred::{remove_file_secure, SecureRemoval, SecureRemoveOptions};
+#[cfg(rustc_1_75)]
+pub use std::fs::FileTimes;
+pub use sync_dir::{sync_dir, SyncDirOptions, SyncReport};
+pub use temp::{tempdir, NamedTempFile, PersistError, TempDir};
+#[cfg(feature = "trash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "trash")))]
+pub use trash::{remove_dir_to_trash, remove_file_to_trash};
+pub use verify::write_verified;
+pub use walk_dir::{walk_dir, WalkDir, WalkDirEntry, WalkDirOptions};
 
 /// Read the entire contents of a file into a bytes vector.
 ///
 /// Wrapper for [`fs::read`](https://doc.rust-lang.org/stable/std/fs/fn.read.html).
-pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<u8>> {
     let path = path.as_ref();
-    let mut file = file::open(path).map_err(|err_gen| err_gen(path.to_path_buf()))?;
+    let timer = Timer::start(Operation::Read, path);
+    let mut file = match fs::File::open(path).with_path(ErrorKind::OpenFile, path) {
+        Ok(file) => file,
+        Err(err) => return timer.finish(Err(err)),
+    };
     let mut bytes = Vec::with_capacity(initial_buffer_size(&file));
-    file.read_to_end(&mut bytes)
-        .map_err(|err| Error::build(err, ErrorKind::Read, path))?;
-    Ok(bytes)
+    let result = file
+        .read_to_end(&mut bytes)
+        .with_path(ErrorKind::Read, path);
+    timer.finish(result.map(|_| bytes))
 }
 
 /// Read the entire contents of a file into a string.
 ///
 /// Wrapper for [`fs::read_to_string`](https://doc.rust-lang.org/stable/std/fs/fn.read_to_string.html).
-pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
     let path = path.as_ref();
-    let mut file = file::open(path).map_err(|err_gen| err_gen(path.to_path_buf()))?;
+    let timer = Timer::start(Operation::Read, path);
+    let mut file = match fs::File::open(path).with_path(ErrorKind::OpenFile, path) {
+        Ok(file) => file,
+        Err(err) => return timer.finish(Err(err)),
+    };
     let mut string = String::with_capacity(initial_buffer_size(&file));
-    file.read_to_string(&mut string)
-        .map_err(|err| Error::build(err, ErrorKind::Read, path))?;
-    Ok(string)
+    let result = file
+        .read_to_string(&mut string)
+        .with_path(ErrorKind::Read, path);
+    timer.finish(result.map(|_| string))
 }
 
 /// Write a slice as the entire contents of a file.
 ///
 /// Wrapper for [`fs::write`](https://doc.rust-lang.org/stable/std/fs/fn.write.html).
-pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let timer = Timer::start(Operation::Write, path);
+    let mut file = match fs::File::create(path).with_path(ErrorKind::CreateFile, path) {
+        Ok(file) => file,
+        Err(err) => return timer.finish(Err(err)),
+    };
+    let result = file.write_all(contents.as_ref()).with_write(
+        ErrorKind::Write,
+        path,
+        contents.as_ref().len(),
+    );
+    timer.finish(result)
+}
+
+/// Append a slice to the end of a file, creating it if it does not exist.
+///
+/// Errors distinguish between failing to open the file and failing to write
+/// to it.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn append<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let timer = Timer::start(Operation::Write, path);
+    let mut file = match fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_path(ErrorKind::OpenAppend, path)
+    {
+        Ok(file) => file,
+        Err(err) => return timer.finish(Err(err)),
+    };
+    let result = file.write_all(contents.as_ref()).with_write(
+        ErrorKind::Append,
+        path,
+        contents.as_ref().len(),
+    );
+    timer.finish(result)
+}
+
+/// Write a slice as the entire contents of a file, first renaming any existing
+/// file to a backup.
+///
+/// The backup's path is `path` with `backup_suffix` appended, such as
+/// `config.toml~` for a suffix of `~`. Any existing backup is replaced. The
+/// path of the backup is returned, or `None` if there was no file to back up.
+///
+/// Errors distinguish between failing to rename the existing file, which
+/// include the paths of the file and the backup, and failing to write the new
+/// contents. The backup is kept if the new contents cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// if let Some(backup) = fs_err::write_with_backup("config.toml", "version = 2\n", ".bak")? {
+///     println!("previous config saved to {}", backup.display());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write_with_backup<P, C, S>(
+    path: P,
+    contents: C,
+    backup_suffix: S,
+) -> std::io::Result<Option<PathBuf>>
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    let path = path.as_ref();
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(backup_suffix);
+    let backup = PathBuf::from(backup);
+
+    let timer = Timer::start_paths(Operation::Rename, path, &backup);
+    let result = match fs::rename(path, &backup) {
+        Ok(()) => Ok(true),
+        Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err).with_paths(SourceDestErrorKind::Backup, path, &backup),
+    };
+    let backup = if timer.finish(result)? {
+        Some(backup)
+    } else {
+        None
+    };
+    write(path, contents)?;
+    Ok(backup)
+}
+
+/// Create an empty file if it does not exist, or update its modification time
+/// to the current time if it does, like the `touch` command.
+///
+/// An existing file is opened without write access, so its modification time
+/// can be updated even if it is read-only. Errors distinguish between failing
+/// to open or create the file and failing to set its modification time.
+#[cfg(rustc_1_75)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn touch<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let timer = Timer::start(Operation::SetTimes, path);
+    let file = match copy::open_for_times(path) {
+        Ok(file) => file,
+        Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+                .with_path(ErrorKind::CreateFile, path)
+            {
+                Ok(file) => file,
+                Err(err) => return timer.finish(Err(err)),
+            }
+        }
+        Err(err) => return timer.finish(Err(err).with_path(ErrorKind::OpenFile, path)),
+    };
+    let result = file
+        .set_modified(std::time::SystemTime::now())
+        .with_path(ErrorKind::SetTimes, path);
+    timer.finish(result)
+}
+
+/// Write a slice as the entire contents of a file, which is given the
+/// permissions `perm`.
+///
+/// On Unix, a new file is created with the mode of `perm`, so that it is never
+/// accessible with broader permissions. The mode is then set explicitly before
+/// writing, so it also applies to an existing file and is not restricted by
+/// the process umask. See [`os::unix::fs::write_with_mode`] to pass the mode
+/// directly.
+///
+/// On other platforms, the permissions are set after the contents are written,
+/// since a read-only file could not be written to.
+///
+/// Errors include the requested permissions if they could not be applied.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write_with_permissions<P: AsRef<Path>, C: AsRef<[u8]>>(
+    path: P,
+    contents: C,
+    perm: fs::Permissions,
+) -> std::io::Result<()> {
     let path = path.as_ref();
-    file::create(path)
-        .map_err(|err_gen| err_gen(path.to_path_buf()))?
-        .write_all(contents.as_ref())
-        .map_err(|err| Error::build(err, ErrorKind::Write, path))
+    let contents = contents.as_ref();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        os::unix::fs::write_with_mode(path, contents, perm.mode())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let readonly = perm.readonly();
+        let mut file = fs::File::create(path).with_path(ErrorKind::CreateFile, path)?;
+        file.write_all(contents)
+            .with_write(ErrorKind::Write, path, contents.len())?;
+        file.set_permissions(perm)
+            .with_path(ErrorKind::SetReadonly { readonly }, path)
+    }
 }
 
 /// Copies the contents of one file to another. This function will also copy the
 /// permission bits of the original file to the destination file.
 ///
 /// Wrapper for [`fs::copy`](https://doc.rust-lang.org/stable/std/fs/fn.copy.html).
-pub fn copy<P, Q>(from: P, to: Q) -> io::Result<u64>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn copy<P, Q>(from: P, to: Q) -> std::io::Result<u64>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
     let from = from.as_ref();
     let to = to.as_ref();
-    fs::copy(from, to)
-        .map_err(|source| SourceDestError::build(source, SourceDestErrorKind::Copy, from, to))
+    let timer = Timer::start_paths(Operation::Copy, from, to);
+    timer.finish(fs::copy(from, to).with_paths(SourceDestErrorKind::Copy, from, to))
 }
 
 /// Creates a new, empty directory at the provided path.
 ///
 /// Wrapper for [`fs::create_dir`](https://doc.rust-lang.org/stable/std/fs/fn.create_dir.html).
-pub fn create_dir<P>(path: P) -> io::Result<()>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn create_dir<P>(path: P) -> std::io::Result<()>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    fs::create_dir(path).map_err(|source| Error::build(source, ErrorKind::CreateDir, path))
+    let timer = Timer::start(Operation::CreateDir, path);
+    timer.finish(fs::create_dir(path).with_path(ErrorKind::CreateDir, path))
 }
 
 /// Recursively create a directory and all of its parent components if they are missing.
 ///
 /// Wrapper for [`fs::create_dir_all`](https://doc.rust-lang.org/stable/std/fs/fn.create_dir_all.html).
-pub fn create_dir_all<P>(path: P) -> io::Result<()>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn create_dir_all<P>(path: P) -> std::io::Result<()>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    fs::create_dir_all(path).map_err(|source| Error::build(source, ErrorKind::CreateDir, path))
+    let timer = Timer::start(Operation::CreateDir, path);
+    timer.finish(fs::create_dir_all(path).with_path(ErrorKind::CreateDir, path))
 }
 
 /// Removes an empty directory.
 ///
 /// Wrapper for [`fs::remove_dir`](https://doc.rust-lang.org/stable/std/fs/fn.remove_dir.html).
-pub fn remove_dir<P>(path: P) -> io::Result<()>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_dir<P>(path: P) -> std::io::Result<()>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    fs::remove_dir(path).map_err(|source| Error::build(source, ErrorKind::RemoveDir, path))
+    let timer = Timer::start(Operation::RemoveDir, path);
+    timer.finish(fs::remove_dir(path).with_path(ErrorKind::RemoveDir, path))
 }
 
 /// Removes a directory at this path, after removing all its contents. Use carefully!
 ///
 /// Wrapper for [`fs::remove_dir_all`](https://doc.rust-lang.org/stable/std/fs/fn.remove_dir_all.html).
-pub fn remove_dir_all<P>(path: P) -> io::Result<()>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_dir_all<P>(path: P) -> std::io::Result<()>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    fs::remove_dir_all(path).map_err(|source| Error::build(source, ErrorKind::RemoveDir, path))
+    let timer = Timer::start(Operation::RemoveDir, path);
+    timer.finish(fs::remove_dir_all(path).with_path(ErrorKind::RemoveDir, path))
 }
 
 /// Removes a file from the filesystem.
 ///
 /// Wrapper for [`fs::remove_file`](https://doc.rust-lang.org/stable/std/fs/fn.remove_file.html).
-pub fn remove_file<P>(path: P) -> io::Result<()>
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_file<P>(path: P) -> std::io::Result<()>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    fs::remove_file(path).map_err(|source| Error::build(source, ErrorKind::RemoveFile, path))
+    let timer = Timer::start(Operation::RemoveFile, path);
+    timer.finish(fs::remove_file(path).with_path(ErrorKind::RemoveFile, path))
 }
 
 /// Given a path, query the file system to get information about a file, directory, etc.
 ///
 /// Wrapper for [`fs::metadata`](https://doc.rust-lang.org/stable/std/fs/fn.metadata.html).
-pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<fs::Metadata> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn metadata<P: AsRef<Path>>(path: P) -> std::io::Result<fs::Metadata> {
     let path = path.as_ref();
-    fs::metadata(path).map_err(|source| Error::build(source, ErrorKind::Metadata, path))
+    let timer = Timer::start(Operation::Metadata, path);
+    timer.finish(fs::metadata(path).with_path(ErrorKind::Metadata, path))
 }
 
 /// Returns the canonical, absolute form of a path with all intermediate components
 /// normalized and symbolic links resolved.
 ///
 /// Wrapper for [`fs::canonicalize`](https://doc.rust-lang.org/stable/std/fs/fn.canonicalize.html).
-pub fn canonicalize<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn canonicalize<P: AsRef<Path>>(path: P) -> std::io::Result<PathBuf> {
     let path = path.as_ref();
-    fs::canonicalize(path).map_err(|source| Error::build(source, ErrorKind::Canonicalize, path))
+    let timer = Timer::start(Operation::Canonicalize, path);
+    timer.finish(fs::canonicalize(path).with_path(ErrorKind::Canonicalize, path))
+}
+
+/// Makes a path absolute without accessing the filesystem.
+///
+/// Wrapper for [`path::absolute`](https://doc.rust-lang.org/stable/std/path/fn.absolute.html).
+#[cfg(rustc_1_79)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn absolute<P: AsRef<Path>>(path: P) -> std::io::Result<PathBuf> {
+    let path = path.as_ref();
+    let timer = Timer::start(Operation::Absolute, path);
+    timer.finish(std::path::absolute(path).with_path(ErrorKind::Absolute, path))
 }
 
 /// Creates a new hard link on the filesystem.
 ///
 /// Wrapper for [`fs::hard_link`](https://doc.rust-lang.org/stable/std/fs/fn.hard_link.html).
-pub fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> std::io::Result<()> {
     let src = src.as_ref();
     let dst = dst.as_ref();
-    fs::hard_link(src, dst)
-        .map_err(|source| SourceDestError::build(source, SourceDestErrorKind::HardLink, src, dst))
+    let timer = Timer::start_paths(Operation::HardLink, src, dst);
+    timer.finish(fs::hard_link(src, dst).with_paths(SourceDestErrorKind::HardLink, src, dst))
 }
 
 /// Reads a symbolic link, returning the file that the link points to.
 ///
 /// Wrapper for [`fs::read_link`](https://doc.rust-lang.org/stable/std/fs/fn.read_link.html).
-pub fn read_link<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_link<P: AsRef<Path>>(path: P) -> std::io::Result<PathBuf> {
     let path = path.as_ref();
-    fs::read_link(path).map_err(|source| Error::build(source, ErrorKind::ReadLink, path))
+    let timer = Timer::start(Operation::ReadLink, path);
+    timer.finish(fs::read_link(path).with_path(ErrorKind::ReadLink, path))
 }
 
 /// Rename a file or directory to a new name, replacing the original file if to already exists.
 ///
 /// Wrapper for [`fs::rename`](https://doc.rust-lang.org/stable/std/fs/fn.rename.html).
-pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> std::io::Result<()> {
     let from = from.as_ref();
     let to = to.as_ref();
-    fs::rename(from, to)
-        .map_err(|source| SourceDestError::build(source, SourceDestErrorKind::Rename, from, to))
+    let timer = Timer::start_paths(Operation::Rename, from, to);
+    timer.finish(fs::rename(from, to).with_paths(SourceDestErrorKind::Rename, from, to))
 }
 
 /// Wrapper for [`fs::soft_link`](https://doc.rust-lang.org/stable/std/fs/fn.soft_link.html).
 #[deprecated = "replaced with std::os::unix::fs::symlink and \
 std::os::windows::fs::{symlink_file, symlink_dir}"]
-pub fn soft_link<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn soft_link<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> std::io::Result<()> {
     let src = src.as_ref();
     let dst = dst.as_ref();
+    let timer = Timer::start_paths(Operation::SoftLink, src, dst);
     #[allow(deprecated)]
-    fs::soft_link(src, dst)
-        .map_err(|source| SourceDestError::build(source, SourceDestErrorKind::SoftLink, src, dst))
+    timer.finish(fs::soft_link(src, dst).with_paths(SourceDestErrorKind::SoftLink, src, dst))
 }
 
 /// Query the metadata about a file without following symlinks.
 ///
 /// Wrapper for [`fs::symlink_metadata`](https://doc.rust-lang.org/stable/std/fs/fn.symlink_metadata.html).
-pub fn symlink_metadata<P: AsRef<Path>>(path: P) -> io::Result<fs::Metadata> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn symlink_metadata<P: AsRef<Path>>(path: P) -> std::io::Result<fs::Metadata> {
     let path = path.as_ref();
-    fs::symlink_metadata(path)
-        .map_err(|source| Error::build(source, ErrorKind::SymlinkMetadata, path))
+    let timer = Timer::start(Operation::SymlinkMetadata, path);
+    timer.finish(fs::symlink_metadata(path).with_path(ErrorKind::SymlinkMetadata, path))
 }
 
 /// Changes the permissions found on a file or a directory.
 ///
 /// Wrapper for [`fs::set_permissions`](https://doc.rust-lang.org/stable/std/fs/fn.set_permissions.html).
-pub fn set_permissions<P: AsRef<Path>>(path: P, perm: fs::Permissions) -> io::Result<()> {
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn set_permissions<P: AsRef<Path>>(path: P, perm: fs::Permissions) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let timer = Timer::start(Operation::SetPermissions, path);
+    timer.finish(fs::set_permissions(path, perm).with_path(ErrorKind::SetPermissions, path))
+}
+
+/// Returns `Ok(true)` if the path points at an existing entity.
+///
+/// Wrapper for [`fs::exists`](https://doc.rust-lang.org/stable/std/fs/fn.exists.html).
+#[cfg(rustc_1_81)]
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn exists<P: AsRef<Path>>(path: P) -> std::io::Result<bool> {
     let path = path.as_ref();
-    fs::set_permissions(path, perm)
-        .map_err(|source| Error::build(source, ErrorKind::SetPermissions, path))
+    let timer = Timer::start(Operation::FileExists, path);
+    timer.finish(fs::exists(path).with_path(ErrorKind::FileExists, path))
 }
 
 fn initial_buffer_size(file: &std::fs::File) -> usize {
@@ -294,4 +625,5 @@ mod private {
     impl Sealed for crate::File {}
     impl Sealed for std::path::Path {}
     impl Sealed for crate::OpenOptions {}
+    impl Sealed for crate::DirBuilder {}
 }
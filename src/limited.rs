@@ -0,0 +1,97 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Read the entire contents of a file into a bytes vector, failing if it is
+/// larger than `limit` bytes.
+///
+/// The size of the file is checked before reading it, so that reading a large
+/// file fails quickly without allocating memory for it. At most `limit` bytes
+/// are read, even if the file grows or reports the wrong size, for example if
+/// it is a pipe. If the limit is exceeded, the error has kind
+/// [`io::ErrorKind::InvalidData`] and its message includes the size of the
+/// file and the limit.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_limited<P: AsRef<Path>>(path: P, limit: u64) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let (file, capacity) = open_limited(path, limit)?;
+    let mut bytes = Vec::with_capacity(capacity);
+    file.take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .with_path(ErrorKind::Read, path)?;
+    check_limit(bytes.len(), limit, path)?;
+    Ok(bytes)
+}
+
+/// Read the entire contents of a file into a string, failing if it is larger
+/// than `limit` bytes.
+///
+/// See [`read_limited`] for how the limit is applied.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_to_string_limited<P: AsRef<Path>>(path: P, limit: u64) -> io::Result<String> {
+    let path = path.as_ref();
+    // Read the bytes first, so that a file which exceeds the limit in the middle
+    // of a character is not reported as invalid UTF-8.
+    let bytes = read_limited(path, limit)?;
+    String::from_utf8(bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        .with_path(ErrorKind::Read, path)
+}
+
+/// Opens the file and checks its reported size against `limit`, returning the
+/// initial capacity of the buffer to read it into.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn open_limited(path: &Path, limit: u64) -> io::Result<(fs::File, usize)> {
+    let file = fs::File::open(path).with_path(ErrorKind::OpenFile, path)?;
+    let len = file.metadata().with_path(ErrorKind::Metadata, path)?.len();
+    if len > limit {
+        return Err(too_large(Some(len), limit)).with_path(ErrorKind::Read, path);
+    }
+    Ok((file, len as usize + 1))
+}
+
+#[cfg_attr(rustc_1_46, track_caller)]
+fn check_limit(len: usize, limit: u64, path: &Path) -> io::Result<()> {
+    if len as u64 > limit {
+        return Err(too_large(None, limit)).with_path(ErrorKind::Read, path);
+    }
+    Ok(())
+}
+
+fn too_large(len: Option<u64>, limit: u64) -> io::Error {
+    let message = match len {
+        Some(len) => format!(
+            "file is {}, which exceeds the limit of {}",
+            FormatSize(len),
+            FormatSize(limit)
+        ),
+        None => format!("file exceeds the limit of {}", FormatSize(limit)),
+    };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Formats a number of bytes using binary units, such as `2.3 GiB`.
+struct FormatSize(u64);
+
+impl std::fmt::Display for FormatSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} bytes", self.0);
+        }
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit + 1 < UNITS.len() {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if size.fract() == 0.0 {
+            write!(f, "{} {}", size, UNITS[unit])
+        } else {
+            write!(f, "{:.1} {}", size, UNITS[unit])
+        }
+    }
+}
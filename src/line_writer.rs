@@ -0,0 +1,103 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::File;
+
+/// Wrapper around [`std::io::LineWriter`][std::io::LineWriter] for a
+/// [`File`](crate::File), which keeps the path of the file available.
+///
+/// The buffer is flushed to the file whenever a newline is written, which is
+/// useful for log files. Errors from writing, including flushing each line,
+/// include the path since they come from the fs-err [`File`](crate::File).
+///
+/// [std::io::LineWriter]: https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html
+pub struct LineWriter {
+    inner: io::LineWriter<File>,
+}
+
+impl LineWriter {
+    /// Opens a file in write-only mode and wraps it in a line writer.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create<P>(path: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        File::create(path).map(LineWriter::new)
+    }
+
+    /// Creates a new line writer with a default buffer capacity.
+    ///
+    /// Wrapper for [`LineWriter::new`](https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html#method.new).
+    pub fn new(file: File) -> Self {
+        LineWriter {
+            inner: io::LineWriter::new(file),
+        }
+    }
+
+    /// Creates a new line writer with the specified buffer capacity.
+    ///
+    /// Wrapper for [`LineWriter::with_capacity`](https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html#method.with_capacity).
+    pub fn with_capacity(capacity: usize, file: File) -> Self {
+        LineWriter {
+            inner: io::LineWriter::with_capacity(capacity, file),
+        }
+    }
+
+    /// Returns the path of the underlying file.
+    pub fn path(&self) -> &Path {
+        self.inner.get_ref().path()
+    }
+
+    /// Returns a reference to the underlying file.
+    ///
+    /// Wrapper for [`LineWriter::get_ref`](https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html#method.get_ref).
+    pub fn get_ref(&self) -> &File {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    ///
+    /// Writing directly to the file may cause data to be written out of order.
+    ///
+    /// Wrapper for [`LineWriter::get_mut`](https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html#method.get_mut).
+    pub fn get_mut(&mut self) -> &mut File {
+        self.inner.get_mut()
+    }
+
+    /// Flushes the buffer and returns the underlying file.
+    ///
+    /// If flushing fails, the error contains the standard library's writer, so
+    /// that the buffered data is not lost.
+    ///
+    /// Wrapper for [`LineWriter::into_inner`](https://doc.rust-lang.org/stable/std/io/struct.LineWriter.html#method.into_inner).
+    pub fn into_inner(self) -> Result<File, io::IntoInnerError<io::LineWriter<File>>> {
+        self.inner.into_inner()
+    }
+}
+
+impl fmt::Debug for LineWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineWriter")
+            .field("path", &self.path())
+            .finish()
+    }
+}
+
+impl Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
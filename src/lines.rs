@@ -0,0 +1,78 @@
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{Error, ErrorKind, ResultExt};
+
+/// Returns an iterator over the lines of a file.
+///
+/// Like [`BufRead::lines`](https://doc.rust-lang.org/stable/std/io/trait.BufRead.html#method.lines),
+/// lines are split on `\n` or `\r\n`, which are not included in the returned
+/// strings. Errors, including a line which is not valid UTF-8, contain the path
+/// of the file and the line number.
+///
+/// # Example
+///
+/// ```no_run
+/// for line in fs_err::read_lines("config.txt")? {
+///     println!("{}", line?);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn read_lines<P: Into<PathBuf>>(path: P) -> io::Result<Lines> {
+    let path = path.into();
+    let file = fs::File::open(&path).with_path(ErrorKind::OpenFile, &path)?;
+    Ok(Lines {
+        inner: io::BufReader::new(file),
+        path,
+        line: 0,
+    })
+}
+
+/// An iterator over the lines of a file, which includes the path and line
+/// number in errors.
+///
+/// This struct is created via [`fs_err::read_lines`][fs_err::read_lines].
+///
+/// [fs_err::read_lines]: fn.read_lines.html
+#[derive(Debug)]
+pub struct Lines {
+    inner: io::BufReader<fs::File>,
+    path: PathBuf,
+    line: u64,
+}
+
+impl Lines {
+    /// Returns the path of the file being read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Iterator for Lines {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line += 1;
+        let kind = ErrorKind::ReadLine { line: self.line };
+
+        let mut buf = Vec::new();
+        match self.inner.read_until(b'\n', &mut buf) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(source) => return Some(Err(Error::build(source, kind, &self.path))),
+        }
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+
+        Some(String::from_utf8(buf).map_err(|err| {
+            let source = io::Error::new(io::ErrorKind::InvalidData, err);
+            Error::build(source, kind, &self.path)
+        }))
+    }
+}
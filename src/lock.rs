@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::random::random_u64;
+
+/// Controls how [`File::lock_with_retry`](crate::File::lock_with_retry) waits
+/// for a lock held by someone else.
+///
+/// Each attempt after the first waits for the backoff delay, which doubles
+/// after every attempt. With jitter enabled, each wait is instead a random
+/// duration between zero and the current delay, to avoid several processes
+/// retrying in lockstep.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let file = fs_err::File::create(".lock")?;
+/// let policy = fs_err::LockRetryPolicy::new()
+///     .attempts(20)
+///     .backoff(Duration::from_millis(50));
+/// file.lock_with_retry(&policy)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockRetryPolicy {
+    attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl LockRetryPolicy {
+    /// Creates a policy making 10 attempts, starting with a 10ms backoff that
+    /// is capped at 1s, with jitter enabled.
+    pub fn new() -> Self {
+        LockRetryPolicy {
+            attempts: 10,
+            backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+            jitter: true,
+        }
+    }
+
+    /// Sets the total number of attempts to acquire the lock, including the
+    /// first. A value of zero is treated as one.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Sets the delay before the second attempt.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the longest delay between two attempts.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets whether the delay between attempts is randomized.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub(crate) fn max_attempts(&self) -> u32 {
+        self.attempts.max(1)
+    }
+
+    /// Returns the delay to wait after the given (zero-based) failed attempt.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .backoff
+            .checked_mul(1 << attempt.min(31))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        if self.jitter {
+            delay.mul_f64(random_fraction())
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for LockRetryPolicy {
+    fn default() -> Self {
+        LockRetryPolicy::new()
+    }
+}
+
+/// Returns a random number in `[0, 1)`.
+fn random_fraction() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
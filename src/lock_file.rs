@@ -0,0 +1,91 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{report_drop_error, ErrorKind, ResultExt};
+
+/// A lock file containing the process ID of its holder, which is removed when
+/// dropped.
+///
+/// The lock is acquired by creating the file, which fails if it already
+/// exists, so only one process can hold it at a time. This is commonly used to
+/// prevent several instances of a daemon from running at once. If the file
+/// already exists, the error has kind [`io::ErrorKind::AlreadyExists`] and
+/// includes the process ID of the holder read from it.
+///
+/// The file is not removed if the process exits without dropping the
+/// `LockFile`, for example because it is killed, and acquiring the lock fails
+/// until it is removed by hand. Errors from removing the file when it is
+/// dropped are passed to the handler set with
+/// [`set_drop_error_handler`](crate::set_drop_error_handler), if any. Use
+/// [`release`](LockFile::release) to handle the error directly instead.
+///
+/// # Example
+///
+/// ```no_run
+/// let _lock = fs_err::LockFile::acquire("/run/mydaemon.pid")?;
+/// // Fails with "lock file `/run/mydaemon.pid` is held by process 1234"
+/// // while the lock is held.
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+#[must_use = "the lock is released immediately if the lock file is not kept"]
+pub struct LockFile {
+    path: Option<PathBuf>,
+}
+
+impl LockFile {
+    /// Creates the lock file at `path` and writes the ID of the current
+    /// process to it.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn acquire<P: Into<PathBuf>>(path: P) -> io::Result<LockFile> {
+        let path = path.into();
+        let mut file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let pid = read_pid(&path);
+                return Err(err).with_path(ErrorKind::LockFileHeld { pid }, path);
+            }
+            Err(err) => return Err(err).with_path(ErrorKind::CreateNewFile, path),
+        };
+
+        let contents = format!("{}\n", std::process::id());
+        if let Err(err) = file.write_all(contents.as_bytes()) {
+            drop(file);
+            let _ = fs::remove_file(&path);
+            return Err(err).with_write(ErrorKind::Write, path, contents.len());
+        }
+        Ok(LockFile { path: Some(path) })
+    }
+
+    /// Returns the path of the lock file.
+    pub fn path(&self) -> &Path {
+        self.path.as_ref().expect("lock file already released")
+    }
+
+    /// Releases the lock by removing the file, returning any error.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn release(mut self) -> io::Result<()> {
+        let path = self.path.take().expect("lock file already released");
+        fs::remove_file(&path).with_path(ErrorKind::RemoveFile, path)
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if let Err(err) = fs::remove_file(&path).with_path(ErrorKind::RemoveFile, path) {
+                report_drop_error(err);
+            }
+        }
+    }
+}
+
+/// Reads the process ID from an existing lock file, if it contains one.
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
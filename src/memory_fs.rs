@@ -0,0 +1,341 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::errors::{Error, ErrorKind, SourceDestError, SourceDestErrorKind};
+use crate::FsBackend;
+
+/// An in-memory [`FsBackend`] for tests, whose errors are formatted like those
+/// from the real filesystem.
+///
+/// The filesystem starts out empty apart from its roots: a path with no
+/// parent, such as `/`, and the current directory for relative paths, are
+/// directories which always exist. Paths are compared as given, so `a/../b`
+/// and `b` are different paths. Errors have the same kind as on the real
+/// filesystem where one exists on Rust 1.40, such as
+/// [`io::ErrorKind::NotFound`] and [`io::ErrorKind::AlreadyExists`], and
+/// [`io::ErrorKind::InvalidInput`] otherwise.
+///
+/// Errors for any other kind, such as a full disk or permission being denied,
+/// can be injected with [`inject_error`](MemoryFs::inject_error).
+///
+/// # Example
+///
+/// ```
+/// use fs_err::{FsBackend, MemoryFs};
+/// use std::io;
+/// use std::path::Path;
+///
+/// let fs = MemoryFs::new();
+/// fs.write(Path::new("config.toml"), b"verbose = true")?;
+/// assert_eq!(fs.read_to_string(Path::new("config.toml"))?, "verbose = true");
+///
+/// fs.inject_error("config.toml", io::ErrorKind::PermissionDenied);
+/// let err = fs.read(Path::new("config.toml")).unwrap_err();
+/// assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub struct MemoryFs {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    nodes: BTreeMap<PathBuf, Node>,
+    errors: HashMap<PathBuf, io::ErrorKind>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    File(Vec<u8>),
+    Dir,
+}
+
+impl MemoryFs {
+    /// Creates an empty in-memory filesystem.
+    pub fn new() -> MemoryFs {
+        MemoryFs::default()
+    }
+
+    /// Makes every operation on `path` fail with an error of the given kind,
+    /// until [`clear_injected_errors`](MemoryFs::clear_injected_errors) is
+    /// called.
+    ///
+    /// The error includes the operation and path like any other error, for
+    /// example ``failed to write to file `out.bin`: ...`` for
+    /// [`io::ErrorKind::WriteZero`].
+    pub fn inject_error<P: Into<PathBuf>>(&self, path: P, kind: io::ErrorKind) {
+        self.lock().errors.insert(path.into(), kind);
+    }
+
+    /// Removes all errors added with [`inject_error`](MemoryFs::inject_error).
+    pub fn clear_injected_errors(&self) {
+        self.lock().errors.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn injected(&self, path: &Path) -> io::Result<()> {
+        match self.errors.get(path) {
+            Some(&kind) => Err(io::Error::from(kind)),
+            None => Ok(()),
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        match self.nodes.get(path) {
+            Some(Node::Dir) => true,
+            Some(Node::File(_)) => false,
+            None => is_root(path),
+        }
+    }
+
+    fn file(&self, path: &Path) -> io::Result<&Vec<u8>> {
+        self.injected(path)?;
+        match self.nodes.get(path) {
+            Some(Node::File(contents)) => Ok(contents),
+            Some(Node::Dir) => Err(invalid("path is a directory")),
+            None if is_root(path) => Err(invalid("path is a directory")),
+            None => Err(not_found()),
+        }
+    }
+
+    /// Checks that `path` can be created, because its parent is a directory.
+    fn check_parent(&self, path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) if self.is_dir(parent) => Ok(()),
+            Some(parent) if self.nodes.contains_key(parent) => {
+                Err(invalid("parent path is not a directory"))
+            }
+            Some(_) => Err(not_found()),
+            None => Err(invalid("path is a directory")),
+        }
+    }
+
+    fn has_children(&self, path: &Path) -> bool {
+        self.nodes.keys().any(|key| key.parent() == Some(path))
+    }
+}
+
+impl FsBackend for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let state = self.lock();
+        state
+            .file(path)
+            .map(|contents| contents.to_vec())
+            .map_err(|err| Error::build(err, ErrorKind::OpenFile, path))
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let contents = self.read(path)?;
+        String::from_utf8(contents).map_err(|_| {
+            let source = io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            );
+            Error::build(source, ErrorKind::Read, path)
+        })
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut state = self.lock();
+        if let Err(err) = state.injected(path) {
+            return Err(Error::build_write(
+                err,
+                ErrorKind::Write,
+                path,
+                contents.len(),
+            ));
+        }
+        let result = match state.nodes.get(path) {
+            Some(Node::Dir) => Err(invalid("path is a directory")),
+            Some(Node::File(_)) => Ok(()),
+            None => state.check_parent(path),
+        };
+        result.map_err(|err| Error::build(err, ErrorKind::CreateFile, path))?;
+        state
+            .nodes
+            .insert(path.to_owned(), Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        let contents = self.read(path)?;
+        Ok(Box::new(io::Cursor::new(contents)))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let state = self.lock();
+        let result = state.injected(path).and_then(|()| {
+            if state.is_dir(path) {
+                Ok(())
+            } else if state.nodes.contains_key(path) {
+                Err(invalid("path is not a directory"))
+            } else {
+                Err(not_found())
+            }
+        });
+        result.map_err(|err| Error::build(err, ErrorKind::ReadDir, path))?;
+        Ok(state
+            .nodes
+            .keys()
+            .filter(|key| key.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        let result = state.injected(path).and_then(|()| {
+            if is_root(path) || state.nodes.contains_key(path) {
+                Err(already_exists())
+            } else {
+                state.check_parent(path)
+            }
+        });
+        result.map_err(|err| Error::build(err, ErrorKind::CreateDir, path))?;
+        state.nodes.insert(path.to_owned(), Node::Dir);
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        let mut missing = Vec::new();
+        for ancestor in path.ancestors() {
+            if let Err(err) = state.injected(ancestor) {
+                return Err(Error::build(err, ErrorKind::CreateDir, ancestor));
+            }
+            if state.is_dir(ancestor) {
+                break;
+            }
+            if state.nodes.contains_key(ancestor) {
+                let source = invalid("path is not a directory");
+                return Err(Error::build(source, ErrorKind::CreateDir, ancestor));
+            }
+            missing.push(ancestor.to_owned());
+        }
+        for dir in missing {
+            state.nodes.insert(dir, Node::Dir);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state
+            .file(path)
+            .map_err(|err| Error::build(err, ErrorKind::RemoveFile, path))?;
+        state.nodes.remove(path);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        let result = state
+            .injected(path)
+            .and_then(|()| match state.nodes.get(path) {
+                Some(Node::Dir) if state.has_children(path) => Err(invalid("directory not empty")),
+                Some(Node::Dir) => Ok(()),
+                Some(Node::File(_)) => Err(invalid("path is not a directory")),
+                None if is_root(path) => Err(invalid("cannot remove a root directory")),
+                None => Err(not_found()),
+            });
+        result.map_err(|err| Error::build(err, ErrorKind::RemoveDir, path))?;
+        state.nodes.remove(path);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        let result = state
+            .injected(from)
+            .and_then(|()| state.injected(to))
+            .and_then(|()| {
+                let node = match state.nodes.get(from) {
+                    Some(node) => node,
+                    None if is_root(from) => return Err(invalid("cannot rename a root directory")),
+                    None => return Err(not_found()),
+                };
+                match (node, state.nodes.get(to)) {
+                    (_, None) => state.check_parent(to),
+                    (Node::File(_), Some(Node::File(_))) => Ok(()),
+                    (Node::File(_), Some(Node::Dir)) => Err(invalid("destination is a directory")),
+                    (Node::Dir, Some(Node::File(_))) => {
+                        Err(invalid("destination is not a directory"))
+                    }
+                    (Node::Dir, Some(Node::Dir)) if state.has_children(to) => {
+                        Err(invalid("directory not empty"))
+                    }
+                    (Node::Dir, Some(Node::Dir)) => Ok(()),
+                }
+            })
+            .and_then(|()| {
+                if from != to && to.starts_with(from) {
+                    Err(invalid("cannot move a directory into itself"))
+                } else {
+                    Ok(())
+                }
+            });
+        result.map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Rename, from, to))?;
+
+        let moved: Vec<PathBuf> = state
+            .nodes
+            .keys()
+            .filter(|key| key.starts_with(from))
+            .cloned()
+            .collect();
+        for old in moved {
+            let node = state.nodes.remove(&old).expect("node exists");
+            let new = match old.strip_prefix(from) {
+                Ok(rest) if rest.as_os_str().is_empty() => to.to_owned(),
+                Ok(rest) => to.join(rest),
+                Err(_) => unreachable!(),
+            };
+            state.nodes.insert(new, node);
+        }
+        Ok(())
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        let mut state = self.lock();
+        let result = state
+            .file(from)
+            .map(|contents| contents.to_vec())
+            .and_then(|contents| {
+                state.injected(to)?;
+                match state.nodes.get(to) {
+                    Some(Node::Dir) => Err(invalid("destination is a directory")),
+                    Some(Node::File(_)) => Ok(contents),
+                    None => state.check_parent(to).map(|()| contents),
+                }
+            });
+        let contents = result
+            .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Copy, from, to))?;
+        let len = contents.len() as u64;
+        state.nodes.insert(to.to_owned(), Node::File(contents));
+        Ok(len)
+    }
+}
+
+fn is_root(path: &Path) -> bool {
+    path.parent().is_none() || path.as_os_str().is_empty()
+}
+
+fn not_found() -> io::Error {
+    io::Error::from(io::ErrorKind::NotFound)
+}
+
+fn already_exists() -> io::Error {
+    io::Error::from(io::ErrorKind::AlreadyExists)
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
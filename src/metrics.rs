@@ -0,0 +1,448 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::errors::sanitize_path;
+use crate::Operation;
+
+/// A completed operation, passed to the recorder set with
+/// [`set_metrics_recorder`].
+#[derive(Debug, Clone, Copy)]
+pub struct OperationMetrics<'a> {
+    operation: Operation,
+    path: &'a Path,
+    dest_path: Option<&'a Path>,
+    duration: Duration,
+    error: Option<&'a io::Error>,
+}
+
+impl<'a> OperationMetrics<'a> {
+    /// Returns the operation which was performed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the path the operation was performed on, or the source path
+    /// for operations involving two paths.
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// Returns the destination path for operations involving two paths, such as
+    /// [`copy`](crate::copy) and [`rename`](crate::rename).
+    pub fn dest_path(&self) -> Option<&'a Path> {
+        self.dest_path
+    }
+
+    /// Returns how long the operation took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the error the operation failed with, or `None` if it succeeded.
+    pub fn error(&self) -> Option<&'a io::Error> {
+        self.error
+    }
+}
+
+/// A function which records metrics for a completed operation.
+///
+/// See [`set_metrics_recorder`].
+pub type MetricsRecorder = fn(&OperationMetrics<'_>);
+
+/// Sets the function called after each filesystem operation with its kind,
+/// path, duration and result, for example to export counters and latency
+/// histograms to a monitoring system.
+///
+/// The recorder is called for the functions which wrap a single [`std::fs`]
+/// function, such as [`read`](crate::read), [`rename`](crate::rename) and
+/// [`read_dir`](crate::read_dir), for [`File::open`](crate::File::open) and
+/// [`File::create`](crate::File::create), and for each call to the `Read` and
+/// `Write` methods and the `sync_all`, `sync_data`, `set_len` and `metadata`
+/// methods of [`File`](crate::File). It is called on the thread which performed
+/// the operation, so it should be fast.
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// use fs_err::OperationMetrics;
+///
+/// fn record(metrics: &OperationMetrics) {
+///     let outcome = if metrics.error().is_some() { "error" } else { "ok" };
+///     eprintln!(
+///         "{:?} {} took {:?}",
+///         metrics.operation(),
+///         outcome,
+///         metrics.duration()
+///     );
+/// }
+///
+/// fs_err::set_metrics_recorder(record);
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_metrics_recorder(recorder: MetricsRecorder) {
+    use std::sync::atomic::Ordering;
+    use std::sync::PoisonError;
+
+    *hooks::METRICS_RECORDER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(recorder);
+    hooks::ENABLED.store(true, Ordering::Release);
+}
+
+/// A filesystem operation which took longer than the threshold set with
+/// [`set_slow_operation_handler`].
+#[derive(Debug, Clone, Copy)]
+pub struct SlowOperation<'a> {
+    operation: Operation,
+    path: &'a Path,
+    dest_path: Option<&'a Path>,
+    elapsed: Duration,
+    finished: bool,
+}
+
+impl<'a> SlowOperation<'a> {
+    /// Returns the operation which is slow.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the path the operation is performed on, or the source path for
+    /// operations involving two paths.
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// Returns the destination path for operations involving two paths, such as
+    /// [`copy`](crate::copy) and [`rename`](crate::rename).
+    pub fn dest_path(&self) -> Option<&'a Path> {
+        self.dest_path
+    }
+
+    /// Returns how long the operation has taken so far, or in total if it has
+    /// finished.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns whether the operation has finished. If not, it is still in
+    /// progress, and may be hung.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl<'a> fmt::Display for SlowOperation<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.finished {
+            "took"
+        } else {
+            "has been running for"
+        };
+        write!(
+            formatter,
+            "{:?} of `{}`",
+            self.operation,
+            sanitize_path(self.path).display()
+        )?;
+        if let Some(dest_path) = self.dest_path {
+            write!(formatter, " to `{}`", sanitize_path(dest_path).display())?;
+        }
+        write!(formatter, " {} {:?}", state, self.elapsed)
+    }
+}
+
+/// A function which is called when an operation is slow.
+///
+/// See [`set_slow_operation_handler`].
+pub type SlowOperationHandler = fn(&SlowOperation<'_>);
+
+/// Sets the function called when a filesystem operation takes longer than
+/// `threshold`, to find out which file is responsible for a stall, such as a
+/// hung network filesystem.
+///
+/// Operations are watched by a background thread, which is started by the
+/// first call to this function. The handler is called once for each slow
+/// operation: from the background thread while it is still running, so that
+/// an operation which never returns is still reported, or from the thread
+/// which performed it if it finishes before the background thread notices.
+/// The same operations are watched as are passed to
+/// [`set_metrics_recorder`], except that the `Read` and `Write` methods of
+/// [`File`](crate::File) are only reported once they finish, to keep them
+/// fast.
+///
+/// This function requires Rust 1.63 or later.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// fs_err::set_slow_operation_handler(Duration::from_secs(5), |slow| {
+///     // For example, "ReadDir of `/mnt/nfs/data` has been running for 5.01s"
+///     eprintln!("warning: {}", slow);
+/// });
+/// ```
+#[cfg(rustc_1_63)]
+pub fn set_slow_operation_handler(threshold: Duration, handler: SlowOperationHandler) {
+    use std::sync::atomic::Ordering;
+    use std::sync::{Once, PoisonError};
+
+    *hooks::SLOW_OPERATION_HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some((threshold, handler));
+    hooks::ENABLED.store(true, Ordering::Release);
+
+    static WATCHDOG: Once = Once::new();
+    WATCHDOG.call_once(|| {
+        let _ = std::thread::Builder::new()
+            .name("fs-err watchdog".to_owned())
+            .spawn(hooks::watchdog);
+    });
+}
+
+/// Measures how long an operation takes, if a hook is set.
+///
+/// The result is passed to [`Timer::finish`] rather than the operation being
+/// run in a closure, so that `#[track_caller]` still applies to it.
+#[derive(Debug)]
+pub(crate) struct Timer<'a> {
+    #[cfg_attr(not(rustc_1_63), allow(dead_code))]
+    active: Option<Active<'a>>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(not(rustc_1_63), allow(dead_code))]
+struct Active<'a> {
+    operation: Operation,
+    path: &'a Path,
+    dest_path: Option<&'a Path>,
+    start: Instant,
+    /// The key of the operation in the operations watched for being slow.
+    id: Option<u64>,
+}
+
+impl<'a> Timer<'a> {
+    /// Starts timing an operation on `path`.
+    pub(crate) fn start(operation: Operation, path: &'a Path) -> Timer<'a> {
+        Timer::start_inner(operation, path, None, true)
+    }
+
+    /// Starts timing an operation from `from` to `to`.
+    pub(crate) fn start_paths(operation: Operation, from: &'a Path, to: &'a Path) -> Timer<'a> {
+        Timer::start_inner(operation, from, Some(to), true)
+    }
+
+    /// Starts timing a read or write on an open file. These are called often,
+    /// so they are not watched while in progress, which would take a global
+    /// lock and copy the path.
+    pub(crate) fn start_io(operation: Operation, path: &'a Path) -> Timer<'a> {
+        Timer::start_inner(operation, path, None, false)
+    }
+
+    #[cfg_attr(not(rustc_1_63), allow(unused_variables))]
+    fn start_inner(
+        operation: Operation,
+        path: &'a Path,
+        dest_path: Option<&'a Path>,
+        watch: bool,
+    ) -> Timer<'a> {
+        #[cfg(rustc_1_63)]
+        {
+            if hooks::ENABLED.load(std::sync::atomic::Ordering::Relaxed) {
+                let mut active = Active {
+                    operation,
+                    path,
+                    dest_path,
+                    start: Instant::now(),
+                    id: None,
+                };
+                if watch {
+                    active.id = hooks::watch(&active);
+                }
+                return Timer {
+                    active: Some(active),
+                };
+            }
+        }
+
+        Timer { active: None }
+    }
+
+    /// Records the result of the operation, then returns it.
+    #[cfg_attr(not(rustc_1_63), allow(unused_mut))]
+    pub(crate) fn finish<T>(mut self, result: io::Result<T>) -> io::Result<T> {
+        #[cfg(rustc_1_63)]
+        {
+            if let Some(active) = self.active.take() {
+                hooks::finish(&active, result.as_ref().err());
+            }
+        }
+        result
+    }
+}
+
+/// Stops watching the operation if it panicked, so that it is not reported as
+/// still running.
+impl<'a> Drop for Timer<'a> {
+    fn drop(&mut self) {
+        #[cfg(rustc_1_63)]
+        {
+            if let Some(id) = self.active.as_ref().and_then(|active| active.id) {
+                hooks::unwatch(id);
+            }
+        }
+    }
+}
+
+#[cfg(rustc_1_63)]
+mod hooks {
+    use super::{Active, MetricsRecorder, OperationMetrics, SlowOperation, SlowOperationHandler};
+    use crate::Operation;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Mutex, PoisonError, RwLock};
+    use std::time::{Duration, Instant};
+
+    /// Whether any hook is set, checked before taking the time so that
+    /// operations are not slowed down otherwise.
+    pub(super) static ENABLED: AtomicBool = AtomicBool::new(false);
+
+    pub(super) static METRICS_RECORDER: RwLock<Option<MetricsRecorder>> = RwLock::new(None);
+
+    pub(super) static SLOW_OPERATION_HANDLER: RwLock<Option<(Duration, SlowOperationHandler)>> =
+        RwLock::new(None);
+
+    /// The operations in progress while a slow operation handler is set. This
+    /// is a `Vec` rather than a map so that it can be created in a static on
+    /// Rust 1.63.
+    static WATCHED: Mutex<Watched> = Mutex::new(Watched {
+        next_id: 0,
+        operations: Vec::new(),
+    });
+
+    struct Watched {
+        next_id: u64,
+        operations: Vec<Watch>,
+    }
+
+    struct Watch {
+        id: u64,
+        operation: Operation,
+        path: PathBuf,
+        dest_path: Option<PathBuf>,
+        start: Instant,
+        reported: bool,
+    }
+
+    fn metrics_recorder() -> Option<MetricsRecorder> {
+        *METRICS_RECORDER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn slow_operation_handler() -> Option<(Duration, SlowOperationHandler)> {
+        *SLOW_OPERATION_HANDLER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn watched() -> std::sync::MutexGuard<'static, Watched> {
+        WATCHED.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds an operation to those watched for being slow, if a handler is set.
+    pub(super) fn watch(active: &Active<'_>) -> Option<u64> {
+        slow_operation_handler()?;
+        let mut watched = watched();
+        let id = watched.next_id;
+        watched.next_id += 1;
+        watched.operations.push(Watch {
+            id,
+            operation: active.operation,
+            path: active.path.to_owned(),
+            dest_path: active.dest_path.map(Path::to_owned),
+            start: active.start,
+            reported: false,
+        });
+        Some(id)
+    }
+
+    /// Removes an operation from those watched for being slow, returning
+    /// whether it has been reported already.
+    pub(super) fn unwatch(id: u64) -> bool {
+        let mut watched = watched();
+        match watched.operations.iter().position(|watch| watch.id == id) {
+            Some(index) => watched.operations.swap_remove(index).reported,
+            None => false,
+        }
+    }
+
+    pub(super) fn finish(active: &Active<'_>, error: Option<&io::Error>) {
+        let duration = active.start.elapsed();
+        if let Some(recorder) = metrics_recorder() {
+            recorder(&OperationMetrics {
+                operation: active.operation,
+                path: active.path,
+                dest_path: active.dest_path,
+                duration,
+                error,
+            });
+        }
+
+        let reported = match active.id {
+            Some(id) => unwatch(id),
+            None => false,
+        };
+        if let Some((threshold, handler)) = slow_operation_handler() {
+            if !reported && duration >= threshold {
+                handler(&SlowOperation {
+                    operation: active.operation,
+                    path: active.path,
+                    dest_path: active.dest_path,
+                    elapsed: duration,
+                    finished: true,
+                });
+            }
+        }
+    }
+
+    /// Reports operations which have been running for longer than the
+    /// threshold, checking several times per threshold.
+    pub(super) fn watchdog() {
+        loop {
+            let (threshold, handler) = match slow_operation_handler() {
+                Some(handler) => handler,
+                None => return,
+            };
+            let interval =
+                (threshold / 10).clamp(Duration::from_millis(10), Duration::from_secs(1));
+            std::thread::sleep(interval);
+
+            // The handler is called without holding the lock, in case it uses
+            // fs-err itself.
+            let mut slow = Vec::new();
+            for watch in watched().operations.iter_mut() {
+                let elapsed = watch.start.elapsed();
+                if !watch.reported && elapsed >= threshold {
+                    watch.reported = true;
+                    slow.push((
+                        watch.operation,
+                        watch.path.clone(),
+                        watch.dest_path.clone(),
+                        elapsed,
+                    ));
+                }
+            }
+            for (operation, path, dest_path, elapsed) in slow {
+                handler(&SlowOperation {
+                    operation,
+                    path: &path,
+                    dest_path: dest_path.as_deref(),
+                    elapsed,
+                    finished: false,
+                });
+            }
+        }
+    }
+}
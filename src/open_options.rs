@@ -1,10 +1,17 @@
 use std::{fs, io, path::PathBuf};
 
-use crate::errors::{Error, ErrorKind};
+use crate::errors::{ErrorKind, ResultExt};
 
 #[derive(Clone, Debug)]
 /// Wrapper around [`std::fs::OpenOptions`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html)
-pub struct OpenOptions(fs::OpenOptions);
+pub struct OpenOptions {
+    options: fs::OpenOptions,
+    no_follow: bool,
+    /// The flags set with `custom_flags`, which are combined with the flag set
+    /// by `no_follow`.
+    #[cfg(any(unix, windows))]
+    custom_flags: u32,
+}
 
 impl OpenOptions {
     /// Creates a blank new set of options ready for configuration.
@@ -12,14 +19,14 @@ impl OpenOptions {
     /// Wrapper for [`std::fs::OpenOptions::new`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.new)
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        OpenOptions(fs::OpenOptions::new())
+        OpenOptions::from_options(fs::OpenOptions::new())
     }
 
     /// Sets the option for read access.
     ///
     /// Wrapper for [`std::fs::OpenOptions::read`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.read)
     pub fn read(&mut self, read: bool) -> &mut Self {
-        self.0.read(read);
+        self.options.read(read);
         self
     }
 
@@ -27,7 +34,7 @@ impl OpenOptions {
     ///
     /// Wrapper for [`std::fs::OpenOptions::write`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.write)
     pub fn write(&mut self, write: bool) -> &mut Self {
-        self.0.write(write);
+        self.options.write(write);
         self
     }
 
@@ -35,7 +42,7 @@ impl OpenOptions {
     ///
     /// Wrapper for [`std::fs::OpenOptions::append`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.append)
     pub fn append(&mut self, append: bool) -> &mut Self {
-        self.0.append(append);
+        self.options.append(append);
         self
     }
 
@@ -43,7 +50,7 @@ impl OpenOptions {
     ///
     /// Wrapper for [`std::fs::OpenOptions::truncate`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.truncate)
     pub fn truncate(&mut self, truncate: bool) -> &mut Self {
-        self.0.truncate(truncate);
+        self.options.truncate(truncate);
         self
     }
 
@@ -51,7 +58,7 @@ impl OpenOptions {
     ///
     /// Wrapper for [`std::fs::OpenOptions::create`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.create)
     pub fn create(&mut self, create: bool) -> &mut Self {
-        self.0.create(create);
+        self.options.create(create);
         self
     }
 
@@ -59,22 +66,38 @@ impl OpenOptions {
     ///
     /// Wrapper for [`std::fs::OpenOptions::create_new`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.create_new)
     pub fn create_new(&mut self, create_new: bool) -> &mut Self {
-        self.0.create_new(create_new);
+        self.options.create_new(create_new);
         self
     }
 
     /// Opens a file at `path` with the options specified by `self`.
     ///
     /// Wrapper for [`std::fs::OpenOptions::open`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#method.open)
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn open<P>(&self, path: P) -> io::Result<crate::File>
     where
         P: Into<PathBuf>,
     {
         let path = path.into();
-        match self.0.open(&path) {
-            Ok(file) => Ok(crate::File::from_parts(file, path)),
-            Err(source) => Err(Error::build(source, ErrorKind::OpenFile, path)),
+        if self.no_follow && !sys::HAS_NO_FOLLOW && is_symlink(&path) {
+            return Err(sys::symlink_error()).with_path(ErrorKind::OpenNoFollow, &path);
+        }
+        let file = match self.options.open(&path) {
+            Ok(file) => file,
+            Err(err) if self.no_follow && is_symlink(&path) => {
+                return Err(err).with_path(ErrorKind::OpenNoFollow, &path)
+            }
+            Err(err) => return Err(err).with_path(ErrorKind::OpenFile, &path),
+        };
+        // On Windows, the symbolic link itself is opened instead.
+        if self.no_follow && cfg!(windows) {
+            if let Ok(metadata) = file.metadata() {
+                if metadata.file_type().is_symlink() {
+                    return Err(sys::symlink_error()).with_path(ErrorKind::OpenNoFollow, &path);
+                }
+            }
         }
+        Ok(crate::File::from_parts(file, path))
     }
 }
 
@@ -83,14 +106,45 @@ impl OpenOptions {
 impl OpenOptions {
     /// Constructs `Self` from [`std::fs::OpenOptions`](https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html)
     pub fn from_options(options: fs::OpenOptions) -> Self {
-        Self(options)
+        OpenOptions {
+            options,
+            no_follow: false,
+            #[cfg(any(unix, windows))]
+            custom_flags: 0,
+        }
+    }
+
+    /// Sets the option to fail if the path refers to a symbolic link, rather
+    /// than opening the file it points to.
+    ///
+    /// Only the last component of the path is checked. This uses `O_NOFOLLOW`
+    /// on Unix and `FILE_FLAG_OPEN_REPARSE_POINT` on Windows, so that a
+    /// symbolic link created after the path is checked cannot be followed.
+    /// The error has a dedicated message saying that the file is a symbolic
+    /// link. Flags set with
+    /// [`options_mut`](OpenOptions::options_mut) are replaced when this is
+    /// enabled, so set custom flags with the `OpenOptionsExt` trait of fs-err
+    /// instead.
+    pub fn no_follow(&mut self, no_follow: bool) -> &mut Self {
+        self.no_follow = no_follow;
+        self.apply_custom_flags();
+        self
+    }
+
+    #[cfg(any(unix, windows))]
+    fn apply_custom_flags(&mut self) {
+        let no_follow = if self.no_follow { sys::NO_FOLLOW } else { 0 };
+        sys::set_custom_flags(&mut self.options, self.custom_flags | no_follow);
     }
 
+    #[cfg(not(any(unix, windows)))]
+    fn apply_custom_flags(&mut self) {}
+
     /// Returns a reference to the underlying [`std::fs::OpenOptions`](https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html).
     ///
     /// Note that calling `open()` on this reference will NOT give you the improved errors from fs-err.
     pub fn options(&self) -> &fs::OpenOptions {
-        &self.0
+        &self.options
     }
 
     /// Returns a mutable reference to the underlying [`std::fs::OpenOptions`](https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html).
@@ -98,7 +152,90 @@ impl OpenOptions {
     /// This allows you to change settings that don't yet have wrappers in fs-err.
     /// Note that calling `open()` on this reference will NOT give you the improved errors from fs-err.
     pub fn options_mut(&mut self) -> &mut fs::OpenOptions {
-        &mut self.0
+        &mut self.options
+    }
+}
+
+fn is_symlink(path: &std::path::Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// The flags used to implement [`OpenOptions::no_follow`].
+mod sys {
+    use std::fs;
+    use std::io;
+
+    /// Whether `NO_FOLLOW` is supported, so that symbolic links do not need to
+    /// be checked for before opening a file.
+    pub(super) const HAS_NO_FOLLOW: bool = NO_FOLLOW != 0;
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        )
+    ))]
+    pub(super) const NO_FOLLOW: u32 = 0o100000;
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        ))
+    ))]
+    pub(super) const NO_FOLLOW: u32 = 0o400000;
+    #[cfg(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub(super) const NO_FOLLOW: u32 = 0x100;
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    pub(super) const NO_FOLLOW: u32 = 0x20000;
+    /// `FILE_FLAG_OPEN_REPARSE_POINT`
+    #[cfg(windows)]
+    pub(super) const NO_FOLLOW: u32 = 0x0020_0000;
+    #[cfg(not(any(
+        windows,
+        target_os = "linux",
+        target_os = "android",
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    )))]
+    pub(super) const NO_FOLLOW: u32 = 0;
+
+    #[cfg(unix)]
+    pub(super) fn set_custom_flags(options: &mut fs::OpenOptions, flags: u32) {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.custom_flags(flags as i32);
+    }
+
+    #[cfg(windows)]
+    pub(super) fn set_custom_flags(options: &mut fs::OpenOptions, flags: u32) {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        options.custom_flags(flags);
+    }
+
+    /// The error returned when a symbolic link is opened without an error from
+    /// the OS.
+    pub(super) fn symlink_error() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, "the file is a symbolic link")
     }
 }
 
@@ -113,7 +250,8 @@ mod unix {
         }
 
         fn custom_flags(&mut self, flags: i32) -> &mut Self {
-            self.options_mut().custom_flags(flags);
+            self.custom_flags = flags as u32;
+            self.apply_custom_flags();
             self
         }
     }
@@ -135,7 +273,8 @@ mod windows {
             self
         }
         fn custom_flags(&mut self, flags: u32) -> &mut Self {
-            self.options_mut().custom_flags(flags);
+            self.custom_flags = flags;
+            self.apply_custom_flags();
             self
         }
 
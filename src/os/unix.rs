@@ -3,17 +3,506 @@ pub mod fs {
     use std::io;
     use std::path::Path;
 
-    use crate::SourceDestError;
-    use crate::SourceDestErrorKind;
+    #[allow(unused_imports)]
+    use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
 
     /// Creates a new symbolic link on the filesystem.
     ///
     /// Wrapper for [`std::os::unix::fs::symlink`](https://doc.rust-lang.org/std/os/unix/fs/fn.symlink.html)
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
         let src = src.as_ref();
         let dst = dst.as_ref();
-        std::os::unix::fs::symlink(src, dst)
-            .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Symlink, src, dst))
+        std::os::unix::fs::symlink(src, dst).with_paths(SourceDestErrorKind::Symlink, src, dst)
+    }
+
+    /// Write a slice as the entire contents of a file, which is given the
+    /// permission bits `mode`, for example `0o600`.
+    ///
+    /// A new file is created with `mode`, so that it is never accessible with
+    /// broader permissions. The mode is then set explicitly before writing, so
+    /// it also applies to an existing file and is not restricted by the
+    /// process umask.
+    ///
+    /// Errors include the requested mode if the file could not be created or
+    /// its mode could not be set.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn write_with_mode<P: AsRef<Path>, C: AsRef<[u8]>>(
+        path: P,
+        contents: C,
+        mode: u32,
+    ) -> io::Result<()> {
+        use std::fs;
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt as _, PermissionsExt as _};
+
+        let path = path.as_ref();
+        let contents = contents.as_ref();
+        let mode = mode & 0o7777;
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(mode)
+            .open(path)
+            .with_path(ErrorKind::CreateFileWithMode { mode }, path)?;
+        file.set_permissions(fs::Permissions::from_mode(mode))
+            .with_path(ErrorKind::SetMode { mode }, path)?;
+        file.write_all(contents)
+            .with_write(ErrorKind::Write, path, contents.len())
+    }
+
+    /// Copies the contents of one file to another, preserving its permissions,
+    /// its access and modification times and its extended attributes.
+    ///
+    /// Like [`copy_preserving`](crate::copy_preserving), but each extended
+    /// attribute of `from` is also copied to `to`, before its permissions are
+    /// applied so that a read-only file can still be given its attributes.
+    /// Attributes in the `security` and `trusted` namespaces are skipped if the
+    /// process is not permitted to set them or the destination filesystem does
+    /// not support them. If any other attribute cannot be copied, the error
+    /// identifies whether reading from `from` or writing to `to` failed.
+    #[cfg(all(
+        rustc_1_75,
+        any(target_os = "linux", target_os = "android"),
+        feature = "libc"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc")))
+    )]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn copy_preserving_xattrs<P, Q>(from: P, to: Q) -> io::Result<u64>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let from = from.as_ref();
+        let to = to.as_ref();
+        let len = super::xattr::copy_file(from, to)?;
+        crate::copy::copy_times(from, to)?;
+        Ok(len)
+    }
+
+    /// Changes the owner and group of the specified path.
+    ///
+    /// Wrapper for [`std::os::unix::fs::chown`](https://doc.rust-lang.org/std/os/unix/fs/fn.chown.html)
+    #[cfg(rustc_1_73)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn chown<P: AsRef<Path>>(dir: P, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::os::unix::fs::chown(dir, uid, gid).with_path(ErrorKind::Chown, dir)
+    }
+
+    /// Changes the owner and group of the file.
+    ///
+    /// Unlike [`std::os::unix::fs::fchown`], this only accepts an fs-err
+    /// [`File`](crate::File), whose path is included in errors.
+    ///
+    /// Wrapper for [`std::os::unix::fs::fchown`](https://doc.rust-lang.org/std/os/unix/fs/fn.fchown.html)
+    #[cfg(rustc_1_73)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn fchown(file: &crate::File, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+        std::os::unix::fs::fchown(file.file(), uid, gid).with_path(ErrorKind::Chown, file.path())
+    }
+
+    /// Changes the owner and group of the specified path, without following
+    /// symbolic links.
+    ///
+    /// Wrapper for [`std::os::unix::fs::lchown`](https://doc.rust-lang.org/std/os/unix/fs/fn.lchown.html)
+    #[cfg(rustc_1_73)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn lchown<P: AsRef<Path>>(dir: P, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::os::unix::fs::lchown(dir, uid, gid).with_path(ErrorKind::Lchown, dir)
+    }
+
+    /// Waits for writeback of pages in the range which is already in progress
+    /// before writing them out, for use with [`sync_file_range`].
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    pub const SYNC_FILE_RANGE_WAIT_BEFORE: u32 = libc::SYNC_FILE_RANGE_WAIT_BEFORE;
+    /// Starts writeback of dirty pages in the range, for use with
+    /// [`sync_file_range`].
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    pub const SYNC_FILE_RANGE_WRITE: u32 = libc::SYNC_FILE_RANGE_WRITE;
+    /// Waits for writeback of pages in the range to complete, for use with
+    /// [`sync_file_range`].
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    pub const SYNC_FILE_RANGE_WAIT_AFTER: u32 = libc::SYNC_FILE_RANGE_WAIT_AFTER;
+
+    /// Writes out the dirty pages in `nbytes` bytes of a file starting at
+    /// `offset`, as controlled by `flags`. An `nbytes` of zero covers
+    /// everything up to the end of the file.
+    ///
+    /// `flags` is a combination of [`SYNC_FILE_RANGE_WAIT_BEFORE`],
+    /// [`SYNC_FILE_RANGE_WRITE`] and [`SYNC_FILE_RANGE_WAIT_AFTER`]. No
+    /// metadata is written, so this does not make the data durable on its own;
+    /// see [`File::sync_range`](crate::File::sync_range) for a portable
+    /// alternative. Errors include the path of the file and the range.
+    ///
+    /// Wrapper for [`sync_file_range`](https://man7.org/linux/man-pages/man2/sync_file_range.2.html).
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn sync_file_range(
+        file: &crate::File,
+        offset: u64,
+        nbytes: u64,
+        flags: u32,
+    ) -> io::Result<()> {
+        crate::file::sys::sync_file_range(file.file(), offset, nbytes, flags).with_path(
+            ErrorKind::SyncRange {
+                offset,
+                len: nbytes,
+            },
+            file.path(),
+        )
+    }
+
+    /// How a file is expected to be accessed, passed to [`fadvise`].
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc")))
+    )]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Advice {
+        /// No particular access pattern is expected. This is the default.
+        Normal,
+        /// The data will be accessed sequentially, so more of it can be read ahead.
+        Sequential,
+        /// The data will be accessed in a random order, so reading ahead is not useful.
+        Random,
+        /// The data will be accessed soon, so it can be read into the page cache now.
+        WillNeed,
+        /// The data will not be accessed again soon, so it can be dropped from the page cache.
+        DontNeed,
+        /// The data will only be accessed once.
+        NoReuse,
+    }
+
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+    impl Advice {
+        fn name(self) -> &'static str {
+            match self {
+                Advice::Normal => "normal",
+                Advice::Sequential => "sequential",
+                Advice::Random => "random",
+                Advice::WillNeed => "will-need",
+                Advice::DontNeed => "dont-need",
+                Advice::NoReuse => "no-reuse",
+            }
+        }
+
+        fn value(self) -> std::os::raw::c_int {
+            match self {
+                Advice::Normal => libc::POSIX_FADV_NORMAL,
+                Advice::Random => libc::POSIX_FADV_RANDOM,
+                Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+                Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+                Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+                Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+            }
+        }
+    }
+
+    /// Tells the kernel how `len` bytes of the file starting at `offset` will
+    /// be accessed, so that it can optimize caching. A `len` of zero applies
+    /// to the rest of the file.
+    ///
+    /// For example, [`Advice::Sequential`] increases read-ahead for streaming,
+    /// and [`Advice::DontNeed`] drops data which has been processed from the
+    /// page cache. Errors include the path of the file and the advice given.
+    ///
+    /// Wrapper for [`posix_fadvise`](https://man7.org/linux/man-pages/man2/posix_fadvise.2.html).
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc")))
+    )]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn fadvise(file: &crate::File, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let kind = ErrorKind::Fadvise {
+            advice: advice.name(),
+        };
+        let (offset, len) = match (libc::off_t::try_from(offset), libc::off_t::try_from(len)) {
+            (Ok(offset), Ok(len)) => (offset, len),
+            _ => {
+                return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                    .with_path(kind, file.path())
+            }
+        };
+
+        // SAFETY: the file descriptor is valid for the duration of the call.
+        match unsafe { libc::posix_fadvise(file.as_raw_fd(), offset, len, advice.value()) } {
+            0 => Ok(()),
+            err => Err(io::Error::from_raw_os_error(err)).with_path(kind, file.path()),
+        }
+    }
+
+    /// Opens a file with the options specified by `options`, bypassing the
+    /// page cache with `O_DIRECT`.
+    ///
+    /// Reads and writes on the file must use buffers whose address and length
+    /// are multiples of the logical block size of the filesystem, at offsets
+    /// which are also multiples of it, otherwise they fail with an unhelpful
+    /// "invalid argument" error. Use [`check_direct_io`] to validate them
+    /// first. Any flags set with
+    /// [`OpenOptionsExt::custom_flags`](OpenOptionsExt::custom_flags) are
+    /// replaced.
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open_direct<P>(options: &crate::OpenOptions, path: P) -> io::Result<crate::File>
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+
+        let path = path.into();
+        let mut options = options.clone();
+        let file = options
+            .options_mut()
+            .custom_flags(libc::O_DIRECT)
+            .open(&path)
+            .with_path(ErrorKind::OpenDirect, &path)?;
+        Ok(crate::File::from_parts(file, path))
+    }
+
+    /// Checks that `buf` and `offset` are suitable for direct I/O on `file`,
+    /// which was opened with [`open_direct`].
+    ///
+    /// `alignment` is the logical block size of the filesystem, usually 512 or
+    /// 4096 bytes. If the address or length of `buf`, or `offset`, is not a
+    /// multiple of it, an error of kind [`io::ErrorKind::InvalidInput`] is
+    /// returned, which includes the path of the file and what was misaligned.
+    // `is_multiple_of` is not available on the minimum supported Rust version.
+    #[allow(clippy::manual_is_multiple_of)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn check_direct_io(
+        file: &crate::File,
+        buf: &[u8],
+        offset: u64,
+        alignment: usize,
+    ) -> io::Result<()> {
+        let kind = ErrorKind::DirectIo { alignment };
+        let alignment = alignment.max(1);
+        let message = if buf.as_ptr() as usize % alignment != 0 {
+            format!("buffer address {:p} is misaligned", buf.as_ptr())
+        } else if buf.len() % alignment != 0 {
+            format!("buffer length {} is misaligned", buf.len())
+        } else if offset % alignment as u64 != 0 {
+            format!("offset {} is misaligned", offset)
+        } else {
+            return Ok(());
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidInput, message)).with_path(kind, file.path())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "libc"))))]
+    pub use self::resolve::{openat2, ResolveFlags};
+
+    #[cfg(all(target_os = "linux", feature = "libc"))]
+    mod resolve {
+        use std::ffi::CString;
+        use std::fs;
+        use std::io;
+        use std::ops::{BitOr, BitOrAssign};
+        use std::os::raw::c_int;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::io::{AsRawFd, FromRawFd};
+        use std::path::Path;
+
+        use crate::errors::{ErrorKind, ResultExt};
+
+        /// Restrictions on how the path passed to [`openat2`] is resolved.
+        ///
+        /// Flags are combined with `|`.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct ResolveFlags(u64);
+
+        impl ResolveFlags {
+            /// Fails if the path crosses a mount point, including bind mounts.
+            pub const NO_XDEV: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_XDEV);
+            /// Fails if the path contains a "magic link", such as those in
+            /// `/proc/self/fd`.
+            pub const NO_MAGICLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_MAGICLINKS);
+            /// Fails if the path contains any symbolic link.
+            pub const NO_SYMLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_SYMLINKS);
+            /// Fails if the path is absolute or resolves to somewhere outside
+            /// of the directory, whether through `..` or symbolic links.
+            pub const BENEATH: ResolveFlags = ResolveFlags(libc::RESOLVE_BENEATH);
+            /// Resolves the path as if the directory were the root directory,
+            /// so that `..` and absolute symbolic links cannot leave it.
+            pub const IN_ROOT: ResolveFlags = ResolveFlags(libc::RESOLVE_IN_ROOT);
+
+            /// Returns flags with no restrictions.
+            pub const fn empty() -> ResolveFlags {
+                ResolveFlags(0)
+            }
+
+            /// Returns the value of the `RESOLVE_*` flags passed to the kernel.
+            pub const fn bits(self) -> u64 {
+                self.0
+            }
+
+            /// Returns whether all of the flags in `other` are set.
+            pub fn contains(self, other: ResolveFlags) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            fn intersects(self, other: ResolveFlags) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            /// Explains which restriction an error from `openat2` was caused
+            /// by, if any.
+            fn reason(self, err: &io::Error) -> Option<&'static str> {
+                let confined = self.intersects(ResolveFlags::BENEATH | ResolveFlags::IN_ROOT);
+                let no_xdev = self.contains(ResolveFlags::NO_XDEV);
+                match err.raw_os_error() {
+                    Some(libc::EXDEV) if confined && no_xdev => {
+                        Some("the path escapes the directory or crosses a mount point")
+                    }
+                    Some(libc::EXDEV) if confined => Some("the path escapes the directory"),
+                    Some(libc::EXDEV) if no_xdev => Some("the path crosses a mount point"),
+                    Some(libc::ELOOP) if self.contains(ResolveFlags::NO_SYMLINKS) => {
+                        Some("the path contains a symbolic link")
+                    }
+                    // Confining the path to the directory also forbids magic links.
+                    Some(libc::ELOOP) if confined || self.contains(ResolveFlags::NO_MAGICLINKS) => {
+                        Some("the path contains a magic link")
+                    }
+                    Some(libc::ENOSYS) => Some("openat2 requires Linux 5.6 or later"),
+                    _ => None,
+                }
+            }
+        }
+
+        impl BitOr for ResolveFlags {
+            type Output = ResolveFlags;
+
+            fn bitor(self, other: ResolveFlags) -> ResolveFlags {
+                ResolveFlags(self.0 | other.0)
+            }
+        }
+
+        impl BitOrAssign for ResolveFlags {
+            fn bitor_assign(&mut self, other: ResolveFlags) {
+                self.0 |= other.0;
+            }
+        }
+
+        /// Opens `path` relative to the directory `dir`, restricting how it is
+        /// resolved with `resolve`.
+        ///
+        /// `flags` are the `O_*` flags passed to
+        /// [`open`](https://man7.org/linux/man-pages/man2/open.2.html), such as
+        /// `O_RDONLY` or `O_WRONLY | O_CREAT`. `O_CLOEXEC` is always added.
+        /// `mode` gives the permissions of a file created with `O_CREAT` or
+        /// `O_TMPFILE`, and is ignored otherwise.
+        ///
+        /// Unlike [`ScopedRoot`](crate::ScopedRoot), the restrictions are
+        /// enforced by the kernel while the path is resolved, so they cannot be
+        /// circumvented by concurrently modifying the directory tree. If the
+        /// path violates one of them, the error says which, for example
+        /// "failed to open file `/srv/www/../secret` because the path escapes
+        /// the directory". The path of the returned [`File`](crate::File) is
+        /// `path` joined to the path of `dir`.
+        ///
+        /// This requires Linux 5.6 or later.
+        ///
+        /// Wrapper for [`openat2`](https://man7.org/linux/man-pages/man2/openat2.2.html).
+        ///
+        /// # Example
+        ///
+        /// ```no_run
+        /// use fs_err::os::unix::fs::{openat2, ResolveFlags};
+        ///
+        /// const O_RDONLY: i32 = 0;
+        ///
+        /// let root = fs_err::File::open("/srv/www")?;
+        /// let page = openat2(
+        ///     &root,
+        ///     "docs/index.html",
+        ///     O_RDONLY,
+        ///     0,
+        ///     ResolveFlags::BENEATH | ResolveFlags::NO_XDEV,
+        /// )?;
+        /// # Ok::<(), std::io::Error>(())
+        /// ```
+        #[cfg_attr(rustc_1_46, track_caller)]
+        pub fn openat2<P: AsRef<Path>>(
+            dir: &crate::File,
+            path: P,
+            flags: i32,
+            mode: u32,
+            resolve: ResolveFlags,
+        ) -> io::Result<crate::File> {
+            let path = path.as_ref();
+            let full_path = dir.path().join(path);
+            match open(dir, path, flags, mode, resolve) {
+                Ok(file) => Ok(crate::File::from_parts(file, full_path)),
+                Err(err) => {
+                    let reason = resolve.reason(&err);
+                    Err(err).with_path(ErrorKind::OpenAt2 { reason }, full_path)
+                }
+            }
+        }
+
+        fn open(
+            dir: &crate::File,
+            path: &Path,
+            flags: i32,
+            mode: u32,
+            resolve: ResolveFlags,
+        ) -> io::Result<fs::File> {
+            let path = CString::new(path.as_os_str().as_bytes())
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            let flags = flags | libc::O_CLOEXEC;
+            // The kernel rejects a mode unless a file may be created.
+            let mode = if flags & (libc::O_CREAT | libc::O_TMPFILE) != 0 {
+                mode & 0o7777
+            } else {
+                0
+            };
+            // SAFETY: `open_how` is a plain C struct, for which all zeroes is
+            // a valid value.
+            let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+            how.flags = flags as u64;
+            how.mode = u64::from(mode);
+            how.resolve = resolve.bits();
+            loop {
+                // SAFETY: the pointers are valid for the duration of the call,
+                // and the size passed is that of `how`.
+                let fd = unsafe {
+                    libc::syscall(
+                        libc::SYS_openat2,
+                        dir.as_raw_fd(),
+                        path.as_ptr(),
+                        &how as *const libc::open_how,
+                        std::mem::size_of::<libc::open_how>(),
+                    )
+                };
+                if fd >= 0 {
+                    // SAFETY: the kernel returned a new file descriptor which
+                    // nothing else owns.
+                    return Ok(unsafe { fs::File::from_raw_fd(fd as c_int) });
+                }
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+        }
     }
 
     /// Wrapper for [`std::os::unix::fs::FileExt`](https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html).
@@ -25,6 +514,10 @@ pub mod fs {
         fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
         /// Wrapper for [`FileExt::write_at`](https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.write_at)
         fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
+        /// Wrapper for [`FileExt::read_exact_at`](https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#method.read_exact_at)
+        fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+        /// Wrapper for [`FileExt::write_all_at`](https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#method.write_all_at)
+        fn write_all_at(&self, buf: &[u8], offset: u64) -> io::Result<()>;
     }
 
     /// Wrapper for [`std::os::unix::fs::OpenOptionsExt`](https://doc.rust-lang.org/std/os/unix/fs/trait.OpenOptionsExt.html)
@@ -37,4 +530,116 @@ pub mod fs {
         /// Wrapper for [`OpenOptionsExt::custom_flags`](https://doc.rust-lang.org/std/os/unix/fs/trait.OpenOptionsExt.html#tymethod.custom_flags)
         fn custom_flags(&mut self, flags: i32) -> &mut Self;
     }
+
+    /// Wrapper for [`std::os::unix::fs::DirBuilderExt`](https://doc.rust-lang.org/std/os/unix/fs/trait.DirBuilderExt.html)
+    ///
+    /// The std traits might be extended in the future (See issue [#49961](https://github.com/rust-lang/rust/issues/49961#issuecomment-382751777)).
+    /// This trait is sealed and can not be implemented by other crates.
+    pub trait DirBuilderExt: crate::Sealed {
+        /// Wrapper for [`DirBuilderExt::mode`](https://doc.rust-lang.org/std/os/unix/fs/trait.DirBuilderExt.html#tymethod.mode)
+        fn mode(&mut self, mode: u32) -> &mut Self;
+    }
+}
+
+#[cfg(all(
+    rustc_1_75,
+    any(target_os = "linux", target_os = "android"),
+    feature = "libc"
+))]
+mod xattr {
+    use std::ffi::CString;
+    use std::fs;
+    use std::io;
+    use std::os::raw::{c_char, c_void};
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::ptr;
+
+    use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+    /// Copies the contents, extended attributes and permissions of `from` to
+    /// `to`, returning the number of bytes copied.
+    ///
+    /// The permissions are applied last, since setting attributes requires
+    /// write access to the file.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub(super) fn copy_file(from: &Path, to: &Path) -> io::Result<u64> {
+        let mut src = fs::File::open(from).with_path(ErrorKind::OpenFile, from)?;
+        let permissions = src
+            .metadata()
+            .with_path(ErrorKind::Metadata, from)?
+            .permissions();
+        let mut dst = fs::File::create(to).with_path(ErrorKind::CreateFile, to)?;
+        let len = io::copy(&mut src, &mut dst).with_paths(SourceDestErrorKind::Copy, from, to)?;
+        copy(&src, from, &dst, to)?;
+        dst.set_permissions(permissions)
+            .with_path(ErrorKind::SetPermissions, to)?;
+        Ok(len)
+    }
+
+    /// Copies all extended attributes of `src` to `dst`.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn copy(src: &fs::File, from: &Path, dst: &fs::File, to: &Path) -> io::Result<()> {
+        // SAFETY: the buffer is valid for writes of `len` bytes.
+        let names = match read_buf(|buf, len| unsafe {
+            libc::flistxattr(src.as_raw_fd(), buf as *mut c_char, len)
+        }) {
+            Ok(names) => names,
+            // The source filesystem does not support extended attributes, so there are none to copy.
+            Err(ref err) if err.raw_os_error() == Some(libc::EOPNOTSUPP) => return Ok(()),
+            Err(err) => return Err(err).with_path(ErrorKind::ReadXattrs, from),
+        };
+
+        for name in names.split(|&b| b == 0).filter(|name| !name.is_empty()) {
+            let privileged = name.starts_with(b"security.") || name.starts_with(b"trusted.");
+            let name = CString::new(name).expect("attribute names are nul-separated");
+            // SAFETY: the name is nul-terminated and the buffer is valid for writes of `len` bytes.
+            let value = read_buf(|buf, len| unsafe {
+                libc::fgetxattr(src.as_raw_fd(), name.as_ptr(), buf, len)
+            })
+            .with_path(ErrorKind::ReadXattrs, from)?;
+            // SAFETY: the name is nul-terminated and the value is valid for reads of its length.
+            let ret = unsafe {
+                libc::fsetxattr(
+                    dst.as_raw_fd(),
+                    name.as_ptr(),
+                    value.as_ptr() as *const c_void,
+                    value.len(),
+                    0,
+                )
+            };
+            if ret == -1 {
+                let err = io::Error::last_os_error();
+                match err.raw_os_error() {
+                    // Setting these namespaces needs privileges or support
+                    // which the destination may lack, such as an SELinux label
+                    // on a filesystem without labels.
+                    Some(libc::EPERM) | Some(libc::EOPNOTSUPP) if privileged => {}
+                    _ => return Err(err).with_path(ErrorKind::WriteXattrs, to),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with a null buffer to query the required size, then again to
+    /// fill a buffer of that size, retrying if the size changed in between.
+    fn read_buf(mut f: impl FnMut(*mut c_void, usize) -> isize) -> io::Result<Vec<u8>> {
+        loop {
+            let len = f(ptr::null_mut(), 0);
+            if len < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut buf = vec![0u8; len as usize];
+            let read = f(buf.as_mut_ptr() as *mut c_void, buf.len());
+            if read >= 0 {
+                buf.truncate(read as usize);
+                return Ok(buf);
+            }
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ERANGE) {
+                return Err(err);
+            }
+        }
+    }
 }
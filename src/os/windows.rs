@@ -1,27 +1,35 @@
 /// Windows-specific extensions to wrappers in `fs_err` for `std::fs` types.
 pub mod fs {
-    use crate::{SourceDestError, SourceDestErrorKind};
+    use crate::errors::{ResultExt, SourceDestErrorKind};
     use std::io;
     use std::path::Path;
 
     /// Creates a new symlink to a directory on the filesystem.
     ///
     /// Wrapper for [std::os::windows::fs::symlink_dir](https://doc.rust-lang.org/std/os/windows/fs/fn.symlink_dir.html)
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn symlink_dir<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
         let src = src.as_ref();
         let dst = dst.as_ref();
-        std::os::windows::fs::symlink_dir(src, dst)
-            .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::SymlinkDir, src, dst))
+        std::os::windows::fs::symlink_dir(src, dst).with_paths(
+            SourceDestErrorKind::SymlinkDir,
+            src,
+            dst,
+        )
     }
 
     /// Creates a new symlink to a non-directory file on the filesystem.
     ///
     /// Wrapper for [std::os::windows::fs::symlink_file](https://doc.rust-lang.org/std/os/windows/fs/fn.symlink_file.html)
+    #[cfg_attr(rustc_1_46, track_caller)]
     pub fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
         let src = src.as_ref();
         let dst = dst.as_ref();
-        std::os::windows::fs::symlink_file(src, dst)
-            .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::SymlinkFile, src, dst))
+        std::os::windows::fs::symlink_file(src, dst).with_paths(
+            SourceDestErrorKind::SymlinkFile,
+            src,
+            dst,
+        )
     }
 
     /// Wrapper for [`std::os::windows::fs::FileExt`](https://doc.rust-lang.org/std/os/windows/fs/trait.FileExt.html).
@@ -1,5 +1,5 @@
 #[allow(unused_imports)]
-use crate::errors::{Error, ErrorKind};
+use crate::errors::{ErrorKind, ResultExt};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -28,6 +28,11 @@ pub trait PathExt: crate::Sealed {
     ///
     /// Wrapper for [`crate::canonicalize`].
     fn fs_err_canonicalize(&self) -> io::Result<PathBuf>;
+    /// Makes a path absolute without accessing the filesystem.
+    ///
+    /// Wrapper for [`crate::absolute`].
+    #[cfg(rustc_1_79)]
+    fn fs_err_absolute(&self) -> io::Result<PathBuf>;
     /// Reads a symbolic link, returning the file that the link points to.
     ///
     /// Wrapper for [`crate::read_link`].
@@ -36,32 +41,164 @@ pub trait PathExt: crate::Sealed {
     ///
     /// Wrapper for [`crate::read_dir`].
     fn fs_err_read_dir(&self) -> io::Result<crate::ReadDir>;
+    /// Reads the entire contents of a file into a bytes vector.
+    ///
+    /// Wrapper for [`crate::read`].
+    fn fs_err_read(&self) -> io::Result<Vec<u8>>;
+    /// Reads the entire contents of a file into a string.
+    ///
+    /// Wrapper for [`crate::read_to_string`].
+    fn fs_err_read_to_string(&self) -> io::Result<String>;
+    /// Writes a slice as the entire contents of a file.
+    ///
+    /// Wrapper for [`crate::write`].
+    fn fs_err_write(&self, contents: &[u8]) -> io::Result<()>;
+    /// Opens a file in read-only mode.
+    ///
+    /// Wrapper for [`crate::File::open`].
+    fn fs_err_open(&self) -> io::Result<crate::File>;
+    /// Opens a file in write-only mode, creating it if it does not exist and
+    /// truncating it if it does.
+    ///
+    /// Wrapper for [`crate::File::create`].
+    fn fs_err_create(&self) -> io::Result<crate::File>;
+    /// Removes a file from the filesystem.
+    ///
+    /// Wrapper for [`crate::remove_file`].
+    fn fs_err_remove_file(&self) -> io::Result<()>;
+    /// Removes an empty directory.
+    ///
+    /// Wrapper for [`crate::remove_dir`].
+    fn fs_err_remove_dir(&self) -> io::Result<()>;
+    /// Removes a directory at this path, after removing all its contents.
+    ///
+    /// Wrapper for [`crate::remove_dir_all`].
+    fn fs_err_remove_dir_all(&self) -> io::Result<()>;
+    /// Creates a new, empty directory at the provided path.
+    ///
+    /// Wrapper for [`crate::create_dir`].
+    fn fs_err_create_dir(&self) -> io::Result<()>;
+    /// Recursively creates a directory and all of its parent components if
+    /// they are missing.
+    ///
+    /// Wrapper for [`crate::create_dir_all`].
+    fn fs_err_create_dir_all(&self) -> io::Result<()>;
+    /// Copies the contents of this file to `dest`, returning the number of
+    /// bytes copied.
+    ///
+    /// Wrapper for [`crate::copy`].
+    fn fs_err_copy_to(&self, dest: &Path) -> io::Result<u64>;
+    /// Renames this file or directory to `dest`, replacing the original file
+    /// if `dest` already exists.
+    ///
+    /// Wrapper for [`crate::rename`].
+    fn fs_err_rename_to(&self, dest: &Path) -> io::Result<()>;
+    /// Creates a new hard link at `dest` to this file.
+    ///
+    /// Wrapper for [`crate::hard_link`].
+    fn fs_err_hard_link_to(&self, dest: &Path) -> io::Result<()>;
 }
 
 impl PathExt for Path {
     #[cfg(rustc_1_63)]
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_try_exists(&self) -> io::Result<bool> {
-        self.try_exists()
-            .map_err(|source| Error::build(source, ErrorKind::FileExists, self))
+        self.try_exists().with_path(ErrorKind::FileExists, self)
     }
 
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_metadata(&self) -> io::Result<fs::Metadata> {
         crate::metadata(self)
     }
 
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_symlink_metadata(&self) -> io::Result<fs::Metadata> {
         crate::symlink_metadata(self)
     }
 
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_canonicalize(&self) -> io::Result<PathBuf> {
         crate::canonicalize(self)
     }
 
+    #[cfg(rustc_1_79)]
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_absolute(&self) -> io::Result<PathBuf> {
+        crate::absolute(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_read_link(&self) -> io::Result<PathBuf> {
         crate::read_link(self)
     }
 
+    #[cfg_attr(rustc_1_46, track_caller)]
     fn fs_err_read_dir(&self) -> io::Result<crate::ReadDir> {
         crate::read_dir(self)
     }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_read(&self) -> io::Result<Vec<u8>> {
+        crate::read(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_read_to_string(&self) -> io::Result<String> {
+        crate::read_to_string(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_write(&self, contents: &[u8]) -> io::Result<()> {
+        crate::write(self, contents)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_open(&self) -> io::Result<crate::File> {
+        crate::File::open(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_create(&self) -> io::Result<crate::File> {
+        crate::File::create(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_remove_file(&self) -> io::Result<()> {
+        crate::remove_file(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_remove_dir(&self) -> io::Result<()> {
+        crate::remove_dir(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_remove_dir_all(&self) -> io::Result<()> {
+        crate::remove_dir_all(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_create_dir(&self) -> io::Result<()> {
+        crate::create_dir(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_create_dir_all(&self) -> io::Result<()> {
+        crate::create_dir_all(self)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_copy_to(&self, dest: &Path) -> io::Result<u64> {
+        crate::copy(self, dest)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_rename_to(&self, dest: &Path) -> io::Result<()> {
+        crate::rename(self, dest)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn fs_err_hard_link_to(&self, dest: &Path) -> io::Result<()> {
+        crate::hard_link(self, dest)
+    }
 }
@@ -0,0 +1,100 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Changes the permissions of a file or directory and, recursively, of
+/// everything within it.
+///
+/// Symbolic links are not followed. Use [`SetPermissionsOptions`] to set
+/// different permissions for files and directories. Errors include the path of
+/// the entry whose permissions could not be changed.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn set_permissions_recursive<P: AsRef<Path>>(path: P, perm: fs::Permissions) -> io::Result<()> {
+    SetPermissionsOptions::new()
+        .files(perm.clone())
+        .dirs(perm)
+        .set(path)
+}
+
+/// Options for recursively changing permissions.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(unix)]
+/// # fn main() -> std::io::Result<()> {
+/// use std::fs::Permissions;
+/// use std::os::unix::fs::PermissionsExt;
+///
+/// fs_err::SetPermissionsOptions::new()
+///     .files(Permissions::from_mode(0o644))
+///     .dirs(Permissions::from_mode(0o755))
+///     .set("public")?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(unix))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetPermissionsOptions {
+    files: Option<fs::Permissions>,
+    dirs: Option<fs::Permissions>,
+}
+
+impl SetPermissionsOptions {
+    /// Creates options which leave the permissions of all entries unchanged.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SetPermissionsOptions {
+            files: None,
+            dirs: None,
+        }
+    }
+
+    /// Sets the permissions given to files.
+    pub fn files(&mut self, perm: fs::Permissions) -> &mut Self {
+        self.files = Some(perm);
+        self
+    }
+
+    /// Sets the permissions given to directories.
+    pub fn dirs(&mut self, perm: fs::Permissions) -> &mut Self {
+        self.dirs = Some(perm);
+        self
+    }
+
+    /// Changes the permissions of `path` and, recursively, of everything within
+    /// it, with these options.
+    ///
+    /// The contents of each directory are changed before the directory itself,
+    /// so that permissions which prevent reading a directory do not stop it
+    /// from being traversed. Symbolic links are not followed.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn set<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let metadata = fs::symlink_metadata(path).with_path(ErrorKind::SymlinkMetadata, path)?;
+        self.set_entry(path, metadata.file_type())
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn set_entry(&self, path: &Path, file_type: fs::FileType) -> io::Result<()> {
+        let perm = if file_type.is_dir() {
+            for entry in crate::read_dir(path)? {
+                let entry = entry?;
+                self.set_entry(&entry.path(), entry.file_type()?)?;
+            }
+            &self.dirs
+        } else if file_type.is_symlink() {
+            return Ok(());
+        } else {
+            &self.files
+        };
+
+        if let Some(perm) = perm {
+            fs::set_permissions(path, perm.clone()).with_path(ErrorKind::SetPermissions, path)?;
+        }
+        Ok(())
+    }
+}
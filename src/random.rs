@@ -0,0 +1,11 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Returns a random number, using the randomly seeded keys of `RandomState` to
+/// avoid depending on a random number generator.
+///
+/// The numbers are unpredictable enough for names of temporary files, jitter
+/// and test data, but not for cryptography.
+pub(crate) fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
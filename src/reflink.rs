@@ -0,0 +1,133 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::errors::{ResultExt, SourceDestErrorKind};
+
+/// Copies the contents of one file to another as a copy-on-write clone, if the
+/// filesystem supports it.
+///
+/// A clone shares its data with the original file until either is modified,
+/// so it is created almost instantly and uses no extra space. With the `libc`
+/// feature, this uses the `FICLONE` ioctl on Linux, for example on Btrfs and
+/// XFS, and `clonefile` on macOS, for example on APFS. If cloning is not
+/// supported by the platform or filesystem, or the `libc` feature is not
+/// enabled, the file is copied with [`copy`](crate::copy) instead.
+///
+/// Like [`copy`](crate::copy), this copies the permissions of `from` to `to`
+/// and returns the number of bytes copied. Errors identify whether cloning or
+/// copying failed.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn copy_reflink<P, Q>(from: P, to: Q) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+    if let Some(len) = sys::reflink(from, to)? {
+        return Ok(len);
+    }
+    fs::copy(from, to).with_paths(SourceDestErrorKind::Copy, from, to)
+}
+
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+mod sys {
+    use std::fs;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+    /// Clones `from` to `to`, returning `None` if cloning is not supported.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub(super) fn reflink(from: &Path, to: &Path) -> io::Result<Option<u64>> {
+        let src = fs::File::open(from).with_path(ErrorKind::OpenFile, from)?;
+        let metadata = src.metadata().with_path(ErrorKind::Metadata, from)?;
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+        let dst = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(to)
+            .with_path(ErrorKind::CreateFile, to)?;
+
+        // The type of the request differs between C libraries.
+        // SAFETY: both file descriptors are valid for the duration of the call.
+        if unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE as _, src.as_raw_fd()) } == -1 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(libc::EXDEV)
+                | Some(libc::EINVAL)
+                | Some(libc::ENOTTY)
+                | Some(libc::EOPNOTSUPP) => Ok(None),
+                _ => Err(err).with_paths(SourceDestErrorKind::Reflink, from, to),
+            };
+        }
+
+        dst.set_permissions(metadata.permissions())
+            .with_path(ErrorKind::SetPermissions, to)?;
+        Ok(Some(metadata.len()))
+    }
+}
+
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "libc"))]
+mod sys {
+    use std::ffi::CString;
+    use std::fs;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+    /// Clones `from` to `to`, returning `None` if cloning is not supported.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub(super) fn reflink(from: &Path, to: &Path) -> io::Result<Option<u64>> {
+        let metadata = fs::metadata(from).with_path(ErrorKind::Metadata, from)?;
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+        let (src, dst) = match (path_to_cstring(from), path_to_cstring(to)) {
+            (Some(src), Some(dst)) => (src, dst),
+            _ => return Ok(None),
+        };
+
+        // SAFETY: both paths are valid nul-terminated strings.
+        if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == -1 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                // `clonefile` cannot replace an existing file, but `copy` can.
+                Some(libc::EEXIST) | Some(libc::EXDEV) | Some(libc::ENOTSUP) => Ok(None),
+                _ => Err(err).with_paths(SourceDestErrorKind::Reflink, from, to),
+            };
+        }
+        Ok(Some(metadata.len()))
+    }
+
+    fn path_to_cstring(path: &Path) -> Option<CString> {
+        CString::new(path.as_os_str().as_bytes()).ok()
+    }
+}
+
+#[cfg(not(all(
+    any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios"
+    ),
+    feature = "libc"
+)))]
+mod sys {
+    use std::io;
+    use std::path::Path;
+
+    /// Cloning is not supported on this platform.
+    pub(super) fn reflink(_from: &Path, _to: &Path) -> io::Result<Option<u64>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::{report_drop_error, ErrorKind, ResultExt};
+
+const ATTEMPTS: u32 = 10;
+const BACKOFF: Duration = Duration::from_millis(10);
+
+/// Removes a directory and all of its contents, retrying on errors which are
+/// likely to be transient.
+///
+/// On Windows, virus scanners and search indexers briefly open files, which
+/// makes removing them or their parent directory fail with "access denied" or
+/// "directory not empty". This function retries those operations several times
+/// with increasing delays, and clears the read-only attribute of entries before
+/// removing them. On other platforms, it behaves like
+/// [`remove_dir_all`](crate::remove_dir_all).
+///
+/// Unlike [`remove_dir_all`](crate::remove_dir_all), errors include the path
+/// of the entry within the tree which could not be removed.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_dir_all_robust<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    remove_tree(path.as_ref())
+}
+
+/// Returns a guard which removes the file or directory at `path` when dropped.
+///
+/// See [`RemoveGuard`] for details.
+///
+/// # Example
+///
+/// ```no_run
+/// let guard = fs_err::remove_on_drop("output.o.partial");
+/// fs_err::write(guard.path(), b"...")?;
+/// // If anything fails before this point, the partial output is removed.
+/// fs_err::rename(guard.disarm(), "output.o")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn remove_on_drop<P: AsRef<Path>>(path: P) -> RemoveGuard {
+    RemoveGuard {
+        path: Some(path.as_ref().to_path_buf()),
+    }
+}
+
+/// A guard which removes a file or directory when dropped, including when
+/// unwinding from a panic, unless it is disarmed.
+///
+/// Directories are removed along with their contents, as by
+/// [`remove_dir_all_robust`], and symbolic links are removed rather than their
+/// targets. Nothing happens if the path does not exist, so the guard can be
+/// created before the file is. Errors from removing the path when the guard is
+/// dropped are passed to the handler set with
+/// [`set_drop_error_handler`](crate::set_drop_error_handler), if any. Use
+/// [`remove`](RemoveGuard::remove) to handle the error directly instead.
+#[derive(Debug)]
+#[must_use = "the path is removed immediately if the guard is not kept"]
+pub struct RemoveGuard {
+    path: Option<PathBuf>,
+}
+
+impl RemoveGuard {
+    /// Returns the path which will be removed.
+    pub fn path(&self) -> &Path {
+        self.path.as_ref().expect("guard already disarmed")
+    }
+
+    /// Disarms the guard so that the path is kept, and returns the path.
+    pub fn disarm(mut self) -> PathBuf {
+        self.path.take().expect("guard already disarmed")
+    }
+
+    /// Removes the path now, returning any error.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove(mut self) -> io::Result<()> {
+        let path = self.path.take().expect("guard already disarmed");
+        remove_any(&path)
+    }
+}
+
+impl Drop for RemoveGuard {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if let Err(err) = remove_any(&path) {
+                report_drop_error(err);
+            }
+        }
+    }
+}
+
+/// Removes a file, directory or symbolic link, if it exists.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn remove_any(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).with_path(ErrorKind::SymlinkMetadata, path),
+    };
+    if metadata.is_dir() {
+        remove_tree(path)
+    } else {
+        clear_readonly(path)?;
+        if is_dir_symlink(path) {
+            retry(|| fs::remove_dir(path)).with_path(ErrorKind::RemoveDir, path)
+        } else {
+            retry(|| fs::remove_file(path)).with_path(ErrorKind::RemoveFile, path)
+        }
+    }
+}
+
+#[cfg_attr(rustc_1_46, track_caller)]
+fn remove_tree(dir: &Path) -> io::Result<()> {
+    for entry in crate::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            remove_tree(&path)?;
+        } else {
+            clear_readonly(&path)?;
+            if is_dir_symlink(&path) {
+                retry(|| fs::remove_dir(&path)).with_path(ErrorKind::RemoveDir, &path)?;
+            } else {
+                retry(|| fs::remove_file(&path)).with_path(ErrorKind::RemoveFile, &path)?;
+            }
+        }
+    }
+    clear_readonly(dir)?;
+    retry(|| fs::remove_dir(dir)).with_path(ErrorKind::RemoveDir, dir)
+}
+
+fn retry(mut f: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(ref err) if attempt + 1 < ATTEMPTS && is_transient(err) => {
+                thread::sleep(BACKOFF * (1 << attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(windows)]
+fn is_transient(err: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_DIR_NOT_EMPTY: i32 = 145;
+
+    err.kind() == io::ErrorKind::PermissionDenied
+        || err.raw_os_error() == Some(ERROR_SHARING_VIOLATION)
+        || err.raw_os_error() == Some(ERROR_DIR_NOT_EMPTY)
+}
+
+#[cfg(not(windows))]
+fn is_transient(_err: &io::Error) -> bool {
+    false
+}
+
+/// Clears the read-only attribute, which prevents files and directories from
+/// being removed on Windows.
+#[cfg(windows)]
+#[cfg_attr(rustc_1_46, track_caller)]
+fn clear_readonly(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path).with_path(ErrorKind::SymlinkMetadata, path)?;
+    let mut permissions = metadata.permissions();
+    if permissions.readonly() {
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(path, permissions).with_path(ErrorKind::SetPermissions, path)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn clear_readonly(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Symbolic links to directories must be removed as directories on Windows.
+#[cfg(windows)]
+fn is_dir_symlink(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_DIRECTORY != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+fn is_dir_symlink(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,314 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::errors::{ErrorKind, ResultExt, SourceDestErrorKind};
+
+/// The maximum number of symbolic links followed when resolving a path, as on
+/// Linux.
+const MAX_LINKS: usize = 40;
+
+/// A directory which paths are resolved within, rejecting paths which would
+/// escape it.
+///
+/// Paths passed to the methods of `ScopedRoot` are relative to the root. They
+/// are resolved one component at a time, following symbolic links, and the
+/// operation fails if the result is outside of the root, whether because of
+/// `..` components or symbolic links which point elsewhere. Absolute paths are
+/// rejected. The error describes how the path escaped, and has kind
+/// [`io::ErrorKind::PermissionDenied`].
+///
+/// The path is checked before the operation is performed, so a process which
+/// can modify the directory tree at the same time could still cause it to
+/// escape the root.
+///
+/// # Example
+///
+/// ```no_run
+/// let root = fs_err::ScopedRoot::new("public")?;
+/// let page = root.read_to_string("docs/index.html")?;
+///
+/// // Fails with an error explaining that the path escapes `public`.
+/// assert!(root.read_to_string("../secrets.txt").is_err());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScopedRoot {
+    root: PathBuf,
+}
+
+/// A component of a path which has not been resolved yet, along with the index
+/// of the symbolic link it came from, if any.
+struct Pending {
+    component: Part,
+    link: Option<usize>,
+}
+
+enum Part {
+    Root(PathBuf),
+    Parent,
+    Normal(OsString),
+}
+
+impl ScopedRoot {
+    /// Creates a `ScopedRoot` for the directory `root`, which is canonicalized
+    /// first.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn new<P: AsRef<Path>>(root: P) -> io::Result<ScopedRoot> {
+        let root = root.as_ref();
+        let root = fs::canonicalize(root).with_path(ErrorKind::Canonicalize, root)?;
+        Ok(ScopedRoot { root })
+    }
+
+    /// Returns the canonical path of the root directory.
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolves `path` within the root, returning the absolute path it refers
+    /// to with all symbolic links followed.
+    ///
+    /// Components which do not exist are kept as they are.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> io::Result<PathBuf> {
+        self.resolve_as(path.as_ref(), true, ErrorKind::Canonicalize)
+    }
+
+    /// Opens a file within the root in read-only mode.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<crate::File> {
+        crate::File::open(self.resolve_as(path.as_ref(), true, ErrorKind::OpenFile)?)
+    }
+
+    /// Opens a file within the root in write-only mode, creating or truncating
+    /// it.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create<P: AsRef<Path>>(&self, path: P) -> io::Result<crate::File> {
+        crate::File::create(self.resolve_as(path.as_ref(), true, ErrorKind::CreateFile)?)
+    }
+
+    /// Reads the entire contents of a file within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<u8>> {
+        crate::read(self.resolve_as(path.as_ref(), true, ErrorKind::OpenFile)?)
+    }
+
+    /// Reads the entire contents of a file within the root into a string.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn read_to_string<P: AsRef<Path>>(&self, path: P) -> io::Result<String> {
+        crate::read_to_string(self.resolve_as(path.as_ref(), true, ErrorKind::OpenFile)?)
+    }
+
+    /// Writes a slice as the entire contents of a file within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(&self, path: P, contents: C) -> io::Result<()> {
+        crate::write(
+            self.resolve_as(path.as_ref(), true, ErrorKind::CreateFile)?,
+            contents,
+        )
+    }
+
+    /// Queries the metadata of a file or directory within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<fs::Metadata> {
+        crate::metadata(self.resolve_as(path.as_ref(), true, ErrorKind::Metadata)?)
+    }
+
+    /// Queries the metadata of a file, directory or symbolic link within the
+    /// root, without following the last component if it is a symbolic link.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn symlink_metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<fs::Metadata> {
+        crate::symlink_metadata(self.resolve_as(
+            path.as_ref(),
+            false,
+            ErrorKind::SymlinkMetadata,
+        )?)
+    }
+
+    /// Returns an iterator over the entries of a directory within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<crate::ReadDir> {
+        crate::read_dir(self.resolve_as(path.as_ref(), true, ErrorKind::ReadDir)?)
+    }
+
+    /// Creates a directory within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        crate::create_dir(self.resolve_as(path.as_ref(), true, ErrorKind::CreateDir)?)
+    }
+
+    /// Creates a directory within the root, along with any missing parents.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn create_dir_all<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        crate::create_dir_all(self.resolve_as(path.as_ref(), true, ErrorKind::CreateDir)?)
+    }
+
+    /// Removes a file or symbolic link within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        crate::remove_file(self.resolve_as(path.as_ref(), false, ErrorKind::RemoveFile)?)
+    }
+
+    /// Removes an empty directory within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        crate::remove_dir(self.resolve_as(path.as_ref(), false, ErrorKind::RemoveDir)?)
+    }
+
+    /// Removes a directory within the root, along with all of its contents.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove_dir_all<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        crate::remove_dir_all(self.resolve_as(path.as_ref(), false, ErrorKind::RemoveDir)?)
+    }
+
+    /// Renames a file or directory within the root. Both paths must be within
+    /// the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> io::Result<()> {
+        let (from, to) = self.resolve_both(
+            from.as_ref(),
+            to.as_ref(),
+            false,
+            SourceDestErrorKind::Rename,
+        )?;
+        crate::rename(from, to)
+    }
+
+    /// Copies the contents and permissions of a file within the root to
+    /// another. Both paths must be within the root.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> io::Result<u64> {
+        let (from, to) =
+            self.resolve_both(from.as_ref(), to.as_ref(), true, SourceDestErrorKind::Copy)?;
+        crate::copy(from, to)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn resolve_as(&self, path: &Path, follow_last: bool, kind: ErrorKind) -> io::Result<PathBuf> {
+        self.resolve_inner(path, follow_last)
+            .with_path(kind, self.root.join(path))
+    }
+
+    /// Resolves the source and destination of an operation.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn resolve_both(
+        &self,
+        from: &Path,
+        to: &Path,
+        follow_last: bool,
+        kind: SourceDestErrorKind,
+    ) -> io::Result<(PathBuf, PathBuf)> {
+        let resolved_from = self.resolve_inner(from, follow_last);
+        let resolved_to = self.resolve_inner(to, follow_last);
+        match (resolved_from, resolved_to) {
+            (Ok(from), Ok(to)) => Ok((from, to)),
+            (Err(err), _) | (_, Err(err)) => {
+                Err(err).with_paths(kind, self.root.join(from), self.root.join(to))
+            }
+        }
+    }
+
+    fn resolve_inner(&self, path: &Path, follow_last: bool) -> io::Result<PathBuf> {
+        let mut pending = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "absolute paths are not allowed",
+                    ))
+                }
+                Component::CurDir => {}
+                Component::ParentDir => pending.push(Part::Parent),
+                Component::Normal(name) => pending.push(Part::Normal(name.to_owned())),
+            }
+        }
+        let mut pending: Vec<Pending> = pending
+            .into_iter()
+            .rev()
+            .map(|component| Pending {
+                component,
+                link: None,
+            })
+            .collect();
+
+        let mut current = self.root.clone();
+        let mut links: Vec<PathBuf> = Vec::new();
+        // How the path first left the root, if it did.
+        let mut escape: Option<Option<usize>> = None;
+        while let Some(Pending { component, link }) = pending.pop() {
+            match component {
+                Part::Root(root) => current = root,
+                Part::Parent => {
+                    current.pop();
+                }
+                Part::Normal(name) => {
+                    let next = current.join(&name);
+                    if !pending.is_empty() || follow_last {
+                        match fs::symlink_metadata(&next) {
+                            Ok(ref metadata) if metadata.file_type().is_symlink() => {
+                                if links.len() >= MAX_LINKS {
+                                    return Err(io::Error::new(
+                                        io::ErrorKind::InvalidInput,
+                                        "too many levels of symbolic links",
+                                    ));
+                                }
+                                let target = fs::read_link(&next)?;
+                                let index = links.len();
+                                links.push(next);
+                                push_target(&mut pending, &target, index);
+                                continue;
+                            }
+                            Ok(_) => {}
+                            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+                            Err(err) => return Err(err),
+                        }
+                    }
+                    current = next;
+                }
+            }
+            if escape.is_none() && !current.starts_with(&self.root) {
+                escape = Some(link);
+            }
+        }
+
+        if current.starts_with(&self.root) {
+            return Ok(current);
+        }
+        let how = match escape {
+            Some(Some(link)) => format!("through the symbolic link `{}`", links[link].display()),
+            _ => "through `..`".to_owned(),
+        };
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "path escapes the root directory `{}` {}",
+                self.root.display(),
+                how
+            ),
+        ))
+    }
+}
+
+/// Adds the components of the target of a symbolic link to the front of the
+/// pending components.
+fn push_target(pending: &mut Vec<Pending>, target: &Path, link: usize) {
+    let mut root = PathBuf::new();
+    let mut parts = Vec::new();
+    for component in target.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => root.push(component.as_os_str()),
+            Component::CurDir => {}
+            Component::ParentDir => parts.push(Part::Parent),
+            Component::Normal(name) => parts.push(Part::Normal(name.to_owned())),
+        }
+    }
+    if root != Path::new("") {
+        parts.insert(0, Part::Root(root));
+    }
+    pending.extend(parts.into_iter().rev().map(|component| Pending {
+        component,
+        link: Some(link),
+    }));
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+use crate::random::random_u64;
+
+const CHUNK: usize = 64 * 1024;
+
+/// Overwrites the contents of a file once with zeros, syncs it to disk and then
+/// removes it.
+///
+/// This uses the default [`SecureRemoveOptions`]; see there for how effective
+/// this is on different storage.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_file_secure<P: AsRef<Path>>(path: P) -> io::Result<SecureRemoval> {
+    SecureRemoveOptions::new().remove(path)
+}
+
+/// Options for overwriting a file before removing it.
+///
+/// Each pass overwrites the whole file and syncs it to disk. The last pass
+/// writes zeros, and any earlier passes write pseudo-random data. Errors say
+/// whether opening, overwriting, syncing or removing the file failed, and
+/// during which pass.
+///
+/// Overwriting is only effective if the filesystem writes the new data in
+/// place. Copy-on-write and log-structured filesystems such as Btrfs, ZFS and
+/// APFS write it elsewhere, and SSDs may remap blocks internally, so the
+/// original data can remain on the storage device. On Linux with the `libc`
+/// feature, the filesystem is checked and the returned [`SecureRemoval`]
+/// includes a [`warning`](SecureRemoval::warning) if it is known not to
+/// overwrite in place. Other platforms are not checked.
+///
+/// # Example
+///
+/// ```no_run
+/// let removal = fs_err::SecureRemoveOptions::new()
+///     .passes(3)
+///     .remove("credentials.json")?;
+/// if let Some(warning) = removal.warning() {
+///     eprintln!("warning: {}", warning);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecureRemoveOptions {
+    passes: u32,
+}
+
+impl SecureRemoveOptions {
+    /// Creates options which overwrite the file once.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SecureRemoveOptions { passes: 1 }
+    }
+
+    /// Sets the number of times the file is overwritten. At least one pass is
+    /// always made.
+    pub fn passes(&mut self, passes: u32) -> &mut Self {
+        self.passes = passes.max(1);
+        self
+    }
+
+    /// Overwrites the file at `path` with these options, then removes it.
+    ///
+    /// Symbolic links and other files which are not regular files are
+    /// rejected with an error of kind [`io::ErrorKind::InvalidInput`].
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> io::Result<SecureRemoval> {
+        let path = path.as_ref();
+        let mut file = open(path).with_path(ErrorKind::ShredOpen, path)?;
+        let len = file.metadata().with_path(ErrorKind::ShredOpen, path)?.len();
+
+        let mut rng = Rng::new();
+        let mut buf = vec![0; CHUNK];
+        for pass in 1..=self.passes {
+            let last = pass == self.passes;
+            overwrite(&mut file, len, &mut buf, !last, &mut rng).with_path(
+                ErrorKind::ShredOverwrite {
+                    pass,
+                    passes: self.passes,
+                },
+                path,
+            )?;
+            file.sync_data().with_path(
+                ErrorKind::ShredSync {
+                    pass,
+                    passes: self.passes,
+                },
+                path,
+            )?;
+        }
+
+        let warning = copy_on_write_filesystem(&file).map(|name| {
+            format!(
+                "file `{}` was on a {} filesystem, which does not overwrite data in place, \
+                 so its original contents may still be on disk",
+                path.display(),
+                name
+            )
+        });
+        drop(file);
+        fs::remove_file(path).with_path(ErrorKind::ShredRemove, path)?;
+
+        Ok(SecureRemoval {
+            passes: self.passes,
+            warning,
+        })
+    }
+}
+
+/// The result of removing a file with [`remove_file_secure`] or
+/// [`SecureRemoveOptions::remove`].
+#[derive(Debug, Clone)]
+pub struct SecureRemoval {
+    passes: u32,
+    warning: Option<String>,
+}
+
+impl SecureRemoval {
+    /// Returns the number of times the file was overwritten.
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Returns whether the file was removed on a filesystem which is known not
+    /// to overwrite data in place, so that overwriting it was only best-effort.
+    pub fn is_best_effort(&self) -> bool {
+        self.warning.is_some()
+    }
+
+    /// Returns a message explaining why the original contents may still be on
+    /// disk, if the filesystem is known not to overwrite data in place.
+    pub fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+}
+
+fn open(path: &Path) -> io::Result<fs::File> {
+    if !fs::symlink_metadata(path)?.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a regular file",
+        ));
+    }
+    fs::OpenOptions::new().write(true).open(path)
+}
+
+fn overwrite(
+    file: &mut fs::File,
+    len: u64,
+    buf: &mut [u8],
+    random: bool,
+    rng: &mut Rng,
+) -> io::Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    if !random {
+        buf.iter_mut().for_each(|byte| *byte = 0);
+    }
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(buf.len() as u64) as usize;
+        if random {
+            rng.fill(&mut buf[..n]);
+        }
+        file.write_all(&buf[..n])?;
+        remaining -= n as u64;
+    }
+    file.flush()
+}
+
+/// A xorshift generator, seeded by [`random_u64`]. The data only needs to be
+/// unpredictable enough to not be mistaken for the original contents.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Rng {
+        Rng(random_u64() | 1)
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            let bytes = self.0.to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Returns the name of the filesystem containing `file` if it is known not to
+/// overwrite data in place.
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "libc"))]
+fn copy_on_write_filesystem(file: &fs::File) -> Option<&'static str> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: `statfs` is a plain C struct, for which all zeroes is a valid
+    // value.
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    // SAFETY: the file descriptor is valid for the duration of the call.
+    if unsafe { libc::fstatfs(file.as_raw_fd(), &mut stat) } != 0 {
+        return None;
+    }
+    // The type of `f_type` differs between architectures, and it is signed on
+    // some, so compare the low 32 bits which hold the magic number.
+    let magic = stat.f_type as u32;
+
+    match magic {
+        0x9123_683e => Some("Btrfs"),
+        0x2fc1_2fc1 => Some("ZFS"),
+        0xca45_1a4e => Some("bcachefs"),
+        0xf2f5_2010 => Some("F2FS"),
+        0x3434 => Some("NILFS"),
+        _ => None,
+    }
+}
+
+#[cfg(not(all(any(target_os = "linux", target_os = "android"), feature = "libc")))]
+fn copy_on_write_filesystem(_file: &fs::File) -> Option<&'static str> {
+    None
+}
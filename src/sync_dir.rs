@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::copy::{check_not_inside, copy_symlink, Filter};
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Makes the directory `dst` match the directory `src`.
+///
+/// Files which are missing from `dst` or have changed are copied from `src`,
+/// and entries in `dst` which do not exist in `src` are removed, depending on
+/// `options`. `dst` and its parents are created if they are missing.
+///
+/// Errors for individual entries do not stop the rest of the tree from being
+/// synced. They are collected in the returned [`SyncReport`], along with the
+/// actions which were taken, and include the path of the entry like any other
+/// error from fs-err. An error is only returned directly if `src` cannot be
+/// read or `dst` cannot be created, or with the kind
+/// [`io::ErrorKind::InvalidInput`] if `dst` is inside `src`.
+///
+/// # Example
+///
+/// ```no_run
+/// use fs_err::SyncDirOptions;
+///
+/// let report = fs_err::sync_dir("assets", "target/assets", &SyncDirOptions::new())?;
+/// // For example, "3 copied, 1 directory created, 2 removed, 41 unchanged"
+/// println!("{}", report);
+/// for err in report.errors() {
+///     eprintln!("error: {}", err);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn sync_dir<P, Q>(src: P, dst: Q, options: &SyncDirOptions<'_>) -> io::Result<SyncReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let src = src.as_ref();
+    let dst = dst.as_ref();
+    let entries = read_entries(src)?;
+    check_not_inside(src, dst)?;
+    if !options.dry_run {
+        crate::create_dir_all(dst)?;
+    }
+
+    let mut report = SyncReport {
+        copied: Vec::new(),
+        created_dirs: Vec::new(),
+        removed: Vec::new(),
+        unchanged: 0,
+        errors: Vec::new(),
+    };
+    options.sync_contents(src, entries, dst, &mut report);
+    Ok(report)
+}
+
+/// Options for [`sync_dir`].
+pub struct SyncDirOptions<'a> {
+    delete: bool,
+    preserve_metadata: bool,
+    compare_contents: bool,
+    dry_run: bool,
+    filter: Option<Filter<'a>>,
+}
+
+impl<'a> SyncDirOptions<'a> {
+    /// Creates options which remove extraneous entries, preserve metadata,
+    /// detect changed files by their size and modification time, and sync
+    /// every entry.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SyncDirOptions {
+            delete: true,
+            preserve_metadata: true,
+            compare_contents: false,
+            dry_run: false,
+            filter: None,
+        }
+    }
+
+    /// Sets whether entries in the destination which do not exist in the
+    /// source are removed.
+    pub fn delete(&mut self, delete: bool) -> &mut Self {
+        self.delete = delete;
+        self
+    }
+
+    /// Sets whether the permissions of directories and the access and
+    /// modification times of files are copied. The permissions of files are
+    /// always copied, as with [`copy`](crate::copy).
+    ///
+    /// Times are only copied on Rust 1.75 or later. Otherwise, or if this is
+    /// disabled, a file is copied again if the source was modified after the
+    /// destination.
+    pub fn preserve_metadata(&mut self, preserve_metadata: bool) -> &mut Self {
+        self.preserve_metadata = preserve_metadata;
+        self
+    }
+
+    /// Sets whether the contents of files with the same size are compared to
+    /// find out whether they have changed, instead of their modification
+    /// times.
+    pub fn compare_contents(&mut self, compare_contents: bool) -> &mut Self {
+        self.compare_contents = compare_contents;
+        self
+    }
+
+    /// Sets whether to only report the actions which would be taken, without
+    /// changing the destination.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets a callback which is passed the source path of each entry, and
+    /// returns `false` to skip it. Skipping a directory skips all of its
+    /// contents. Entries in the destination which are skipped are never
+    /// removed.
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&Path) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn sync_contents(
+        &self,
+        src: &Path,
+        entries: Vec<(OsString, fs::FileType)>,
+        dst: &Path,
+        report: &mut SyncReport,
+    ) {
+        let mut existing = BTreeMap::new();
+        if is_dir(dst) {
+            match read_entries(dst) {
+                Ok(entries) => existing.extend(entries),
+                Err(err) => return report.errors.push(err),
+            }
+        }
+
+        for (name, file_type) in entries {
+            let existing_type = existing.remove(&name);
+            let src = src.join(&name);
+            if !self.includes(&src) {
+                continue;
+            }
+            let dst = dst.join(&name);
+            if let Err(err) = self.sync_entry(&src, file_type, &dst, existing_type, report) {
+                report.errors.push(err);
+            }
+        }
+
+        if !self.delete {
+            return;
+        }
+        for (name, file_type) in existing {
+            if !self.includes(&src.join(&name)) {
+                continue;
+            }
+            let dst = dst.join(&name);
+            match self.remove(&dst, file_type) {
+                Ok(()) => report.removed.push(dst),
+                Err(err) => report.errors.push(err),
+            }
+        }
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn sync_entry(
+        &self,
+        src: &Path,
+        file_type: fs::FileType,
+        dst: &Path,
+        existing: Option<fs::FileType>,
+        report: &mut SyncReport,
+    ) -> io::Result<()> {
+        if file_type.is_dir() {
+            match existing {
+                Some(existing) if existing.is_dir() => {}
+                Some(existing) => {
+                    self.remove(dst, existing)?;
+                    report.removed.push(dst.to_owned());
+                    self.create_dir(dst, report)?;
+                }
+                None => self.create_dir(dst, report)?,
+            }
+            self.sync_contents(src, read_entries(src)?, dst, report);
+            // The permissions are set afterwards, in case they prevent the
+            // contents from being written.
+            if self.preserve_metadata && !self.dry_run {
+                crate::set_permissions(dst, crate::metadata(src)?.permissions())?;
+            }
+            return Ok(());
+        }
+
+        if let Some(existing) = existing {
+            if existing.is_dir() {
+                self.remove(dst, existing)?;
+                report.removed.push(dst.to_owned());
+            } else if !self.changed(src, file_type, dst, existing)? {
+                report.unchanged += 1;
+                return Ok(());
+            } else if (existing.is_symlink() || file_type.is_symlink()) && !self.dry_run {
+                // Symbolic links cannot be created over an existing file, and
+                // copying a file over a link would write to its target instead.
+                crate::remove_file(dst)?;
+            }
+        }
+
+        if !self.dry_run {
+            if file_type.is_symlink() {
+                copy_symlink(src, dst)?;
+            } else {
+                self.copy_file(src, dst)?;
+            }
+        }
+        report.copied.push(dst.to_owned());
+        Ok(())
+    }
+
+    /// Returns whether the file or link `dst` differs from `src`.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn changed(
+        &self,
+        src: &Path,
+        file_type: fs::FileType,
+        dst: &Path,
+        existing: fs::FileType,
+    ) -> io::Result<bool> {
+        if file_type.is_symlink() || existing.is_symlink() {
+            if file_type.is_symlink() != existing.is_symlink() {
+                return Ok(true);
+            }
+            return Ok(crate::read_link(src)? != crate::read_link(dst)?);
+        }
+
+        let src_metadata = crate::metadata(src)?;
+        let dst_metadata = crate::metadata(dst)?;
+        if src_metadata.len() != dst_metadata.len() {
+            return Ok(true);
+        }
+        if self.compare_contents {
+            return Ok(!same_contents(src, dst)?);
+        }
+        let src_modified = src_metadata
+            .modified()
+            .with_path(ErrorKind::Metadata, src)?;
+        let dst_modified = dst_metadata
+            .modified()
+            .with_path(ErrorKind::Metadata, dst)?;
+        if self.preserves_times() {
+            Ok(src_modified != dst_modified)
+        } else {
+            Ok(src_modified > dst_modified)
+        }
+    }
+
+    fn preserves_times(&self) -> bool {
+        cfg!(rustc_1_75) && self.preserve_metadata
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn copy_file(&self, src: &Path, dst: &Path) -> io::Result<()> {
+        #[cfg(rustc_1_75)]
+        {
+            if self.preserve_metadata {
+                return crate::copy_preserving(src, dst).map(drop);
+            }
+        }
+        crate::copy(src, dst).map(drop)
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn create_dir(&self, dst: &Path, report: &mut SyncReport) -> io::Result<()> {
+        if !self.dry_run {
+            crate::create_dir(dst)?;
+        }
+        report.created_dirs.push(dst.to_owned());
+        Ok(())
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn remove(&self, dst: &Path, file_type: fs::FileType) -> io::Result<()> {
+        if self.dry_run {
+            Ok(())
+        } else if file_type.is_dir() {
+            crate::remove_dir_all(dst)
+        } else {
+            crate::remove_file(dst)
+        }
+    }
+
+    fn includes(&self, src: &Path) -> bool {
+        match &self.filter {
+            Some(filter) => filter(src),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for SyncDirOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncDirOptions")
+            .field("delete", &self.delete)
+            .field("preserve_metadata", &self.preserve_metadata)
+            .field("compare_contents", &self.compare_contents)
+            .field("dry_run", &self.dry_run)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// The actions taken by [`sync_dir`], and the errors for entries which could
+/// not be synced.
+///
+/// Paths are in the destination directory. With
+/// [`dry_run`](SyncDirOptions::dry_run), these are the actions which would
+/// have been taken.
+#[derive(Debug)]
+pub struct SyncReport {
+    copied: Vec<PathBuf>,
+    created_dirs: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    unchanged: usize,
+    errors: Vec<io::Error>,
+}
+
+impl SyncReport {
+    /// Returns the files and symbolic links which were copied because they
+    /// were missing or had changed.
+    pub fn copied(&self) -> &[PathBuf] {
+        &self.copied
+    }
+
+    /// Returns the directories which were created.
+    pub fn created_dirs(&self) -> &[PathBuf] {
+        &self.created_dirs
+    }
+
+    /// Returns the entries which were removed, either because they do not
+    /// exist in the source or because they had to be replaced by an entry of
+    /// a different type. Directories are removed along with their contents.
+    pub fn removed(&self) -> &[PathBuf] {
+        &self.removed
+    }
+
+    /// Returns the number of files and symbolic links which were already up
+    /// to date.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Returns the errors for entries which could not be synced.
+    pub fn errors(&self) -> &[io::Error] {
+        &self.errors
+    }
+
+    /// Returns whether every entry was synced without errors.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize| if count == 1 { "y" } else { "ies" };
+        write!(
+            formatter,
+            "{} copied, {} director{} created, {} removed, {} unchanged",
+            self.copied.len(),
+            self.created_dirs.len(),
+            plural(self.created_dirs.len()),
+            self.removed.len(),
+            self.unchanged
+        )?;
+        if !self.errors.is_empty() {
+            let s = if self.errors.len() == 1 { "" } else { "s" };
+            write!(formatter, ", {} error{}", self.errors.len(), s)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the names and types of the entries in `dir`, sorted by name.
+#[cfg_attr(rustc_1_46, track_caller)]
+fn read_entries(dir: &Path) -> io::Result<Vec<(OsString, fs::FileType)>> {
+    let mut entries = Vec::new();
+    for entry in crate::read_dir(dir)? {
+        let entry = entry?;
+        entries.push((entry.file_name(), entry.file_type()?));
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+fn is_dir(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.is_dir())
+        .unwrap_or(false)
+}
+
+#[cfg_attr(rustc_1_46, track_caller)]
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut a = crate::File::open(a)?;
+    let mut b = crate::File::open(b)?;
+    let mut a_buf = [0; 8 * 1024];
+    let mut b_buf = [0; 8 * 1024];
+    loop {
+        let a_len = read_full(&mut a, &mut a_buf)?;
+        let b_len = read_full(&mut b, &mut b_buf)?;
+        if a_buf[..a_len] != b_buf[..b_len] {
+            return Ok(false);
+        }
+        if a_len == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Reads until `buf` is full or the end of the file is reached.
+fn read_full(file: &mut crate::File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
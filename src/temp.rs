@@ -0,0 +1,338 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{report_drop_error, ErrorKind, ResultExt, SourceDestErrorKind};
+use crate::random::random_u64;
+use crate::File;
+
+/// Creates a new temporary directory in the system's temporary directory.
+///
+/// See [`TempDir`] for how the directory is removed.
+///
+/// # Example
+///
+/// ```no_run
+/// let dir = fs_err::tempdir()?;
+/// fs_err::write(dir.path().join("input.txt"), "hello")?;
+/// dir.close()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn tempdir() -> io::Result<TempDir> {
+    TempDir::new()
+}
+
+/// A directory which is removed, along with its contents, when dropped.
+///
+/// Errors from removing the directory when it is dropped are passed to the
+/// handler set with [`set_drop_error_handler`](crate::set_drop_error_handler),
+/// if any. Use [`close`](TempDir::close) to remove the directory and handle the
+/// error directly instead.
+#[derive(Debug)]
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    /// Creates a new temporary directory in the system's temporary directory,
+    /// as returned by [`std::env::temp_dir`].
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn new() -> io::Result<TempDir> {
+        TempDir::new_in(std::env::temp_dir())
+    }
+
+    /// Creates a new temporary directory inside `dir`.
+    ///
+    /// The directory is given a random name which does not already exist. On
+    /// Unix, only the current user is allowed to access it.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<TempDir> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name());
+            match create_private_dir(&path) {
+                Ok(()) => return Ok(TempDir { path: Some(path) }),
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err).with_path(ErrorKind::CreateTempDir, &path),
+            }
+        }
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        self.path
+            .as_ref()
+            .expect("temporary directory already removed")
+    }
+
+    /// Keeps the directory, instead of removing it when dropped, and returns
+    /// its path.
+    pub fn keep(mut self) -> PathBuf {
+        self.path
+            .take()
+            .expect("temporary directory already removed")
+    }
+
+    /// Removes the directory and its contents, returning any error.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn close(mut self) -> io::Result<()> {
+        let path = self
+            .path
+            .take()
+            .expect("temporary directory already removed");
+        fs::remove_dir_all(&path).with_path(ErrorKind::RemoveTempDir, &path)
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if let Err(err) = fs::remove_dir_all(&path).with_path(ErrorKind::RemoveTempDir, &path) {
+                report_drop_error(err);
+            }
+        }
+    }
+}
+
+/// A file which is removed when dropped, unless it is persisted.
+///
+/// Errors from removing the file when it is dropped are passed to the handler
+/// set with [`set_drop_error_handler`](crate::set_drop_error_handler), if any.
+/// Use [`close`](NamedTempFile::close) to remove the file and handle the error
+/// directly instead.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::io::Write;
+///
+/// let mut temp = fs_err::NamedTempFile::new_in("out")?;
+/// temp.write_all(b"generated output")?;
+/// temp.as_file().sync_all()?;
+/// temp.persist("out/result.bin")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct NamedTempFile {
+    file: Option<File>,
+}
+
+impl NamedTempFile {
+    /// Creates a new temporary file in the system's temporary directory, as
+    /// returned by [`std::env::temp_dir`].
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn new() -> io::Result<NamedTempFile> {
+        NamedTempFile::new_in(std::env::temp_dir())
+    }
+
+    /// Creates a new temporary file inside `dir`, opened for reading and
+    /// writing.
+    ///
+    /// The file is given a random name which does not already exist. On Unix,
+    /// only the current user is allowed to access it. To
+    /// [`persist`](NamedTempFile::persist) the file by renaming it, `dir` must
+    /// be on the same filesystem as the destination.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<NamedTempFile> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name());
+            match create_private_file(&path) {
+                Ok(file) => {
+                    return Ok(NamedTempFile {
+                        file: Some(File::from_parts(file, path)),
+                    })
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err).with_path(ErrorKind::CreateTempFile, &path),
+            }
+        }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        self.as_file().path()
+    }
+
+    /// Returns a reference to the open file.
+    pub fn as_file(&self) -> &File {
+        self.file.as_ref().expect("temporary file already removed")
+    }
+
+    /// Returns a mutable reference to the open file.
+    pub fn as_file_mut(&mut self) -> &mut File {
+        self.file.as_mut().expect("temporary file already removed")
+    }
+
+    /// Renames the file to `dest`, replacing any file which already exists
+    /// there, and returns it so that it is no longer removed when dropped.
+    ///
+    /// The returned [`File`] reports errors with the path `dest`. The contents
+    /// are not synced to disk first, so call [`File::sync_all`] beforehand, and
+    /// [`fsync_parent_dir`](crate::fsync_parent_dir) afterwards, if the new file
+    /// must survive a crash.
+    ///
+    /// If the file cannot be renamed, the error includes both paths, and the
+    /// temporary file can be recovered from it to try again.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn persist<P: AsRef<Path>>(mut self, dest: P) -> Result<File, PersistError> {
+        let dest = dest.as_ref();
+        if let Err(error) = fs::rename(self.path(), dest).with_paths(
+            SourceDestErrorKind::Persist,
+            self.path(),
+            dest,
+        ) {
+            return Err(PersistError { error, file: self });
+        }
+
+        let (file, _) = self
+            .file
+            .take()
+            .expect("temporary file already removed")
+            .into_parts();
+        Ok(File::from_parts(file, dest))
+    }
+
+    /// Keeps the file at its temporary path, instead of removing it when
+    /// dropped, and returns it.
+    pub fn keep(mut self) -> File {
+        self.file.take().expect("temporary file already removed")
+    }
+
+    /// Closes and removes the file, returning any error.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    pub fn close(mut self) -> io::Result<()> {
+        let (file, path) = self
+            .file
+            .take()
+            .expect("temporary file already removed")
+            .into_parts();
+        drop(file);
+        fs::remove_file(&path).with_path(ErrorKind::RemoveTempFile, &path)
+    }
+}
+
+impl AsRef<Path> for NamedTempFile {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Read for NamedTempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_file_mut().read(buf)
+    }
+}
+
+impl Write for NamedTempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.as_file_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.as_file_mut().flush()
+    }
+}
+
+impl Seek for NamedTempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.as_file_mut().seek(pos)
+    }
+}
+
+impl Drop for NamedTempFile {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let (file, path) = file.into_parts();
+            // The file must be closed before it can be removed on Windows.
+            drop(file);
+            if let Err(err) = fs::remove_file(&path).with_path(ErrorKind::RemoveTempFile, &path) {
+                report_drop_error(err);
+            }
+        }
+    }
+}
+
+/// The error returned when [`NamedTempFile::persist`] fails, which contains the
+/// temporary file so that it is not removed.
+///
+/// This converts into an [`io::Error`] with `?`, in which case the temporary
+/// file is removed.
+#[derive(Debug)]
+pub struct PersistError {
+    error: io::Error,
+    file: NamedTempFile,
+}
+
+impl PersistError {
+    /// Returns the error which caused the file not to be persisted.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the error, removing the temporary file.
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+
+    /// Returns the temporary file which could not be persisted.
+    pub fn into_file(self) -> NamedTempFile {
+        self.file
+    }
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, formatter)
+    }
+}
+
+impl StdError for PersistError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
+}
+
+impl From<PersistError> for io::Error {
+    fn from(err: PersistError) -> io::Error {
+        err.error
+    }
+}
+
+/// Returns a random name for a temporary file or directory.
+pub(crate) fn temp_name() -> String {
+    format!(".tmp{:016x}", random_u64())
+}
+
+#[cfg(unix)]
+fn create_private_dir(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    fs::DirBuilder::new().mode(0o700).create(path)
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(path: &Path) -> io::Result<()> {
+    fs::create_dir(path)
+}
+
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+    options.open(path)
+}
@@ -46,7 +46,13 @@ impl DirBuilder {
         self.inner
             .create(path)
             .await
-            .map_err(|err| Error::build(err, ErrorKind::CreateDir, path))
+            .map_err(|err| Error::build_async(err, ErrorKind::CreateDir, path))
+    }
+}
+
+impl From<tokio::fs::DirBuilder> for DirBuilder {
+    fn from(inner: tokio::fs::DirBuilder) -> Self {
+        DirBuilder { inner }
     }
 }
 
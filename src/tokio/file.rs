@@ -4,6 +4,8 @@ use std::io;
 use std::io::{IoSlice, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+use std::sync::{Arc, Mutex, PoisonError};
 use std::task::{ready, Context, Poll};
 use tokio::fs;
 use tokio::fs::File as TokioFile;
@@ -26,7 +28,7 @@ impl File {
         let path = path.into();
         let f = TokioFile::open(&path)
             .await
-            .map_err(|err| Error::build(err, ErrorKind::OpenFile, &path))?;
+            .map_err(|err| Error::build_async(err, ErrorKind::OpenFile, &path))?;
         Ok(File::from_parts(f, path))
     }
 
@@ -37,7 +39,25 @@ impl File {
         let path = path.into();
         match TokioFile::create(&path).await {
             Ok(f) => Ok(File::from_parts(f, path)),
-            Err(err) => Err(Error::build(err, ErrorKind::CreateFile, &path)),
+            Err(err) => Err(Error::build_async(err, ErrorKind::CreateFile, &path)),
+        }
+    }
+
+    /// Opens a file in read-write mode, failing if the file already exists.
+    ///
+    /// Equivalent to [`tokio::fs::File::create_new`], which requires a newer
+    /// version of tokio.
+    pub async fn create_new(path: impl Into<PathBuf>) -> io::Result<File> {
+        let path = path.into();
+        match fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(f) => Ok(File::from_parts(f, path)),
+            Err(err) => Err(Error::build_async(err, ErrorKind::CreateNewFile, &path)),
         }
     }
 
@@ -113,6 +133,7 @@ impl File {
     /// Tries to immediately destructure `File` into a [`crate::File`].
     ///
     /// Wrapper for [`tokio::fs::File::try_into_std`].
+    #[allow(clippy::result_large_err)]
     pub fn try_into_std(self) -> Result<crate::File, File> {
         match self.tokio.try_into_std() {
             Ok(f) => Ok(crate::File::from_parts(f, self.path)),
@@ -131,6 +152,170 @@ impl File {
     }
 }
 
+/// File locking, which waits for locks on a blocking thread so that the
+/// executor is not blocked.
+///
+/// The lock is taken on a duplicate of the file handle, which shares locks with
+/// the original, so it applies to this `File` and is released by
+/// [`unlock`](File::unlock) or by closing it.
+///
+/// If the future returned by [`lock`](File::lock),
+/// [`lock_shared`](File::lock_shared) or
+/// [`lock_with_retry`](File::lock_with_retry) is dropped before it completes,
+/// for example because it timed out, the blocking thread keeps waiting and
+/// releases the lock as soon as it is acquired, so the file is not left locked.
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-rt")))]
+impl File {
+    /// Acquires an exclusive lock on the file, waiting until it can be
+    /// acquired.
+    ///
+    /// Async equivalent of [`fs_err::File::lock`](crate::File::lock).
+    pub async fn lock(&self) -> io::Result<()> {
+        let file = self.clone_std(ErrorKind::Lock).await?;
+        lock_blocking(file, |file| file.lock()).await
+    }
+
+    /// Acquires a shared lock on the file, waiting until it can be acquired.
+    ///
+    /// Async equivalent of [`fs_err::File::lock_shared`](crate::File::lock_shared).
+    pub async fn lock_shared(&self) -> io::Result<()> {
+        let file = self.clone_std(ErrorKind::LockShared).await?;
+        lock_blocking(file, |file| file.lock_shared()).await
+    }
+
+    /// Tries to acquire an exclusive lock on the file, without waiting.
+    ///
+    /// Async equivalent of [`fs_err::File::try_lock`](crate::File::try_lock).
+    pub async fn try_lock(&self) -> Result<(), std::fs::TryLockError> {
+        self.clone_std(ErrorKind::Lock)
+            .await
+            .map_err(std::fs::TryLockError::Error)?
+            .try_lock()
+    }
+
+    /// Tries to acquire a shared lock on the file, without waiting.
+    ///
+    /// Async equivalent of [`fs_err::File::try_lock_shared`](crate::File::try_lock_shared).
+    pub async fn try_lock_shared(&self) -> Result<(), std::fs::TryLockError> {
+        self.clone_std(ErrorKind::LockShared)
+            .await
+            .map_err(std::fs::TryLockError::Error)?
+            .try_lock_shared()
+    }
+
+    /// Acquires an exclusive lock on the file, retrying according to `policy`
+    /// while the lock is held by someone else.
+    ///
+    /// Async equivalent of [`fs_err::File::lock_with_retry`](crate::File::lock_with_retry).
+    pub async fn lock_with_retry(&self, policy: &crate::LockRetryPolicy) -> io::Result<()> {
+        let file = self.clone_std(ErrorKind::Lock).await?;
+        let policy = *policy;
+        lock_blocking(file, move |file| file.lock_with_retry(&policy)).await
+    }
+
+    /// Releases all locks on the file.
+    ///
+    /// Async equivalent of [`fs_err::File::unlock`](crate::File::unlock).
+    pub async fn unlock(&self) -> io::Result<()> {
+        let file = self.clone_std(ErrorKind::Unlock).await?;
+        blocking(move || file.unlock()).await
+    }
+
+    /// Duplicates the file handle as a synchronous [`crate::File`].
+    async fn clone_std(&self, kind: ErrorKind) -> io::Result<crate::File> {
+        let file = self
+            .tokio
+            .try_clone()
+            .await
+            .map_err(|err| self.error(err, kind))?;
+        Ok(crate::File::from_parts(
+            file.into_std().await,
+            self.path.clone(),
+        ))
+    }
+}
+
+/// Runs `f` on a blocking thread, resuming any panic.
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+async fn blocking<F>(f: F) -> io::Result<()>
+where
+    F: FnOnce() -> io::Result<()> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        // The runtime is shutting down.
+        Err(_) => Err(io::ErrorKind::Interrupted.into()),
+    }
+}
+
+/// Runs `lock` on `file` on a blocking thread, releasing the lock if the
+/// future is dropped before it completes.
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+async fn lock_blocking<F>(file: crate::File, lock: F) -> io::Result<()>
+where
+    F: FnOnce(&crate::File) -> io::Result<()> + Send + 'static,
+{
+    let mut guard = UnlockOnCancel {
+        file: Arc::new(file),
+        state: Arc::new(Mutex::new(LockState::Waiting)),
+        finished: false,
+    };
+    let (file, state) = (guard.file.clone(), guard.state.clone());
+    let result = blocking(move || {
+        lock(&file)?;
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            LockState::Cancelled => file.unlock(),
+            _ => {
+                *state = LockState::Locked;
+                Ok(())
+            }
+        }
+    })
+    .await;
+    guard.finished = true;
+    result
+}
+
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+enum LockState {
+    /// The blocking thread is waiting for the lock.
+    Waiting,
+    /// The lock has been acquired.
+    Locked,
+    /// The future was dropped while waiting, so the blocking thread must
+    /// release the lock once it has been acquired.
+    Cancelled,
+}
+
+/// Releases a lock acquired by [`lock_blocking`] if its future is dropped
+/// before returning the result.
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+struct UnlockOnCancel {
+    file: Arc<crate::File>,
+    state: Arc<Mutex<LockState>>,
+    finished: bool,
+}
+
+#[cfg(all(rustc_1_89, feature = "tokio-rt"))]
+impl Drop for UnlockOnCancel {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            LockState::Waiting => *state = LockState::Cancelled,
+            LockState::Locked => {
+                let _ = self.file.unlock();
+            }
+            LockState::Cancelled => {}
+        }
+    }
+}
+
 /// Methods added by fs-err that are not available on
 /// [`tokio::fs::File`].
 impl File {
@@ -167,7 +352,7 @@ impl File {
 
     /// Wrap the error in information specific to this `File` object.
     fn error(&self, source: io::Error, kind: ErrorKind) -> io::Error {
-        Error::build(source, kind, &self.path)
+        Error::build_async(source, kind, &self.path)
     }
 }
 
@@ -178,6 +363,17 @@ impl From<crate::File> for File {
     }
 }
 
+/// Converts back into a [`crate::File`] with the same path, like
+/// [`File::try_into_std`]. This fails, returning the file unchanged, while an
+/// operation is still in progress; use [`File::into_std`] to wait for it.
+impl std::convert::TryFrom<File> for crate::File {
+    type Error = File;
+
+    fn try_from(f: File) -> Result<Self, File> {
+        f.try_into_std()
+    }
+}
+
 impl From<File> for TokioFile {
     fn from(f: File) -> Self {
         f.into_parts().0
@@ -198,6 +394,28 @@ impl std::os::windows::io::AsRawHandle for File {
     }
 }
 
+#[cfg(all(unix, rustc_1_63))]
+impl std::os::unix::io::AsFd for File {
+    fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+        use std::os::unix::io::{AsRawFd, BorrowedFd};
+
+        // SAFETY: the file descriptor is owned by `self`, so it stays open for
+        // the lifetime of the borrow.
+        unsafe { BorrowedFd::borrow_raw(self.tokio.as_raw_fd()) }
+    }
+}
+
+#[cfg(all(windows, rustc_1_63))]
+impl std::os::windows::io::AsHandle for File {
+    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        use std::os::windows::io::{AsRawHandle, BorrowedHandle};
+
+        // SAFETY: the handle is owned by `self`, so it stays open for the
+        // lifetime of the borrow.
+        unsafe { BorrowedHandle::borrow_raw(self.tokio.as_raw_handle()) }
+    }
+}
+
 impl AsyncRead for File {
     fn poll_read(
         mut self: Pin<&mut Self>,
@@ -234,7 +452,7 @@ impl AsyncWrite for File {
     ) -> Poll<io::Result<usize>> {
         Poll::Ready(
             ready!(Pin::new(&mut self.tokio).poll_write(cx, buf))
-                .map_err(|err| self.error(err, ErrorKind::Write)),
+                .map_err(|err| Error::build_async(err, ErrorKind::Write, &self.path)),
         )
     }
 
@@ -259,7 +477,7 @@ impl AsyncWrite for File {
     ) -> Poll<io::Result<usize>> {
         Poll::Ready(
             ready!(Pin::new(&mut self.tokio).poll_write_vectored(cx, bufs))
-                .map_err(|err| self.error(err, ErrorKind::Write)),
+                .map_err(|err| Error::build_async(err, ErrorKind::Write, &self.path)),
         )
     }
 
@@ -0,0 +1,152 @@
+use crate::errors::{Error, ErrorKind};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::fs;
+use tokio::io::{AsyncRead, ReadBuf};
+
+const CHUNK: usize = 8 * 1024;
+
+/// Returns a stream over the lines of a file.
+///
+/// This is the async equivalent of [`fs_err::read_lines`](crate::read_lines).
+/// Lines are split on `\n` or `\r\n`, which are not included in the returned
+/// strings. Errors, including a line which is not valid UTF-8, contain the path
+/// of the file and the line number.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// let mut lines = fs_err::tokio::read_lines("app.log").await?;
+/// while let Some(line) = lines.next_line().await? {
+///     println!("{}", line);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub async fn read_lines(path: impl Into<PathBuf>) -> io::Result<Lines> {
+    let path = path.into();
+    let file = match fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(err) => return Err(Error::build_async(err, ErrorKind::OpenFile, path)),
+    };
+    Ok(Lines {
+        file,
+        path,
+        line: 0,
+        buf: Vec::new(),
+        start: 0,
+        chunk: vec![0; CHUNK].into_boxed_slice(),
+        eof: false,
+    })
+}
+
+/// A stream over the lines of a file, which includes the path and line number
+/// in errors.
+///
+/// This struct is created via [`read_lines`].
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct Lines {
+    file: fs::File,
+    path: PathBuf,
+    line: u64,
+    /// Data which has been read but not yet returned, starting at `start`.
+    buf: Vec<u8>,
+    start: usize,
+    chunk: Box<[u8]>,
+    eof: bool,
+}
+
+impl Lines {
+    /// Returns the path of the file being read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the next line of the file, or `None` at the end of the file.
+    ///
+    /// This method is cancel safe: if it is cancelled, no line is skipped and
+    /// the next call continues from the same line.
+    pub async fn next_line(&mut self) -> io::Result<Option<String>> {
+        let kind = ErrorKind::ReadLine {
+            line: self.line + 1,
+        };
+
+        let mut searched = self.start;
+        let end = loop {
+            if let Some(i) = self.buf[searched..].iter().position(|&b| b == b'\n') {
+                break searched + i + 1;
+            }
+            if self.eof {
+                if self.start == self.buf.len() {
+                    return Ok(None);
+                }
+                break self.buf.len();
+            }
+
+            // Discard the lines which have already been returned.
+            self.buf.drain(..self.start);
+            self.start = 0;
+            searched = self.buf.len();
+            if let Err(err) = self.fill().await {
+                self.line += 1;
+                return Err(Error::build_async(err, kind, &self.path));
+            }
+        };
+
+        let mut line = self.buf[self.start..end].to_vec();
+        self.start = end;
+        self.line += 1;
+        if line.ends_with(b"\n") {
+            line.pop();
+            if line.ends_with(b"\r") {
+                line.pop();
+            }
+        }
+
+        String::from_utf8(line).map(Some).map_err(|err| {
+            let source = io::Error::new(io::ErrorKind::InvalidData, err);
+            Error::build_async(source, kind, &self.path)
+        })
+    }
+
+    /// Reads more data from the file into the buffer.
+    async fn fill(&mut self) -> io::Result<()> {
+        let n = ReadChunk {
+            file: &mut self.file,
+            buf: &mut self.chunk,
+        }
+        .await?;
+        if n == 0 {
+            self.eof = true;
+        }
+        self.buf.extend_from_slice(&self.chunk[..n]);
+        Ok(())
+    }
+}
+
+/// A future which reads once from a file into a buffer.
+struct ReadChunk<'a> {
+    file: &'a mut fs::File,
+    buf: &'a mut [u8],
+}
+
+impl Future for ReadChunk<'_> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let mut buf = ReadBuf::new(this.buf);
+        match Pin::new(&mut *this.file).poll_read(cx, &mut buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(buf.filled().len())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -6,11 +6,25 @@ use std::path::{Path, PathBuf};
 use tokio::io;
 mod dir_builder;
 mod file;
+mod lines;
 mod open_options;
+#[cfg(rustc_1_75)]
+mod path;
 mod read_dir;
+mod temp;
+#[cfg(feature = "tokio-time")]
+mod timeout;
+mod walk_dir;
 
+pub use self::lines::{read_lines, Lines};
 pub use self::open_options::OpenOptions;
+#[cfg(rustc_1_75)]
+pub use self::path::AsyncPathExt;
 pub use self::read_dir::{read_dir, DirEntry, ReadDir};
+pub use self::temp::{tempdir, NamedTempFile, PersistError, TempDir};
+#[cfg(feature = "tokio-time")]
+pub use self::timeout::{with_timeout, Timeout};
+pub use self::walk_dir::{walk_dir, WalkDir, WalkDirOptions};
 pub use dir_builder::DirBuilder;
 pub use file::File;
 
@@ -23,7 +37,7 @@ pub async fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
     let path = path.as_ref();
     tokio::fs::canonicalize(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::Canonicalize, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::Canonicalize, path))
 }
 
 /// Copies the contents of one file to another. This function will also copy the permission bits
@@ -36,7 +50,7 @@ pub async fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<u64, i
     let (from, to) = (from.as_ref(), to.as_ref());
     tokio::fs::copy(from, to)
         .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Copy, from, to))
+        .map_err(|err| SourceDestError::build_async(err, SourceDestErrorKind::Copy, from, to))
 }
 
 /// Creates a new, empty directory at the provided path.
@@ -47,7 +61,7 @@ pub async fn create_dir(path: impl AsRef<Path>) -> io::Result<()> {
     let path = path.as_ref();
     tokio::fs::create_dir(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::CreateDir, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::CreateDir, path))
 }
 
 /// Recursively creates a directory and all of its parent components if they
@@ -59,7 +73,7 @@ pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
     let path = path.as_ref();
     tokio::fs::create_dir_all(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::CreateDir, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::CreateDir, path))
 }
 
 /// Creates a new hard link on the filesystem.
@@ -70,7 +84,7 @@ pub async fn hard_link(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Resu
     let (src, dst) = (src.as_ref(), dst.as_ref());
     tokio::fs::hard_link(src, dst)
         .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::HardLink, src, dst))
+        .map_err(|err| SourceDestError::build_async(err, SourceDestErrorKind::HardLink, src, dst))
 }
 
 /// Given a path, queries the file system to get information about a file,
@@ -82,7 +96,7 @@ pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
     let path = path.as_ref();
     tokio::fs::metadata(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::Metadata, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::Metadata, path))
 }
 
 /// Reads the entire contents of a file into a bytes vector.
@@ -93,7 +107,7 @@ pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
     let path = path.as_ref();
     tokio::fs::read(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::Read, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::Read, path))
 }
 
 /// Reads a symbolic link, returning the file that the link points to.
@@ -104,7 +118,7 @@ pub async fn read_link(path: impl AsRef<Path>) -> io::Result<PathBuf> {
     let path = path.as_ref();
     tokio::fs::read_link(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::ReadLink, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::ReadLink, path))
 }
 
 /// Creates a future which will open a file for reading and read the entire
@@ -116,7 +130,7 @@ pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
     let path = path.as_ref();
     tokio::fs::read_to_string(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::Read, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::Read, path))
 }
 
 /// Removes an existing, empty directory.
@@ -127,7 +141,7 @@ pub async fn remove_dir(path: impl AsRef<Path>) -> io::Result<()> {
     let path = path.as_ref();
     tokio::fs::remove_dir(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::RemoveDir, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::RemoveDir, path))
 }
 
 /// Removes a directory at this path, after removing all its contents. Use carefully!
@@ -138,7 +152,7 @@ pub async fn remove_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
     let path = path.as_ref();
     tokio::fs::remove_dir_all(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::RemoveDir, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::RemoveDir, path))
 }
 
 /// Removes a file from the filesystem.
@@ -149,7 +163,7 @@ pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
     let path = path.as_ref();
     tokio::fs::remove_file(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::RemoveFile, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::RemoveFile, path))
 }
 
 /// Renames a file or directory to a new name, replacing the original file if
@@ -161,7 +175,7 @@ pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<
     let (from, to) = (from.as_ref(), to.as_ref());
     tokio::fs::rename(from, to)
         .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Rename, from, to))
+        .map_err(|err| SourceDestError::build_async(err, SourceDestErrorKind::Rename, from, to))
 }
 
 /// Changes the permissions found on a file or a directory.
@@ -172,7 +186,7 @@ pub async fn set_permissions(path: impl AsRef<Path>, perm: Permissions) -> io::R
     let path = path.as_ref();
     tokio::fs::set_permissions(path, perm)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::SetPermissions, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::SetPermissions, path))
 }
 
 /// Queries the file system metadata for a path.
@@ -183,7 +197,7 @@ pub async fn symlink_metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
     let path = path.as_ref();
     tokio::fs::symlink_metadata(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::SymlinkMetadata, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::SymlinkMetadata, path))
 }
 
 /// Creates a new symbolic link on the filesystem.
@@ -195,7 +209,7 @@ pub async fn symlink(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result
     let (src, dst) = (src.as_ref(), dst.as_ref());
     tokio::fs::symlink(src, dst)
         .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::Symlink, src, dst))
+        .map_err(|err| SourceDestError::build_async(err, SourceDestErrorKind::Symlink, src, dst))
 }
 
 /// Creates a new directory symlink on the filesystem.
@@ -207,7 +221,7 @@ pub async fn symlink_dir(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Re
     let (src, dst) = (src.as_ref(), dst.as_ref());
     tokio::fs::symlink_dir(src, dst)
         .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::SymlinkDir, src, dst))
+        .map_err(|err| SourceDestError::build_async(err, SourceDestErrorKind::SymlinkDir, src, dst))
 }
 
 /// Creates a new file symbolic link on the filesystem.
@@ -217,9 +231,9 @@ pub async fn symlink_dir(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Re
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 pub async fn symlink_file(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
     let (src, dst) = (src.as_ref(), dst.as_ref());
-    tokio::fs::symlink_file(src, dst)
-        .await
-        .map_err(|err| SourceDestError::build(err, SourceDestErrorKind::SymlinkFile, src, dst))
+    tokio::fs::symlink_file(src, dst).await.map_err(|err| {
+        SourceDestError::build_async(err, SourceDestErrorKind::SymlinkFile, src, dst)
+    })
 }
 
 /// Creates a future that will open a file for writing and write the entire
@@ -231,5 +245,5 @@ pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Re
     let (path, contents) = (path.as_ref(), contents.as_ref());
     tokio::fs::write(path, contents)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::Write, path))
+        .map_err(|err| Error::build_async(err, ErrorKind::Write, path))
 }
@@ -91,7 +91,22 @@ impl OpenOptions {
             .open(path)
             .await
             .map(|f| File::from_parts(f, path))
-            .map_err(|err| Error::build(err, ErrorKind::OpenFile, path))
+            .map_err(|err| Error::build_async(err, ErrorKind::OpenFile, path))
+    }
+
+    /// Returns a reference to the underlying [`tokio::fs::OpenOptions`].
+    ///
+    /// Note that calling `open()` on this reference will NOT give you the improved errors from fs-err.
+    pub fn options(&self) -> &TokioOpenOptions {
+        &self.tokio
+    }
+
+    /// Returns a mutable reference to the underlying [`tokio::fs::OpenOptions`].
+    ///
+    /// This allows you to change settings that don't yet have wrappers in fs-err.
+    /// Note that calling `open()` on this reference will NOT give you the improved errors from fs-err.
+    pub fn options_mut(&mut self) -> &mut TokioOpenOptions {
+        &mut self.tokio
     }
 }
 
@@ -114,6 +129,52 @@ impl OpenOptions {
     }
 }
 
+#[cfg(windows)]
+impl OpenOptions {
+    /// Overrides the `dwDesiredAccess` argument to the call to `CreateFile`
+    /// with the specified value.
+    ///
+    /// Wrapper for [`tokio::fs::OpenOptions::access_mode`].
+    pub fn access_mode(&mut self, access: u32) -> &mut OpenOptions {
+        self.tokio.access_mode(access);
+        self
+    }
+
+    /// Overrides the `dwShareMode` argument to the call to `CreateFile` with
+    /// the specified value.
+    ///
+    /// Wrapper for [`tokio::fs::OpenOptions::share_mode`].
+    pub fn share_mode(&mut self, share: u32) -> &mut OpenOptions {
+        self.tokio.share_mode(share);
+        self
+    }
+
+    /// Sets extra flags for the `dwFileFlags` argument to the call to
+    /// `CreateFile2`.
+    ///
+    /// Wrapper for [`tokio::fs::OpenOptions::custom_flags`].
+    pub fn custom_flags(&mut self, flags: u32) -> &mut OpenOptions {
+        self.tokio.custom_flags(flags);
+        self
+    }
+
+    /// Sets the `dwFileAttributes` argument to the call to `CreateFile2`.
+    ///
+    /// Wrapper for [`tokio::fs::OpenOptions::attributes`].
+    pub fn attributes(&mut self, attributes: u32) -> &mut OpenOptions {
+        self.tokio.attributes(attributes);
+        self
+    }
+
+    /// Sets the `dwSecurityQosFlags` argument to the call to `CreateFile2`.
+    ///
+    /// Wrapper for [`tokio::fs::OpenOptions::security_qos_flags`].
+    pub fn security_qos_flags(&mut self, flags: u32) -> &mut OpenOptions {
+        self.tokio.security_qos_flags(flags);
+        self
+    }
+}
+
 impl From<std::fs::OpenOptions> for OpenOptions {
     fn from(std: std::fs::OpenOptions) -> Self {
         OpenOptions { tokio: std.into() }
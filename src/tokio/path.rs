@@ -0,0 +1,208 @@
+use crate::tokio::{File, ReadDir};
+use std::fs::Metadata;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Defines aliases on [`Path`] for the `fs_err::tokio` functions.
+///
+/// This is the async equivalent of [`fs_err::PathExt`](crate::PathExt). It
+/// requires Rust 1.75 or later.
+///
+/// This trait is sealed and can not be implemented by other crates.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use fs_err::tokio::AsyncPathExt;
+/// use std::path::Path;
+///
+/// let path = Path::new("config.toml");
+/// if path.fs_err_metadata().await?.len() > 0 {
+///     println!("{}", path.fs_err_read_to_string().await?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+//
+// Because no one else can implement it, we can add methods backwards-compatibly.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub trait AsyncPathExt: crate::Sealed {
+    /// Given a path, queries the file system to get information about a file,
+    /// directory, etc.
+    ///
+    /// Wrapper for [`crate::tokio::metadata`].
+    fn fs_err_metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send + '_;
+    /// Queries the metadata about a file without following symlinks.
+    ///
+    /// Wrapper for [`crate::tokio::symlink_metadata`].
+    fn fs_err_symlink_metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send + '_;
+    /// Returns the canonical, absolute form of a path with all intermediate
+    /// components normalized and symbolic links resolved.
+    ///
+    /// Wrapper for [`crate::tokio::canonicalize`].
+    fn fs_err_canonicalize(&self) -> impl Future<Output = io::Result<PathBuf>> + Send + '_;
+    /// Reads a symbolic link, returning the file that the link points to.
+    ///
+    /// Wrapper for [`crate::tokio::read_link`].
+    fn fs_err_read_link(&self) -> impl Future<Output = io::Result<PathBuf>> + Send + '_;
+    /// Returns a stream over the entries within a directory.
+    ///
+    /// Wrapper for [`crate::tokio::read_dir`].
+    fn fs_err_read_dir(&self) -> impl Future<Output = io::Result<ReadDir>> + Send + '_;
+    /// Reads the entire contents of a file into a bytes vector.
+    ///
+    /// Wrapper for [`crate::tokio::read`].
+    fn fs_err_read(&self) -> impl Future<Output = io::Result<Vec<u8>>> + Send + '_;
+    /// Reads the entire contents of a file into a string.
+    ///
+    /// Wrapper for [`crate::tokio::read_to_string`].
+    fn fs_err_read_to_string(&self) -> impl Future<Output = io::Result<String>> + Send + '_;
+    /// Writes a slice as the entire contents of a file.
+    ///
+    /// Wrapper for [`crate::tokio::write`].
+    fn fs_err_write<'a>(
+        &'a self,
+        contents: &'a [u8],
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a;
+    /// Opens a file in read-only mode.
+    ///
+    /// Wrapper for [`crate::tokio::File::open`].
+    fn fs_err_open(&self) -> impl Future<Output = io::Result<File>> + Send + '_;
+    /// Opens a file in write-only mode, creating it if it does not exist and
+    /// truncating it if it does.
+    ///
+    /// Wrapper for [`crate::tokio::File::create`].
+    fn fs_err_create(&self) -> impl Future<Output = io::Result<File>> + Send + '_;
+    /// Removes a file from the filesystem.
+    ///
+    /// Wrapper for [`crate::tokio::remove_file`].
+    fn fs_err_remove_file(&self) -> impl Future<Output = io::Result<()>> + Send + '_;
+    /// Removes an empty directory.
+    ///
+    /// Wrapper for [`crate::tokio::remove_dir`].
+    fn fs_err_remove_dir(&self) -> impl Future<Output = io::Result<()>> + Send + '_;
+    /// Removes a directory at this path, after removing all its contents.
+    ///
+    /// Wrapper for [`crate::tokio::remove_dir_all`].
+    fn fs_err_remove_dir_all(&self) -> impl Future<Output = io::Result<()>> + Send + '_;
+    /// Creates a new, empty directory at the provided path.
+    ///
+    /// Wrapper for [`crate::tokio::create_dir`].
+    fn fs_err_create_dir(&self) -> impl Future<Output = io::Result<()>> + Send + '_;
+    /// Recursively creates a directory and all of its parent components if
+    /// they are missing.
+    ///
+    /// Wrapper for [`crate::tokio::create_dir_all`].
+    fn fs_err_create_dir_all(&self) -> impl Future<Output = io::Result<()>> + Send + '_;
+    /// Copies the contents of this file to `dest`, returning the number of
+    /// bytes copied.
+    ///
+    /// Wrapper for [`crate::tokio::copy`].
+    fn fs_err_copy_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<u64>> + Send + 'a;
+    /// Renames this file or directory to `dest`, replacing the original file
+    /// if `dest` already exists.
+    ///
+    /// Wrapper for [`crate::tokio::rename`].
+    fn fs_err_rename_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a;
+    /// Creates a new hard link at `dest` to this file.
+    ///
+    /// Wrapper for [`crate::tokio::hard_link`].
+    fn fs_err_hard_link_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a;
+}
+
+impl AsyncPathExt for Path {
+    fn fs_err_metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send + '_ {
+        crate::tokio::metadata(self)
+    }
+
+    fn fs_err_symlink_metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send + '_ {
+        crate::tokio::symlink_metadata(self)
+    }
+
+    fn fs_err_canonicalize(&self) -> impl Future<Output = io::Result<PathBuf>> + Send + '_ {
+        crate::tokio::canonicalize(self)
+    }
+
+    fn fs_err_read_link(&self) -> impl Future<Output = io::Result<PathBuf>> + Send + '_ {
+        crate::tokio::read_link(self)
+    }
+
+    fn fs_err_read_dir(&self) -> impl Future<Output = io::Result<ReadDir>> + Send + '_ {
+        crate::tokio::read_dir(self)
+    }
+
+    fn fs_err_read(&self) -> impl Future<Output = io::Result<Vec<u8>>> + Send + '_ {
+        crate::tokio::read(self)
+    }
+
+    fn fs_err_read_to_string(&self) -> impl Future<Output = io::Result<String>> + Send + '_ {
+        crate::tokio::read_to_string(self)
+    }
+
+    fn fs_err_write<'a>(
+        &'a self,
+        contents: &'a [u8],
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a {
+        crate::tokio::write(self, contents)
+    }
+
+    fn fs_err_open(&self) -> impl Future<Output = io::Result<File>> + Send + '_ {
+        File::open(self)
+    }
+
+    fn fs_err_create(&self) -> impl Future<Output = io::Result<File>> + Send + '_ {
+        File::create(self)
+    }
+
+    fn fs_err_remove_file(&self) -> impl Future<Output = io::Result<()>> + Send + '_ {
+        crate::tokio::remove_file(self)
+    }
+
+    fn fs_err_remove_dir(&self) -> impl Future<Output = io::Result<()>> + Send + '_ {
+        crate::tokio::remove_dir(self)
+    }
+
+    fn fs_err_remove_dir_all(&self) -> impl Future<Output = io::Result<()>> + Send + '_ {
+        crate::tokio::remove_dir_all(self)
+    }
+
+    fn fs_err_create_dir(&self) -> impl Future<Output = io::Result<()>> + Send + '_ {
+        crate::tokio::create_dir(self)
+    }
+
+    fn fs_err_create_dir_all(&self) -> impl Future<Output = io::Result<()>> + Send + '_ {
+        crate::tokio::create_dir_all(self)
+    }
+
+    fn fs_err_copy_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<u64>> + Send + 'a {
+        crate::tokio::copy(self, dest)
+    }
+
+    fn fs_err_rename_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a {
+        crate::tokio::rename(self, dest)
+    }
+
+    fn fs_err_hard_link_to<'a>(
+        &'a self,
+        dest: &'a Path,
+    ) -> impl Future<Output = io::Result<()>> + Send + 'a {
+        crate::tokio::hard_link(self, dest)
+    }
+}
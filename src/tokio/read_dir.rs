@@ -14,7 +14,7 @@ pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
     let path = path.as_ref();
     let tokio = fs::read_dir(path)
         .await
-        .map_err(|err| Error::build(err, ErrorKind::ReadDir, path))?;
+        .map_err(|err| Error::build_async(err, ErrorKind::ReadDir, path))?;
     Ok(ReadDir {
         tokio,
         path: path.to_owned(),
@@ -39,7 +39,7 @@ impl ReadDir {
     pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
         match self.tokio.next_entry().await {
             Ok(entry) => Ok(entry.map(|e| DirEntry { tokio: e })),
-            Err(err) => Err(Error::build(err, ErrorKind::ReadDir, &self.path)),
+            Err(err) => Err(Error::build_async(err, ErrorKind::ReadDir, &self.path)),
         }
     }
 
@@ -49,7 +49,7 @@ impl ReadDir {
     pub fn poll_next_entry(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<DirEntry>>> {
         Poll::Ready(match ready!(self.tokio.poll_next_entry(cx)) {
             Ok(entry) => Ok(entry.map(|e| DirEntry { tokio: e })),
-            Err(err) => Err(Error::build(err, ErrorKind::ReadDir, &self.path)),
+            Err(err) => Err(Error::build_async(err, ErrorKind::ReadDir, &self.path)),
         })
     }
 }
@@ -64,6 +64,10 @@ pub struct DirEntry {
 }
 
 impl DirEntry {
+    pub(crate) fn from_tokio(tokio: fs::DirEntry) -> DirEntry {
+        DirEntry { tokio }
+    }
+
     /// Returns the full path to the file that this entry represents.
     ///
     /// Wrapper around [`tokio::fs::DirEntry::path`].
@@ -86,7 +90,7 @@ impl DirEntry {
         self.tokio
             .metadata()
             .await
-            .map_err(|err| Error::build(err, ErrorKind::Metadata, self.path()))
+            .map_err(|err| Error::build_async(err, ErrorKind::Metadata, self.path()))
     }
 
     /// Returns the file type for the file that this entry points at.
@@ -96,7 +100,7 @@ impl DirEntry {
         self.tokio
             .file_type()
             .await
-            .map_err(|err| Error::build(err, ErrorKind::Metadata, self.path()))
+            .map_err(|err| Error::build_async(err, ErrorKind::Metadata, self.path()))
     }
 }
 
@@ -0,0 +1,335 @@
+use crate::errors::{report_drop_error, Error, ErrorKind, SourceDestError, SourceDestErrorKind};
+use crate::temp::temp_name;
+use crate::tokio::File;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::{self, IoSlice, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// Creates a new temporary directory in the system's temporary directory.
+///
+/// See [`TempDir`] for how the directory is removed.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub async fn tempdir() -> io::Result<TempDir> {
+    TempDir::new().await
+}
+
+/// A directory which is removed, along with its contents, when dropped.
+///
+/// This is the async equivalent of [`fs_err::TempDir`](crate::TempDir).
+/// Dropping it removes the directory synchronously, blocking the current
+/// thread, and passes any error to the handler set with
+/// [`set_drop_error_handler`](crate::set_drop_error_handler). Use
+/// [`close`](TempDir::close) to remove the directory without blocking and
+/// handle the error directly instead.
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    /// Creates a new temporary directory in the system's temporary directory,
+    /// as returned by [`std::env::temp_dir`].
+    pub async fn new() -> io::Result<TempDir> {
+        TempDir::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates a new temporary directory inside `dir`.
+    ///
+    /// The directory is given a random name which does not already exist. On
+    /// Unix, only the current user is allowed to access it.
+    pub async fn new_in(dir: impl AsRef<Path>) -> io::Result<TempDir> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name());
+            #[cfg_attr(not(unix), allow(unused_mut))]
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            builder.mode(0o700);
+            match builder.create(&path).await {
+                Ok(()) => return Ok(TempDir { path: Some(path) }),
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(Error::build_async(err, ErrorKind::CreateTempDir, path)),
+            }
+        }
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        self.path
+            .as_ref()
+            .expect("temporary directory already removed")
+    }
+
+    /// Keeps the directory, instead of removing it when dropped, and returns
+    /// its path.
+    pub fn keep(mut self) -> PathBuf {
+        self.path
+            .take()
+            .expect("temporary directory already removed")
+    }
+
+    /// Removes the directory and its contents, returning any error.
+    pub async fn close(mut self) -> io::Result<()> {
+        let path = self
+            .path
+            .take()
+            .expect("temporary directory already removed");
+        fs::remove_dir_all(&path)
+            .await
+            .map_err(|err| Error::build_async(err, ErrorKind::RemoveTempDir, path))
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if let Err(err) = std::fs::remove_dir_all(&path) {
+                report_drop_error(Error::build_async(err, ErrorKind::RemoveTempDir, path));
+            }
+        }
+    }
+}
+
+/// A file which is removed when dropped, unless it is persisted.
+///
+/// This is the async equivalent of
+/// [`fs_err::NamedTempFile`](crate::NamedTempFile). Dropping it removes the
+/// file synchronously, blocking the current thread, and passes any error to the
+/// handler set with [`set_drop_error_handler`](crate::set_drop_error_handler).
+/// Use [`close`](NamedTempFile::close) to remove the file without blocking and
+/// handle the error directly instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// let temp = fs_err::tokio::NamedTempFile::new_in("out").await?;
+/// fs_err::tokio::write(temp.path(), b"generated output").await?;
+/// temp.persist("out/result.bin").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct NamedTempFile {
+    file: Option<File>,
+}
+
+impl NamedTempFile {
+    /// Creates a new temporary file in the system's temporary directory, as
+    /// returned by [`std::env::temp_dir`].
+    pub async fn new() -> io::Result<NamedTempFile> {
+        NamedTempFile::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates a new temporary file inside `dir`, opened for reading and
+    /// writing.
+    ///
+    /// The file is given a random name which does not already exist. On Unix,
+    /// only the current user is allowed to access it. To
+    /// [`persist`](NamedTempFile::persist) the file by renaming it, `dir` must
+    /// be on the same filesystem as the destination.
+    pub async fn new_in(dir: impl AsRef<Path>) -> io::Result<NamedTempFile> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name());
+            let mut options = fs::OpenOptions::new();
+            options.read(true).write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            match options.open(&path).await {
+                Ok(file) => {
+                    return Ok(NamedTempFile {
+                        file: Some(File::from_parts(file, path)),
+                    })
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(Error::build_async(err, ErrorKind::CreateTempFile, path)),
+            }
+        }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        self.as_file().path()
+    }
+
+    /// Returns a reference to the open file.
+    pub fn as_file(&self) -> &File {
+        self.file.as_ref().expect("temporary file already removed")
+    }
+
+    /// Returns a mutable reference to the open file.
+    pub fn as_file_mut(&mut self) -> &mut File {
+        self.file.as_mut().expect("temporary file already removed")
+    }
+
+    /// Renames the file to `dest`, replacing any file which already exists
+    /// there, and returns it so that it is no longer removed when dropped.
+    ///
+    /// The returned [`File`] reports errors with the path `dest`. As with
+    /// [`fs_err::NamedTempFile::persist`](crate::NamedTempFile::persist), the
+    /// contents are not synced to disk first.
+    ///
+    /// If the file cannot be renamed, the error includes both paths, and the
+    /// temporary file can be recovered from it to try again.
+    pub async fn persist(mut self, dest: impl AsRef<Path>) -> Result<File, PersistError> {
+        let dest = dest.as_ref();
+        if let Err(err) = fs::rename(self.path(), dest).await {
+            let error =
+                SourceDestError::build_async(err, SourceDestErrorKind::Persist, self.path(), dest);
+            return Err(PersistError { error, file: self });
+        }
+
+        let (file, _) = self
+            .file
+            .take()
+            .expect("temporary file already removed")
+            .into_parts();
+        Ok(File::from_parts(file, dest))
+    }
+
+    /// Keeps the file at its temporary path, instead of removing it when
+    /// dropped, and returns it.
+    pub fn keep(mut self) -> File {
+        self.file.take().expect("temporary file already removed")
+    }
+
+    /// Closes and removes the file, returning any error.
+    pub async fn close(mut self) -> io::Result<()> {
+        let file = self.file.take().expect("temporary file already removed");
+        let (file, path) = file.into_std().await.into_parts();
+        drop(file);
+        fs::remove_file(&path)
+            .await
+            .map_err(|err| Error::build_async(err, ErrorKind::RemoveTempFile, path))
+    }
+}
+
+impl AsRef<Path> for NamedTempFile {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl AsyncRead for NamedTempFile {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(self.as_file_mut()).poll_read(cx, buf)
+    }
+}
+
+impl AsyncSeek for NamedTempFile {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        Pin::new(self.as_file_mut()).start_seek(position)
+    }
+
+    fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Pin::new(self.as_file_mut()).poll_complete(cx)
+    }
+}
+
+impl AsyncWrite for NamedTempFile {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.as_file_mut()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.as_file_mut()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.as_file_mut()).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.as_file_mut()).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.as_file().is_write_vectored()
+    }
+}
+
+impl Drop for NamedTempFile {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let (file, path) = file.into_parts();
+            // The file must be closed before it can be removed on Windows.
+            drop(file);
+            if let Err(err) = std::fs::remove_file(&path) {
+                report_drop_error(Error::build_async(err, ErrorKind::RemoveTempFile, path));
+            }
+        }
+    }
+}
+
+/// The error returned when [`NamedTempFile::persist`] fails, which contains the
+/// temporary file so that it is not removed.
+///
+/// This converts into an [`io::Error`] with `?`, in which case the temporary
+/// file is removed.
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct PersistError {
+    error: io::Error,
+    file: NamedTempFile,
+}
+
+impl PersistError {
+    /// Returns the error which caused the file not to be persisted.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the error, removing the temporary file.
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+
+    /// Returns the temporary file which could not be persisted.
+    pub fn into_file(self) -> NamedTempFile {
+        self.file
+    }
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, formatter)
+    }
+}
+
+impl StdError for PersistError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
+}
+
+impl From<PersistError> for io::Error {
+    fn from(err: PersistError) -> io::Error {
+        err.error
+    }
+}
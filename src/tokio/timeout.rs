@@ -0,0 +1,247 @@
+use crate::errors::{Error, ErrorKind, SourceDestError, SourceDestErrorKind};
+use crate::tokio::ReadDir;
+use std::fs::{Metadata, Permissions};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Returns a [`Timeout`] which runs filesystem operations with a time limit.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use std::time::Duration;
+///
+/// let result = fs_err::tokio::with_timeout(Duration::from_secs(5))
+///     .metadata("/mnt/nfs/data.bin")
+///     .await;
+/// if let Err(err) = &result {
+///     // If the server hangs, fails with "failed to query metadata of file
+///     // `/mnt/nfs/data.bin`: timed out after 5s", without touching the
+///     // filesystem again to diagnose the error.
+///     assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+///     assert!(fs_err::context_of(err).unwrap().diagnostic().is_none());
+/// }
+/// let metadata = result?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-time")))]
+pub fn with_timeout(duration: Duration) -> Timeout {
+    Timeout { duration }
+}
+
+/// Runs filesystem operations with a time limit, created by [`with_timeout`].
+///
+/// Each method behaves like the function of the same name in this module, but
+/// fails with an error of kind [`io::ErrorKind::TimedOut`] if the operation
+/// does not complete within the time limit. The error includes the operation
+/// and path(s), like any other error from fs-err, so that a hang on a network
+/// filesystem can be attributed. It never includes a
+/// [diagnostic](crate::ErrorContext::diagnostic), since diagnosing the error
+/// would block on the same filesystem.
+///
+/// The operations run on tokio's blocking thread pool, so an operation which
+/// times out keeps running in the background until the operating system
+/// returns. The runtime must have the time driver enabled, as for
+/// [`tokio::time::timeout`].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-time")))]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl Timeout {
+    /// Returns the time limit for each operation.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Like [`canonicalize`](crate::tokio::canonicalize), with a time limit.
+    pub async fn canonicalize(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = path.as_ref();
+        self.run(super::canonicalize(path), |err| {
+            Error::build_async(err, ErrorKind::Canonicalize, path)
+        })
+        .await
+    }
+
+    /// Like [`copy`](crate::tokio::copy), with a time limit.
+    pub async fn copy(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<u64> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        self.run(super::copy(from, to), |err| {
+            SourceDestError::build_async(err, SourceDestErrorKind::Copy, from, to)
+        })
+        .await
+    }
+
+    /// Like [`create_dir`](crate::tokio::create_dir), with a time limit.
+    pub async fn create_dir(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::create_dir(path), |err| {
+            Error::build_async(err, ErrorKind::CreateDir, path)
+        })
+        .await
+    }
+
+    /// Like [`create_dir_all`](crate::tokio::create_dir_all), with a time
+    /// limit.
+    pub async fn create_dir_all(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::create_dir_all(path), |err| {
+            Error::build_async(err, ErrorKind::CreateDir, path)
+        })
+        .await
+    }
+
+    /// Like [`hard_link`](crate::tokio::hard_link), with a time limit.
+    pub async fn hard_link(&self, src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        self.run(super::hard_link(src, dst), |err| {
+            SourceDestError::build_async(err, SourceDestErrorKind::HardLink, src, dst)
+        })
+        .await
+    }
+
+    /// Like [`metadata`](crate::tokio::metadata), with a time limit.
+    pub async fn metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        let path = path.as_ref();
+        self.run(super::metadata(path), |err| {
+            Error::build_async(err, ErrorKind::Metadata, path)
+        })
+        .await
+    }
+
+    /// Like [`read`](crate::tokio::read), with a time limit.
+    pub async fn read(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        let path = path.as_ref();
+        self.run(super::read(path), |err| {
+            Error::build_async(err, ErrorKind::Read, path)
+        })
+        .await
+    }
+
+    /// Like [`read_dir`](crate::tokio::read_dir), with a time limit for
+    /// opening the directory. Reading the entries is not limited.
+    pub async fn read_dir(&self, path: impl AsRef<Path>) -> io::Result<ReadDir> {
+        let path = path.as_ref();
+        self.run(super::read_dir(path), |err| {
+            Error::build_async(err, ErrorKind::ReadDir, path)
+        })
+        .await
+    }
+
+    /// Like [`read_link`](crate::tokio::read_link), with a time limit.
+    pub async fn read_link(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = path.as_ref();
+        self.run(super::read_link(path), |err| {
+            Error::build_async(err, ErrorKind::ReadLink, path)
+        })
+        .await
+    }
+
+    /// Like [`read_to_string`](crate::tokio::read_to_string), with a time
+    /// limit.
+    pub async fn read_to_string(&self, path: impl AsRef<Path>) -> io::Result<String> {
+        let path = path.as_ref();
+        self.run(super::read_to_string(path), |err| {
+            Error::build_async(err, ErrorKind::Read, path)
+        })
+        .await
+    }
+
+    /// Like [`remove_dir`](crate::tokio::remove_dir), with a time limit.
+    pub async fn remove_dir(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::remove_dir(path), |err| {
+            Error::build_async(err, ErrorKind::RemoveDir, path)
+        })
+        .await
+    }
+
+    /// Like [`remove_dir_all`](crate::tokio::remove_dir_all), with a time
+    /// limit.
+    pub async fn remove_dir_all(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::remove_dir_all(path), |err| {
+            Error::build_async(err, ErrorKind::RemoveDir, path)
+        })
+        .await
+    }
+
+    /// Like [`remove_file`](crate::tokio::remove_file), with a time limit.
+    pub async fn remove_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::remove_file(path), |err| {
+            Error::build_async(err, ErrorKind::RemoveFile, path)
+        })
+        .await
+    }
+
+    /// Like [`rename`](crate::tokio::rename), with a time limit.
+    pub async fn rename(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        self.run(super::rename(from, to), |err| {
+            SourceDestError::build_async(err, SourceDestErrorKind::Rename, from, to)
+        })
+        .await
+    }
+
+    /// Like [`set_permissions`](crate::tokio::set_permissions), with a time
+    /// limit.
+    pub async fn set_permissions(
+        &self,
+        path: impl AsRef<Path>,
+        perm: Permissions,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+        self.run(super::set_permissions(path, perm), |err| {
+            Error::build_async(err, ErrorKind::SetPermissions, path)
+        })
+        .await
+    }
+
+    /// Like [`symlink_metadata`](crate::tokio::symlink_metadata), with a time
+    /// limit.
+    pub async fn symlink_metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        let path = path.as_ref();
+        self.run(super::symlink_metadata(path), |err| {
+            Error::build_async(err, ErrorKind::SymlinkMetadata, path)
+        })
+        .await
+    }
+
+    /// Like [`write`](crate::tokio::write), with a time limit.
+    pub async fn write(
+        &self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        let (path, contents) = (path.as_ref(), contents.as_ref());
+        self.run(super::write(path, contents), |err| {
+            Error::build_async(err, ErrorKind::Write, path)
+        })
+        .await
+    }
+
+    /// Runs `future`, or returns a timeout error with the context added by
+    /// `error` if it does not complete in time.
+    async fn run<T>(
+        &self,
+        future: impl Future<Output = io::Result<T>>,
+        error: impl FnOnce(io::Error) -> io::Error,
+    ) -> io::Result<T> {
+        match tokio::time::timeout(self.duration, future).await {
+            Ok(result) => result,
+            Err(_) => {
+                let source = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("timed out after {:?}", self.duration),
+                );
+                Err(error(source))
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::errors::{Error, ErrorKind};
+use crate::tokio::DirEntry;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Returns a stream over the entries within a directory and all of its
+/// subdirectories.
+///
+/// This is the async equivalent of [`fs_err::walk_dir`](crate::walk_dir). It
+/// uses the default [`WalkDirOptions`]: there is no depth limit, and
+/// symbolic links are not followed.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub fn walk_dir(path: impl Into<PathBuf>) -> WalkDir {
+    WalkDirOptions::new().walk(path)
+}
+
+/// Options for walking a directory tree with [`walk_dir`].
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// let mut walk = fs_err::tokio::WalkDirOptions::new()
+///     .max_depth(2)
+///     .follow_symlinks(true)
+///     .walk("src");
+/// while let Some(entry) = walk.next_entry().await? {
+///     println!("{}", entry.path().display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct WalkDirOptions {
+    max_depth: Option<usize>,
+    follow_symlinks: bool,
+}
+
+impl WalkDirOptions {
+    /// Creates options which walk the whole tree without following symbolic
+    /// links.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        WalkDirOptions {
+            max_depth: None,
+            follow_symlinks: false,
+        }
+    }
+
+    /// Sets the maximum depth of entries to return. The entries directly
+    /// within the directory have a depth of 1, so a maximum depth of 1 behaves
+    /// like [`read_dir`](crate::tokio::read_dir), and a maximum depth of 0
+    /// returns no entries.
+    pub fn max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets whether symbolic links to directories are descended into.
+    ///
+    /// A symbolic link which leads to one of the directories containing it is
+    /// reported as an error of kind [`io::ErrorKind::InvalidData`] instead of
+    /// being followed forever.
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Returns a stream over the entries within the directory `path` and its
+    /// subdirectories with these options.
+    pub fn walk(&self, path: impl Into<PathBuf>) -> WalkDir {
+        WalkDir {
+            options: self.clone(),
+            root: Some(path.into()),
+            stack: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// A stream over the entries in a directory tree, created by [`walk_dir`] or
+/// [`WalkDirOptions::walk`].
+///
+/// Entries are returned before the contents of the directories they refer to.
+/// If a subdirectory cannot be read, the entry for it is returned as usual,
+/// then the next call to [`next_entry`](WalkDir::next_entry) returns an error
+/// with the path of that subdirectory, and the walk continues with the call
+/// after that.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct WalkDir {
+    options: WalkDirOptions,
+    root: Option<PathBuf>,
+    stack: Vec<Level>,
+    error: Option<io::Error>,
+}
+
+/// A directory which is being read.
+#[derive(Debug)]
+struct Level {
+    read_dir: fs::ReadDir,
+    path: PathBuf,
+    /// The canonical path of the directory, if symbolic links are followed.
+    canonical: Option<PathBuf>,
+}
+
+impl WalkDir {
+    /// Returns the next entry in the directory tree.
+    pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
+        if let Some(root) = self.root.take() {
+            self.push(root, None).await?;
+        }
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let too_deep = match self.options.max_depth {
+                Some(max_depth) => depth > max_depth,
+                None => false,
+            };
+            if too_deep {
+                // The entries of the directory being walked are too deep with
+                // a maximum depth of 0.
+                self.stack.pop();
+                continue;
+            }
+            let level = match self.stack.last_mut() {
+                Some(level) => level,
+                None => return Ok(None),
+            };
+            let entry = match level.read_dir.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => {
+                    self.stack.pop();
+                    continue;
+                }
+                Err(err) => {
+                    let path = level.path.clone();
+                    self.stack.pop();
+                    return Err(Error::build_async(err, ErrorKind::ReadDir, path));
+                }
+            };
+
+            let below_max_depth = match self.options.max_depth {
+                Some(max_depth) => depth < max_depth,
+                None => true,
+            };
+            if below_max_depth {
+                // The error is returned after the entry itself.
+                self.error = self.descend(&entry).await.err();
+            }
+            return Ok(Some(DirEntry::from_tokio(entry)));
+        }
+    }
+
+    /// Starts reading the directory `entry` refers to, if it should be
+    /// descended into.
+    async fn descend(&mut self, entry: &fs::DirEntry) -> io::Result<()> {
+        let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .await
+            .map_err(|err| Error::build_async(err, ErrorKind::Metadata, &path))?;
+        if !self.options.follow_symlinks {
+            if file_type.is_dir() {
+                self.push(path, None).await?;
+            }
+            return Ok(());
+        }
+        if file_type.is_symlink() {
+            // Broken links and links to files are returned without descending.
+            match fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_dir() => {}
+                _ => return Ok(()),
+            }
+        } else if !file_type.is_dir() {
+            return Ok(());
+        }
+
+        let canonical = canonicalize(&path).await?;
+        let is_loop = self
+            .stack
+            .iter()
+            .any(|level| level.canonical.as_deref() == Some(canonical.as_path()));
+        if is_loop {
+            let source = io::Error::new(
+                io::ErrorKind::InvalidData,
+                "symbolic link leads to a directory containing it",
+            );
+            return Err(Error::build_async(source, ErrorKind::ReadDir, path));
+        }
+        self.push(path, Some(canonical)).await
+    }
+
+    async fn push(&mut self, path: PathBuf, canonical: Option<PathBuf>) -> io::Result<()> {
+        let read_dir = fs::read_dir(&path)
+            .await
+            .map_err(|err| Error::build_async(err, ErrorKind::ReadDir, &path))?;
+        let canonical = match canonical {
+            Some(canonical) => Some(canonical),
+            None if self.options.follow_symlinks => Some(canonicalize(&path).await?),
+            None => None,
+        };
+        self.stack.push(Level {
+            read_dir,
+            path,
+            canonical,
+        });
+        Ok(())
+    }
+}
+
+async fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path)
+        .await
+        .map_err(|err| Error::build_async(err, ErrorKind::Canonicalize, path))
+}
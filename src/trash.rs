@@ -0,0 +1,442 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Moves a file to the trash, or recycle bin, instead of permanently removing
+/// it.
+///
+/// This follows the FreeDesktop.org trash specification on Linux and other
+/// Unix platforms, asks Finder to move the file on macOS and uses the recycle
+/// bin on Windows, so that the file can be restored by the user's file
+/// manager.
+/// Symbolic links are moved to the trash rather than their targets. Fails if
+/// `path` is a directory; use [`remove_dir_to_trash`] for those.
+///
+/// # Example
+///
+/// ```no_run
+/// fs_err::remove_file_to_trash("old-notes.txt")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_file_to_trash<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let metadata = fs::symlink_metadata(path).with_path(ErrorKind::TrashFile, path)?;
+    if metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path is a directory",
+        ))
+        .with_path(ErrorKind::TrashFile, path);
+    }
+    sys::trash(path).with_path(ErrorKind::TrashFile, path)
+}
+
+/// Moves a directory and all of its contents to the trash, or recycle bin,
+/// instead of permanently removing them.
+///
+/// See [`remove_file_to_trash`] for how the trash is found. Fails if `path` is
+/// not a directory.
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn remove_dir_to_trash<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let metadata = fs::symlink_metadata(path).with_path(ErrorKind::TrashDir, path)?;
+    if !metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path is not a directory",
+        ))
+        .with_path(ErrorKind::TrashDir, path);
+    }
+    sys::trash(path).with_path(ErrorKind::TrashDir, path)
+}
+
+/// An implementation of the FreeDesktop.org trash specification:
+/// <https://specifications.freedesktop.org/trash-spec/latest/>
+#[cfg(all(unix, not(target_vendor = "apple")))]
+mod sys {
+    use std::ffi::{OsStr, OsString};
+    use std::fmt::Write as _;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::path::{Path, PathBuf};
+
+    pub(super) fn trash(path: &Path) -> io::Result<()> {
+        let (parent, name) = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "path does not have a file name",
+                ))
+            }
+        };
+        let parent = if parent == Path::new("") {
+            std::env::current_dir()?
+        } else {
+            parent.canonicalize()?
+        };
+        let path = parent.join(name);
+        let dev = fs::symlink_metadata(&path)?.dev();
+
+        let home_trash = home_trash()?;
+        let (trash_dir, original_path) = if fs::metadata(&home_trash)?.dev() == dev {
+            (home_trash, path.clone())
+        } else {
+            // Files on other filesystems are moved to a trash directory at the
+            // top of that filesystem, with paths relative to it.
+            let top_dir = top_dir(&parent, dev)?;
+            let trash_dir = top_dir_trash(&top_dir)?;
+            let relative = path.strip_prefix(&top_dir).unwrap_or(&path).to_path_buf();
+            (trash_dir, relative)
+        };
+        let files_dir = trash_dir.join("files");
+        let info_dir = trash_dir.join("info");
+        create_trash_dir(&files_dir)?;
+        create_trash_dir(&info_dir)?;
+
+        let (trash_name, info_path) = write_info(&info_dir, &files_dir, name, &original_path)?;
+        if let Err(err) = fs::rename(&path, files_dir.join(&trash_name)) {
+            let _ = fs::remove_file(&info_path);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Returns `$XDG_DATA_HOME/Trash`, creating it if necessary.
+    fn home_trash() -> io::Result<PathBuf> {
+        let data_home = match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+            _ => match std::env::var_os("HOME") {
+                Some(home) => Path::new(&home).join(".local/share"),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "could not find the trash directory because $HOME is not set",
+                    ))
+                }
+            },
+        };
+        let trash = data_home.join("Trash");
+        create_trash_dir(&trash)?;
+        Ok(trash)
+    }
+
+    /// Returns the top directory of the filesystem containing `dir`, which is
+    /// on the device `dev`.
+    fn top_dir(dir: &Path, dev: u64) -> io::Result<PathBuf> {
+        let mut top_dir = dir;
+        while let Some(parent) = top_dir.parent() {
+            if fs::metadata(parent)?.dev() != dev {
+                break;
+            }
+            top_dir = parent;
+        }
+        Ok(top_dir.to_path_buf())
+    }
+
+    /// Returns `$topdir/.Trash/$uid` if an administrator has created
+    /// `$topdir/.Trash` securely, or `$topdir/.Trash-$uid` otherwise.
+    fn top_dir_trash(top_dir: &Path) -> io::Result<PathBuf> {
+        // The sticky bit, which has the same value on all Unix platforms
+        // although the type of `libc::S_ISVTX` varies.
+        const S_ISVTX: u32 = 0o1000;
+
+        // SAFETY: `getuid` is always successful.
+        let uid = unsafe { libc::getuid() };
+        let shared = top_dir.join(".Trash");
+        if let Ok(metadata) = fs::symlink_metadata(&shared) {
+            if metadata.is_dir() && metadata.mode() & S_ISVTX != 0 {
+                let trash = shared.join(uid.to_string());
+                if create_trash_dir(&trash).is_ok() {
+                    return Ok(trash);
+                }
+            }
+        }
+        let trash = top_dir.join(format!(".Trash-{}", uid));
+        create_trash_dir(&trash)?;
+        Ok(trash)
+    }
+
+    fn create_trash_dir(dir: &Path) -> io::Result<()> {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!(
+                        "could not create trash directory `{}`: {}",
+                        dir.display(),
+                        err
+                    ),
+                )
+            })
+    }
+
+    /// Creates the `.trashinfo` file recording where the file came from, with a
+    /// name which is not used in the trash, and returns the name under which the
+    /// file should be stored along with the path of the info file.
+    fn write_info(
+        info_dir: &Path,
+        files_dir: &Path,
+        name: &OsStr,
+        original_path: &Path,
+    ) -> io::Result<(OsString, PathBuf)> {
+        let contents = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(original_path),
+            local_time()?
+        );
+        for n in 1.. {
+            let mut trash_name = name.to_owned();
+            if n > 1 {
+                trash_name.push(format!(".{}", n));
+            }
+            let mut info_name = trash_name.clone();
+            info_name.push(".trashinfo");
+            let info_path = info_dir.join(info_name);
+
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(mut file) => {
+                    // A file may have been left in the trash without its info file.
+                    if fs::symlink_metadata(files_dir.join(&trash_name)).is_ok() {
+                        let _ = fs::remove_file(&info_path);
+                        continue;
+                    }
+                    if let Err(err) = file.write_all(contents.as_bytes()) {
+                        let _ = fs::remove_file(&info_path);
+                        return Err(err);
+                    }
+                    return Ok((trash_name, info_path));
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => {
+                    return Err(io::Error::new(
+                        err.kind(),
+                        format!(
+                            "could not create trash info file `{}`: {}",
+                            info_path.display(),
+                            err
+                        ),
+                    ))
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    /// Percent-encodes a path as in a URL, as required in `.trashinfo` files.
+    fn encode_path(path: &Path) -> String {
+        let mut encoded = String::new();
+        for &byte in path.as_os_str().as_bytes() {
+            match byte {
+                b'A'..=b'Z'
+                | b'a'..=b'z'
+                | b'0'..=b'9'
+                | b'/'
+                | b'-'
+                | b'_'
+                | b'.'
+                | b'~'
+                | b'!'
+                | b'*'
+                | b'\''
+                | b'('
+                | b')' => encoded.push(byte as char),
+                _ => {
+                    let _ = write!(encoded, "%{:02X}", byte);
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Returns the current local time in the `YYYY-MM-DDThh:mm:ss` format.
+    fn local_time() -> io::Result<String> {
+        // SAFETY: `struct tm` is valid when zeroed, and is only read if
+        // `localtime_r` succeeds.
+        let tm = unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            let mut tm = std::mem::zeroed::<libc::tm>();
+            if libc::localtime_r(&now, &mut tm).is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            tm
+        };
+        Ok(format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min,
+            tm.tm_sec
+        ))
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod sys {
+    use std::io;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Asks Finder to move the file to the trash with `osascript`, so that it
+    /// can be put back from the trash.
+    pub(super) fn trash(path: &Path) -> io::Result<()> {
+        let path = std::env::current_dir()?.join(path);
+        let path = match path.to_str() {
+            Some(path) => path.replace('\\', "\\\\").replace('"', "\\\""),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "path is not valid UTF-8",
+                ))
+            }
+        };
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "tell application \"Finder\" to delete POSIX file \"{}\"",
+                path
+            ))
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // -1743 is `errAEEventNotPermitted`, when the user has not allowed
+        // the program to control Finder.
+        let kind = if stderr.contains("(-1743)") {
+            io::ErrorKind::PermissionDenied
+        } else {
+            io::ErrorKind::Other
+        };
+        Err(io::Error::new(
+            kind,
+            format!(
+                "Finder could not move the item to the trash: {}",
+                stderr.trim()
+            ),
+        ))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::io;
+    use std::os::raw::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+
+    const ERROR_CANCELLED: i32 = 1223;
+    const FO_DELETE: u32 = 3;
+    const FOF_SILENT: u16 = 0x4;
+    const FOF_NOCONFIRMATION: u16 = 0x10;
+    const FOF_ALLOWUNDO: u16 = 0x40;
+    const FOF_NOERRORUI: u16 = 0x400;
+
+    /// `SHFILEOPSTRUCTW`, which is packed on 32-bit platforms.
+    #[cfg_attr(target_pointer_width = "32", repr(C, packed))]
+    #[cfg_attr(not(target_pointer_width = "32"), repr(C))]
+    struct FileOp {
+        hwnd: *mut c_void,
+        func: u32,
+        from: *const u16,
+        to: *const u16,
+        flags: u16,
+        any_operations_aborted: i32,
+        name_mappings: *mut c_void,
+        progress_title: *const u16,
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHFileOperationW(op: *mut FileOp) -> i32;
+    }
+
+    /// Moves the file to the recycle bin with `SHFileOperationW`.
+    pub(super) fn trash(path: &Path) -> io::Result<()> {
+        // The recycle bin requires a full path, and `pFrom` is a list of paths
+        // terminated by an empty one.
+        let path = std::env::current_dir()?.join(path);
+        let mut from: Vec<u16> = path.as_os_str().encode_wide().collect();
+        from.extend_from_slice(&[0, 0]);
+
+        let mut op = FileOp {
+            hwnd: std::ptr::null_mut(),
+            func: FO_DELETE,
+            from: from.as_ptr(),
+            to: std::ptr::null(),
+            flags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT,
+            any_operations_aborted: 0,
+            name_mappings: std::ptr::null_mut(),
+            progress_title: std::ptr::null(),
+        };
+        // SAFETY: `op` is a valid `SHFILEOPSTRUCTW`, and `from` outlives the call.
+        match unsafe { SHFileOperationW(&mut op) } {
+            0 if op.any_operations_aborted != 0 => {
+                Err(io::Error::from_raw_os_error(ERROR_CANCELLED))
+            }
+            0 => Ok(()),
+            code => Err(error(code)),
+        }
+    }
+
+    /// Converts a value returned by `SHFileOperationW` to an error. Some values
+    /// are `DE_*` codes specific to it, which overlap with system error codes
+    /// and so cannot be passed to `from_raw_os_error`.
+    fn error(code: i32) -> io::Error {
+        let (kind, message) = match code {
+            0x74 => (
+                io::ErrorKind::InvalidInput,
+                "cannot move a root directory to the recycle bin",
+            ),
+            0x75 => (io::ErrorKind::Interrupted, "the operation was cancelled"),
+            0x78 => (io::ErrorKind::PermissionDenied, "access is denied"),
+            0x79 | 0x81 | 0xB7 => (io::ErrorKind::InvalidInput, "the path is too long"),
+            0x7C => (
+                io::ErrorKind::NotFound,
+                "the path does not exist or is not valid",
+            ),
+            0x85 => (
+                io::ErrorKind::Other,
+                "the file is too large for the recycle bin",
+            ),
+            0x86..=0x88 => (
+                io::ErrorKind::PermissionDenied,
+                "the file is on read-only media",
+            ),
+            0x71..=0x88 | 0x402 | 0x10000 => (
+                io::ErrorKind::Other,
+                "the recycle bin returned an unexpected error",
+            ),
+            code => return io::Error::from_raw_os_error(code),
+        };
+        io::Error::new(kind, format!("{} (code {:#X})", message, code))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::io;
+    use std::path::Path;
+
+    pub(super) fn trash(_path: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the trash is not supported on this platform",
+        ))
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::errors::{ErrorKind, ResultExt};
+
+/// Write a slice as the entire contents of a file, then read it back to check
+/// that it was written correctly.
+///
+/// The file is created or truncated, written, synced to disk and then opened
+/// again to compare its contents with `contents`. Each step fails with its own
+/// message and [`Operation`](crate::Operation), so the error says which one
+/// went wrong for which path. If the contents read back differ, the error has
+/// [`Operation::Verify`](crate::Operation::Verify) and kind
+/// [`io::ErrorKind::InvalidData`], and says where they first differ.
+///
+/// The contents are usually read back from the operating system's cache, so
+/// this detects files which were modified or truncated concurrently, or
+/// filesystems which lose writes, rather than faulty storage media.
+///
+/// # Example
+///
+/// ```no_run
+/// fs_err::write_verified("firmware.bin", [0x7f, b'E', b'L', b'F'])?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(rustc_1_46, track_caller)]
+pub fn write_verified<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+
+    let mut file = fs::File::create(path).with_path(ErrorKind::CreateFile, path)?;
+    file.write_all(contents)
+        .with_write(ErrorKind::Write, path, contents.len())?;
+    file.sync_all().with_path(ErrorKind::SyncFile, path)?;
+    drop(file);
+
+    let mut read = Vec::with_capacity(contents.len() + 1);
+    fs::File::open(path)
+        .and_then(|file| file.take(contents.len() as u64 + 1).read_to_end(&mut read))
+        .with_path(ErrorKind::ReadBack, path)?;
+
+    match mismatch(contents, &read) {
+        Some(message) => Err(io::Error::new(io::ErrorKind::InvalidData, message))
+            .with_path(ErrorKind::Verify, path),
+        None => Ok(()),
+    }
+}
+
+/// Describes how the contents read back differ from those written, if at all.
+fn mismatch(written: &[u8], read: &[u8]) -> Option<String> {
+    if let Some(offset) = written.iter().zip(read).position(|(a, b)| a != b) {
+        return Some(format!("contents differ at byte {}", offset));
+    }
+    if read.len() > written.len() {
+        Some(format!(
+            "read back more than the {} bytes written",
+            written.len()
+        ))
+    } else if read.len() < written.len() {
+        Some(format!(
+            "read back {} bytes, but {} were written",
+            read.len(),
+            written.len()
+        ))
+    } else {
+        None
+    }
+}
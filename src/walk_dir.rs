@@ -0,0 +1,312 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::vec;
+
+use crate::errors::{Error, ErrorKind};
+use crate::{DirEntry, ReadDir};
+
+/// Returns an iterator over the entries within a directory and all of its
+/// subdirectories.
+///
+/// This uses the default [`WalkDirOptions`]: there is no depth limit, symbolic
+/// links are not followed, and entries are returned in the order the operating
+/// system lists them.
+pub fn walk_dir<P: Into<PathBuf>>(path: P) -> WalkDir {
+    WalkDirOptions::new().walk(path)
+}
+
+/// Options for walking a directory tree with [`walk_dir`].
+///
+/// # Example
+///
+/// ```no_run
+/// let walk = fs_err::WalkDirOptions::new()
+///     .max_depth(2)
+///     .follow_symlinks(true)
+///     .sort(true)
+///     .walk("src");
+/// for entry in walk {
+///     let entry = entry?;
+///     println!("{} ({} bytes)", entry.path().display(), entry.metadata().len());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct WalkDirOptions {
+    max_depth: Option<usize>,
+    follow_symlinks: bool,
+    sort: bool,
+}
+
+impl WalkDirOptions {
+    /// Creates options which walk the whole tree without following symbolic
+    /// links or sorting entries.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        WalkDirOptions {
+            max_depth: None,
+            follow_symlinks: false,
+            sort: false,
+        }
+    }
+
+    /// Sets the maximum depth of entries to return. The entries directly
+    /// within the directory have a depth of 1, so a maximum depth of 1 behaves
+    /// like [`read_dir`](crate::read_dir), and a maximum depth of 0 returns no
+    /// entries.
+    pub fn max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets whether symbolic links to directories are descended into.
+    ///
+    /// A symbolic link which leads to one of the directories containing it is
+    /// reported as an error of kind [`io::ErrorKind::InvalidData`] instead of
+    /// being followed forever.
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Sets whether the entries within each directory are returned in order
+    /// of their file names. This reads all of the entries of a directory
+    /// before returning the first one.
+    pub fn sort(&mut self, sort: bool) -> &mut Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Returns an iterator over the entries within the directory `path` and
+    /// its subdirectories with these options.
+    pub fn walk<P: Into<PathBuf>>(&self, path: P) -> WalkDir {
+        WalkDir {
+            options: self.clone(),
+            root: Some(path.into()),
+            stack: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// An iterator over the entries in a directory tree, created by [`walk_dir`]
+/// or [`WalkDirOptions::walk`].
+///
+/// Entries are returned depth-first, before the contents of the directories
+/// they refer to. If a subdirectory cannot be read, the entry for it is
+/// returned as usual, then the next call to `next` returns an error with the
+/// path of that subdirectory, and the walk continues with the call after that.
+/// Errors reading the metadata of an entry include the path of the entry.
+#[derive(Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct WalkDir {
+    options: WalkDirOptions,
+    root: Option<PathBuf>,
+    stack: Vec<Level>,
+    error: Option<io::Error>,
+}
+
+/// A directory which is being read.
+#[derive(Debug)]
+struct Level {
+    entries: Entries,
+    /// The canonical path of the directory, if symbolic links are followed.
+    canonical: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+enum Entries {
+    Unsorted(Box<ReadDir>),
+    Sorted(vec::IntoIter<io::Result<DirEntry>>),
+}
+
+impl Iterator for Entries {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Entries::Unsorted(read_dir) => read_dir.next(),
+            Entries::Sorted(entries) => entries.next(),
+        }
+    }
+}
+
+impl Iterator for WalkDir {
+    type Item = io::Result<WalkDirEntry>;
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            if let Err(err) = self.push(root, None) {
+                return Some(Err(err));
+            }
+        }
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let too_deep = match self.options.max_depth {
+                Some(max_depth) => depth > max_depth,
+                None => false,
+            };
+            if too_deep {
+                // The entries of the directory being walked are too deep with
+                // a maximum depth of 0.
+                self.stack.pop();
+                continue;
+            }
+            let entry = match self.stack.last_mut()?.entries.next() {
+                Some(Ok(entry)) => entry,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+                Some(Err(err)) => {
+                    self.stack.pop();
+                    return Some(Err(err));
+                }
+            };
+            let entry = match self.read_entry(entry, depth) {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let below_max_depth = match self.options.max_depth {
+                Some(max_depth) => depth < max_depth,
+                None => true,
+            };
+            if below_max_depth && entry.file_type().is_dir() {
+                // The error is returned after the entry itself.
+                self.error = self.descend(&entry).err();
+            }
+            return Some(Ok(entry));
+        }
+    }
+}
+
+impl WalkDir {
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn read_entry(&self, entry: DirEntry, depth: usize) -> io::Result<WalkDirEntry> {
+        let mut metadata = entry.metadata()?;
+        let is_symlink = metadata.file_type().is_symlink();
+        if is_symlink && self.options.follow_symlinks {
+            // Broken links are returned as links.
+            if let Ok(target) = fs::metadata(entry.path()) {
+                metadata = target;
+            }
+        }
+        Ok(WalkDirEntry {
+            path: entry.path(),
+            depth,
+            metadata,
+            is_symlink,
+        })
+    }
+
+    /// Starts reading the directory `entry` refers to.
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn descend(&mut self, entry: &WalkDirEntry) -> io::Result<()> {
+        if !entry.is_symlink {
+            return self.push(entry.path.clone(), None);
+        }
+
+        let canonical = crate::canonicalize(&entry.path)?;
+        let is_loop = self
+            .stack
+            .iter()
+            .any(|level| level.canonical.as_deref() == Some(canonical.as_path()));
+        if is_loop {
+            let source = io::Error::new(
+                io::ErrorKind::InvalidData,
+                "symbolic link leads to a directory containing it",
+            );
+            return Err(Error::build(source, ErrorKind::ReadDir, &entry.path));
+        }
+        self.push(entry.path.clone(), Some(canonical))
+    }
+
+    #[cfg_attr(rustc_1_46, track_caller)]
+    fn push(&mut self, path: PathBuf, canonical: Option<PathBuf>) -> io::Result<()> {
+        let read_dir = crate::read_dir(&path)?;
+        let entries = if self.options.sort {
+            // Errors are returned after the entries which could be read.
+            let mut entries: Vec<_> = read_dir.collect();
+            entries.sort_by(|a, b| match (a, b) {
+                (Ok(a), Ok(b)) => a.file_name().cmp(&b.file_name()),
+                (a, b) => b.is_ok().cmp(&a.is_ok()),
+            });
+            Entries::Sorted(entries.into_iter())
+        } else {
+            Entries::Unsorted(Box::new(read_dir))
+        };
+        let canonical = match canonical {
+            Some(canonical) => Some(canonical),
+            None if self.options.follow_symlinks => Some(crate::canonicalize(&path)?),
+            None => None,
+        };
+        self.stack.push(Level { entries, canonical });
+        Ok(())
+    }
+}
+
+/// An entry in a directory tree, returned by [`WalkDir`].
+///
+/// The metadata of the entry is read while walking the tree, so it is
+/// available without another call to the filesystem.
+#[derive(Debug, Clone)]
+pub struct WalkDirEntry {
+    path: PathBuf,
+    depth: usize,
+    metadata: fs::Metadata,
+    is_symlink: bool,
+}
+
+impl WalkDirEntry {
+    /// Returns the full path of the entry, starting with the path of the
+    /// directory being walked.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the full path of the entry.
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// Returns the file name of the entry.
+    pub fn file_name(&self) -> &OsStr {
+        self.path
+            .file_name()
+            .expect("directory entries have a file name")
+    }
+
+    /// Returns the depth of the entry: 1 for entries directly within the
+    /// directory being walked, 2 for the entries within those, and so on.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the metadata of the entry.
+    ///
+    /// If symbolic links are followed, this is the metadata of the file the
+    /// link points to, unless the link is broken.
+    pub fn metadata(&self) -> &fs::Metadata {
+        &self.metadata
+    }
+
+    /// Returns the file type of the entry, from its
+    /// [`metadata`](WalkDirEntry::metadata).
+    pub fn file_type(&self) -> fs::FileType {
+        self.metadata.file_type()
+    }
+
+    /// Returns whether the entry is a symbolic link, even if it is being
+    /// followed.
+    pub fn path_is_symlink(&self) -> bool {
+        self.is_symlink
+    }
+}
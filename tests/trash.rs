@@ -0,0 +1,51 @@
+#![cfg(all(feature = "trash", target_os = "linux"))]
+
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fs-err-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn remove_file_to_trash_round_trip() {
+    let dir = temp_dir("trash");
+    let data_home = dir.join("data");
+    std::env::set_var("XDG_DATA_HOME", &data_home);
+
+    let path = dir.join("notes 1.txt");
+    fs::write(&path, "hello").unwrap();
+    fs_err::remove_file_to_trash(&path).unwrap();
+    assert!(!path.exists());
+
+    let trashed = data_home.join("Trash/files/notes 1.txt");
+    let info = fs::read_to_string(data_home.join("Trash/info/notes 1.txt.trashinfo")).unwrap();
+    let mut lines = info.lines();
+    assert_eq!(lines.next(), Some("[Trash Info]"));
+    let encoded = path.to_str().unwrap().replace(' ', "%20");
+    assert_eq!(lines.next(), Some(format!("Path={}", encoded).as_str()));
+    let date = lines.next().unwrap();
+    assert!(date.starts_with("DeletionDate="));
+    assert_eq!(date.len(), "DeletionDate=YYYY-MM-DDThh:mm:ss".len());
+    assert_eq!(&date[23..24], "T");
+
+    // Restore the file as a file manager would.
+    fs::rename(&trashed, &path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+
+    // Trashing the same name again does not overwrite the info file left
+    // behind by the restored file.
+    fs_err::remove_file_to_trash(&path).unwrap();
+    assert!(data_home.join("Trash/files/notes 1.txt.2").exists());
+    assert!(data_home
+        .join("Trash/info/notes 1.txt.2.trashinfo")
+        .exists());
+
+    let err = fs_err::remove_file_to_trash(&dir).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    fs::remove_dir_all(&dir).unwrap();
+}